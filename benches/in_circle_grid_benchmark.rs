@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, black_box};
-use spatial_neighbors::{SpatialPartitioner};
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
 use spatial_neighbors::grid::Grid;
 
 pub fn in_circle_radius_benchmark(c: &mut Criterion) {
@@ -108,5 +108,28 @@ pub fn in_circle_count_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, in_circle_count_benchmark, in_circle_radius_benchmark);
+/// the bounds of a thin ring cover whole cells but most of their elements lie in its hole
+pub fn in_ring_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InRingGrid");
+
+    for cell_count in [10, 100].iter() {
+        group.bench_with_input(BenchmarkId::new("Grid", cell_count), cell_count, |b, cell_count| {
+            let mut spatial_hash = black_box(Grid::with_cell_count(-500.0..500.0, -500.0..500.0, (*cell_count, *cell_count)));
+
+            for x in -500..500 {
+                for y in -500..500 {
+                    spatial_hash.insert((x as f64, y as f64), 1);
+                }
+            }
+
+            b.iter(|| {
+                spatial_hash.in_ring((0.0, 0.0), 90.0, 100.0)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, in_circle_count_benchmark, in_circle_radius_benchmark, in_ring_benchmark);
 criterion_main!(benches);
\ No newline at end of file
@@ -1,5 +1,6 @@
 use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, black_box};
-use spatial_neighbors::{SpatialPartitioner};
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::linear_quad_tree::LinearQuadTree;
 use spatial_neighbors::quad_tree::QuadTree;
 
 pub fn in_circle_radius_benchmark(c: &mut Criterion) {
@@ -47,6 +48,22 @@ pub fn in_circle_radius_benchmark(c: &mut Criterion) {
                 quad_tree.in_circle((0.0, 0.0), *radius as f64)
             })
         });
+
+        group.bench_with_input(BenchmarkId::new("LinearQuadTree", radius), radius, |b, radius| {
+            let mut elements = Vec::new();
+
+            for x in -500..500 {
+                for y in -500..500 {
+                    elements.push(((x as f64, y as f64), 1));
+                }
+            }
+
+            let linear_quad_tree = black_box(LinearQuadTree::from_elements(-500.0..500.0, -500.0..500.0, &elements));
+
+            b.iter(|| {
+                linear_quad_tree.in_circle((0.0, 0.0), *radius as f64)
+            })
+        });
     }
 }
 
@@ -101,10 +118,77 @@ pub fn in_circle_count_benchmark(c: &mut Criterion) {
                 quad_tree.in_circle((0.0, 0.0), 10.0)
             })
         });
+
+        group.bench_with_input(BenchmarkId::new("LinearQuadTree", size), size, |b, size| {
+            let size = black_box((*size as f64).sqrt() as i32);
+
+            let mut elements = Vec::new();
+
+            for x in (-size + 1)..size {
+                for y in (-size + 1)..size {
+                    elements.push(((x as f64, y as f64), 1));
+                }
+            }
+
+            let linear_quad_tree = black_box(LinearQuadTree::from_elements(-size as f64..size as f64, -size as f64..size as f64, &elements));
+
+            b.iter(|| {
+                linear_quad_tree.in_circle((0.0, 0.0), 10.0)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// all elements in a small cluster of the bounds, so the tree gets deep before the leaves are reached
+pub fn in_circle_depth_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InCircleDepthQuadTree");
+
+    for size in [40_000, 160_000].iter() {
+        group.bench_with_input(BenchmarkId::new("QuadTree10", size), size, |b, size| {
+            let side = black_box((*size as f64).sqrt() as i32);
+            let center = side as f64 * 0.005;
+
+            let mut quad_tree = black_box(QuadTree::with_capacity(-500.0..500.0, -500.0..500.0, 10));
+
+            for x in 0..side {
+                for y in 0..side {
+                    quad_tree.insert((x as f64 * 0.01, y as f64 * 0.01), 1);
+                }
+            }
+
+            b.iter(|| {
+                quad_tree.in_circle((center, center), 0.5)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// the bounds of a thin ring cover whole nodes but most of their elements lie in its hole
+pub fn in_ring_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InRingQuadTree");
+
+    for capacity in [10, 50].iter() {
+        group.bench_with_input(BenchmarkId::new("QuadTree", capacity), capacity, |b, capacity| {
+            let mut quad_tree = black_box(QuadTree::with_capacity(-500.0..500.0, -500.0..500.0, *capacity));
+
+            for x in -500..500 {
+                for y in -500..500 {
+                    quad_tree.insert((x as f64, y as f64), 1);
+                }
+            }
+
+            b.iter(|| {
+                quad_tree.in_ring((0.0, 0.0), 90.0, 100.0)
+            })
+        });
     }
 
     group.finish();
 }
 
-criterion_group!(benches, in_circle_count_benchmark, in_circle_radius_benchmark);
+criterion_group!(benches, in_circle_count_benchmark, in_circle_radius_benchmark, in_circle_depth_benchmark, in_ring_benchmark);
 criterion_main!(benches);
\ No newline at end of file
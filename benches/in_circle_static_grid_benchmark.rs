@@ -0,0 +1,59 @@
+use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, black_box};
+use spatial_neighbors::SpatialIndex;
+use spatial_neighbors::static_grid::StaticGrid;
+
+fn elements(size: i32) -> Vec<((f64, f64), i32)> {
+    let mut elements = Vec::new();
+
+    for x in (-size + 1)..size {
+        for y in (-size + 1)..size {
+            elements.push(((x as f64, y as f64), 1));
+        }
+    }
+
+    elements
+}
+
+pub fn in_circle_radius_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InCircleRadiusStaticGrid");
+
+    let elements = elements(500);
+
+    for radius in [10, 50, 100].iter() {
+        for cell_count in [10, 100, 1000] {
+            group.bench_with_input(BenchmarkId::new(format!("StaticGrid{}", cell_count), radius), radius, |b, radius| {
+                let static_grid = black_box(StaticGrid::from_elements(-500.0..500.0, -500.0..500.0, (cell_count, cell_count), &elements));
+
+                b.iter(|| {
+                    static_grid.in_circle((0.0, 0.0), *radius as f64)
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+pub fn in_circle_count_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InCircleCountStaticGrid");
+
+    for size in [5_000, 20_000, 50_000].iter() {
+        let size = black_box((*size as f64).sqrt() as i32);
+        let elements = elements(size);
+
+        for cell_count in [10, 100, 1000] {
+            group.bench_with_input(BenchmarkId::new(format!("StaticGrid{}", cell_count), size), &size, |b, size| {
+                let static_grid = black_box(StaticGrid::from_elements(-size as f64..*size as f64, -size as f64..*size as f64, (cell_count, cell_count), &elements));
+
+                b.iter(|| {
+                    static_grid.in_circle((0.0, 0.0), 10.0)
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, in_circle_count_benchmark, in_circle_radius_benchmark);
+criterion_main!(benches);
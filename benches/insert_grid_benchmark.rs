@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, black_box};
-use spatial_neighbors::{SpatialPartitioner};
+use spatial_neighbors::SpatialIndexMut;
 use spatial_neighbors::grid::Grid;
 
 pub fn insert_benchmark(c: &mut Criterion) {
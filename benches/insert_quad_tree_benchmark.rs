@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, black_box};
-use spatial_neighbors::{SpatialPartitioner};
+use spatial_neighbors::SpatialIndexMut;
 use spatial_neighbors::quad_tree::QuadTree;
 
 pub fn insert_benchmark(c: &mut Criterion) {
@@ -52,5 +52,31 @@ pub fn insert_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, insert_benchmark);
+/// a cluster of elements moves through the leaves of a QuadTree with a max_depth, every leaf
+/// grows its block and gets empty again
+pub fn churn_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ChurnQuadTree");
+
+    for size in [100, 1_000].iter() {
+        group.bench_with_input(BenchmarkId::new("QuadTree10", size), size, |b, size| {
+            let mut quad_tree = QuadTree::builder().bounds(0.0..64.0, 0.0..64.0).capacity(10).max_depth(3).build().unwrap();
+            let mut round = 0;
+
+            b.iter(|| {
+                let spot = ((round % 8) as f64 * 8.0 + 4.0, ((round / 8) % 8) as f64 * 8.0 + 4.0);
+                round += 1;
+
+                let handles: Vec<_> = (0..*size).map(|i| quad_tree.insert((spot.0 + (i % 32) as f64 * 0.1, spot.1 + (i / 32) as f64 * 0.1), i)).collect();
+
+                for handle in handles {
+                    black_box(quad_tree.remove(handle));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, insert_benchmark, churn_benchmark);
 criterion_main!(benches);
\ No newline at end of file
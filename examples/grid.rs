@@ -1,6 +1,6 @@
 use colored::Colorize;
 use spatial_neighbors::quad_tree::QuadTree;
-use spatial_neighbors::SpatialPartitioner;
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
 
 const WIDTH: f64 = 10.0;
 const HEIGHT: f64 = 10.0;
@@ -13,7 +13,7 @@ fn main() {
     let mut quad_tree = QuadTree::with_capacity((-WIDTH / 2.0)..(WIDTH / 2.0), (-HEIGHT / 2.0)..(HEIGHT / 2.0), 5);
 
     for (index, element) in elements.iter().enumerate() {
-        quad_tree.insert(element.location, index)
+        quad_tree.insert(element.location, index);
     }
 
     let in_circle = quad_tree.in_circle(POSITION, RADIUS);
@@ -48,7 +48,7 @@ fn generate_elements() -> Vec<Element> {
     elements
 }
 
-fn print_elements(elements: &Vec<Element>) {
+fn print_elements(elements: &[Element]) {
 
     let width = WIDTH as i32;
     let height = HEIGHT as i32;
@@ -58,7 +58,7 @@ fn print_elements(elements: &Vec<Element>) {
             let index = (y + height / 2) * width + (x + width / 2);
 
             if elements.get(index as usize).unwrap().in_circle {
-                print!("{}", format!(" X ").green())
+                print!("{}", " X ".green())
             } else {
                 print!(" * ")
             }
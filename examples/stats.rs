@@ -0,0 +1,83 @@
+//! prints the stats of a Grid and a QuadTree filled with a generated dataset
+//!
+//! cargo run --example stats -- [count] [clustered|uniform] [cell_count] [capacity]
+
+use spatial_neighbors::SpatialIndexMut;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::quad_tree::QuadTree;
+
+const WIDTH: f64 = 1000.0;
+const HEIGHT: f64 = 1000.0;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let count: usize = argument(&args, 0, 100_000);
+    let clustered = args.get(1).is_none_or(|kind| kind != "uniform");
+    let cell_count: usize = argument(&args, 2, 100);
+    let capacity: u16 = argument(&args, 3, 16);
+
+    let mut grid = Grid::with_cell_count(0.0..WIDTH, 0.0..HEIGHT, (cell_count, cell_count));
+    let mut quad_tree = QuadTree::with_capacity(0.0..WIDTH, 0.0..HEIGHT, capacity);
+
+    for (index, position) in generate_positions(count, clustered).into_iter().enumerate() {
+        grid.insert(position, index);
+        quad_tree.insert(position, index);
+    }
+
+    println!("{} {} positions in {} x {}\n", count, if clustered { "clustered" } else { "uniform" }, WIDTH, HEIGHT);
+    println!("{}", grid.stats());
+    println!("{}", quad_tree.stats());
+}
+
+fn argument<T: std::str::FromStr>(args: &[String], index: usize, default: T) -> T {
+    match args.get(index) {
+        Some(arg) => arg.parse().unwrap_or_else(|_| panic!("argument {} '{}' is not valid", index + 1, arg)),
+        None => default,
+    }
+}
+
+/// uniform positions or positions around a few centers, most of the area stays empty
+fn generate_positions(count: usize, clustered: bool) -> Vec<(f64, f64)> {
+    let mut random = Random(42);
+
+    let centers: Vec<(f64, f64)> = (0..8).map(|_| (random.unit() * WIDTH, random.unit() * HEIGHT)).collect();
+
+    (0..count).map(|_| {
+        if !clustered {
+            return (random.unit() * WIDTH, random.unit() * HEIGHT);
+        }
+
+        let center = centers[random.below(centers.len())];
+
+        // the sum of uniform offsets is close to a normal distribution
+        let offset = |random: &mut Random| (0..4).map(|_| random.unit() - 0.5).sum::<f64>() * 40.0;
+        let position = (center.0 + offset(&mut random), center.1 + offset(&mut random));
+
+        (position.0.clamp(0.0, WIDTH.next_down()), position.1.clamp(0.0, HEIGHT.next_down()))
+    }).collect()
+}
+
+/// SplitMix64, enough to spread the positions without a dependency
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, max: usize) -> usize {
+        (self.next() % max as u64) as usize
+    }
+
+    /// in 0.0..1.0
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,27 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors_fuzz::{run, Coordinate, Step};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    x: (Coordinate, Coordinate),
+    y: (Coordinate, Coordinate),
+    cell_count: (u8, u8),
+    steps: Vec<Step>,
+}
+
+fuzz_target!(|input: Input| {
+    let x = input.x.0.value()..input.x.1.value();
+    let y = input.y.0.value()..input.y.1.value();
+
+    // invalid settings are rejected with a ConfigError, which is documented
+    let Ok(mut grid) = Grid::try_with_cell_count(x.clone(), y.clone(), (input.cell_count.0 as usize, input.cell_count.1 as usize)) else {
+        return;
+    };
+
+    // a Grid also finds positions outside of its bounds
+    run(&mut grid, (x, y), true, &input.steps);
+});
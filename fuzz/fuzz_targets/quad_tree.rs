@@ -0,0 +1,34 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use spatial_neighbors::quad_tree::QuadTree;
+use spatial_neighbors_fuzz::{run, Coordinate, Step};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    x: (Coordinate, Coordinate),
+    y: (Coordinate, Coordinate),
+    capacity: u8,
+    max_depth: Option<u8>,
+    steps: Vec<Step>,
+}
+
+fuzz_target!(|input: Input| {
+    let x = input.x.0.value()..input.x.1.value();
+    let y = input.y.0.value()..input.y.1.value();
+
+    let mut builder = QuadTree::builder().bounds(x.clone(), y.clone()).capacity(input.capacity as u16);
+
+    if let Some(max_depth) = input.max_depth {
+        builder = builder.max_depth(max_depth as u32);
+    }
+
+    // invalid settings are rejected with a ConfigError, which is documented
+    let Ok(mut quad_tree) = builder.build() else {
+        return;
+    };
+
+    // a QuadTree also finds positions outside of its bounds
+    run(&mut quad_tree, (x, y), true, &input.steps);
+});
@@ -0,0 +1,139 @@
+//! Operations of the fuzz targets, every structure is compared against a BruteForce after each step
+
+use std::ops::Range;
+use arbitrary::Arbitrary;
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::brute_force::BruteForce;
+use spatial_neighbors::handle::Handle;
+use spatial_neighbors::shape::{Circle, Rect, Shape};
+
+/// A coordinate which is often one of the values structures tend to break on
+#[derive(Debug, Clone, Copy, Arbitrary)]
+pub enum Coordinate {
+    /// in steps of 1/16 so positions often share a cell border or lie on top of each other
+    Small(i16),
+    Any(f64),
+    NaN,
+    Infinity,
+    NegInfinity,
+    Huge(bool),
+}
+
+impl Coordinate {
+    pub fn value(self) -> f64 {
+        match self {
+            Coordinate::Small(value) => value as f64 / 16.0,
+            Coordinate::Any(value) => value,
+            Coordinate::NaN => f64::NAN,
+            Coordinate::Infinity => f64::INFINITY,
+            Coordinate::NegInfinity => f64::NEG_INFINITY,
+            Coordinate::Huge(negative) => if negative { -1e300 } else { 1e300 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Arbitrary)]
+pub enum Step {
+    Insert(Coordinate, Coordinate),
+    /// removes the element at `index % count` of the stored elements
+    Remove(u16),
+    /// moves the element at `index % count` of the stored elements
+    UpdatePosition(u16, Coordinate, Coordinate),
+    /// (x, y, radius)
+    Circle(Coordinate, Coordinate, Coordinate),
+    /// (min_x, max_x, min_y, max_y)
+    Rect(Coordinate, Coordinate, Coordinate, Coordinate),
+    Clear,
+}
+
+/// runs the steps against the structure and a BruteForce and panics if any answer differs
+///
+/// positions outside of the bounds are only inserted if `outside` is true, because `insert` and
+/// `update_position` are documented to panic for them
+pub fn run<T: SpatialIndexMut<usize>>(index: &mut T, bounds: (Range<f64>, Range<f64>), outside: bool, steps: &[Step]) {
+    let mut brute_force = BruteForce::new();
+    let mut stored: Vec<(Handle, Handle)> = Vec::new();
+
+    let inside = |position: (f64, f64)| bounds.0.contains(&position.0) && bounds.1.contains(&position.1);
+
+    for (id, step) in steps.iter().enumerate() {
+        match step {
+            Step::Insert(x, y) => {
+                let position = (x.value(), y.value());
+
+                let handle = if inside(position) {
+                    index.insert(position, id)
+                } else if outside {
+                    index.insert_unchecked(position, id)
+                } else {
+                    continue;
+                };
+
+                stored.push((handle, brute_force.insert(position, id)));
+                assert_same_element(index, &brute_force, stored[stored.len() - 1]);
+            }
+            Step::Remove(i) => {
+                if stored.is_empty() {
+                    continue;
+                }
+
+                let handles = stored.swap_remove(*i as usize % stored.len());
+
+                assert_eq!(index.remove(handles.0), brute_force.remove(handles.1));
+                assert_eq!(index.remove(handles.0), None);
+            }
+            Step::UpdatePosition(i, x, y) => {
+                let position = (x.value(), y.value());
+
+                if stored.is_empty() || !inside(position) {
+                    continue;
+                }
+
+                let handles = stored[*i as usize % stored.len()];
+
+                assert!(index.update_position(handles.0, position));
+                assert!(brute_force.update_position(handles.1, position));
+                assert_same_element(index, &brute_force, handles);
+            }
+            Step::Circle(x, y, radius) => {
+                let radius = radius.value();
+
+                // the squared distances overflow for such radii, so which far away positions count as inside is undefined
+                if (radius * radius).is_infinite() {
+                    continue;
+                }
+
+                assert_same_answer(index, &brute_force, &Circle::new((x.value(), y.value()), radius));
+            }
+            Step::Rect(min_x, max_x, min_y, max_y) => {
+                assert_same_answer(index, &brute_force, &Rect::new(min_x.value()..max_x.value(), min_y.value()..max_y.value()));
+            }
+            Step::Clear => {
+                index.clear();
+                brute_force.clear();
+                stored.clear();
+            }
+        }
+
+        assert_eq!(index.count(), brute_force.count());
+    }
+}
+
+fn assert_same_element(index: &impl SpatialIndexMut<usize>, brute_force: &BruteForce<usize>, handles: (Handle, Handle)) {
+    // NaN is not equal to itself, so the positions are compared by their bits
+    let bits = |element: Option<((f64, f64), usize)>| element.map(|((x, y), id)| ((x.to_bits(), y.to_bits()), id));
+
+    assert_eq!(bits(index.get(handles.0)), bits(brute_force.get(handles.1)));
+}
+
+fn assert_same_answer<S: Shape + std::fmt::Debug>(index: &impl SpatialIndex<usize>, brute_force: &BruteForce<usize>, shape: &S) {
+    let mut found = index.query(shape);
+    let mut expected = brute_force.query(shape);
+
+    found.sort();
+    expected.sort();
+
+    assert_eq!(found, expected, "query {:?}", shape);
+    assert_eq!(index.query_count(shape), expected.len(), "query_count {:?}", shape);
+    assert_eq!(index.query_any(shape), !expected.is_empty(), "query_any {:?}", shape);
+}
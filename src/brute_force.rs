@@ -0,0 +1,139 @@
+use crate::{SpatialIndex, SpatialIndexMut};
+use crate::handle::{Handle, Slots};
+use crate::shape::Shape;
+
+/// Stores all elements in one `Vec` and tests every one of them for each query
+///
+/// Far too slow for real workloads, but simple enough to be obviously right. So it serves as the
+/// reference the other structures are compared against, e.g. with [testing](crate::testing)
+///
+/// There are no bounds, every position can be inserted
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "BruteForceSnapshot<Data>"))]
+pub struct BruteForce<Data: Copy> {
+    elements: Vec<((f64, f64), Data)>,
+    /// the handle of each element at the same index in `elements`
+    handles: Vec<Handle>,
+    /// the index in `elements` of each element
+    slots: Slots<usize>,
+}
+
+impl<Data: Copy> BruteForce<Data> {
+    /// create an empty BruteForce
+    pub fn new() -> BruteForce<Data> {
+        BruteForce {
+            elements: Vec::new(),
+            handles: Vec::new(),
+            slots: Slots::new(),
+        }
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: &[((f64, f64), Data)] all elements in no particular order
+    ///
+    pub fn elements(&self) -> &[((f64, f64), Data)] {
+        &self.elements
+    }
+}
+
+impl<Data: Copy> Default for BruteForce<Data> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Data: Copy> SpatialIndex<Data> for BruteForce<Data> {
+    fn count(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        self.elements.iter().filter(|element| shape.contains(element.0)).map(|element| element.1).collect()
+    }
+
+    fn query_count<S: Shape>(&self, shape: &S) -> usize {
+        self.elements.iter().filter(|element| shape.contains(element.0)).count()
+    }
+
+    fn query_any<S: Shape>(&self, shape: &S) -> bool {
+        self.elements.iter().any(|element| shape.contains(element.0))
+    }
+}
+
+impl<Data: Copy> SpatialIndexMut<Data> for BruteForce<Data> {
+    fn insert(&mut self, position: (f64, f64), data: Data) -> Handle {
+        self.insert_unchecked(position, data)
+    }
+
+    fn insert_unchecked(&mut self, position: (f64, f64), data: Data) -> Handle {
+        let handle = self.slots.insert(self.elements.len());
+
+        self.elements.push((position, data));
+        self.handles.push(handle);
+
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<((f64, f64), Data)> {
+        Some(self.elements[self.slots.get(handle)?])
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<Data> {
+        let index = self.slots.remove(handle)?;
+
+        let element = self.elements.swap_remove(index);
+        self.handles.swap_remove(index);
+
+        if let Some(moved) = self.handles.get(index) {
+            self.slots.set(*moved, index);
+        }
+
+        Some(element.1)
+    }
+
+    fn update_position(&mut self, handle: Handle, position: (f64, f64)) -> bool {
+        let Some(index) = self.slots.get(handle) else {
+            return false;
+        };
+
+        self.elements[index].0 = position;
+
+        true
+    }
+
+    fn clear(&mut self) {
+        self.elements.clear();
+        self.handles.clear();
+        self.slots.clear();
+    }
+}
+
+/// the fields of a [BruteForce] as they are deserialized, [`BruteForce::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BruteForceSnapshot<Data> {
+    elements: Vec<((f64, f64), Data)>,
+    handles: Vec<Handle>,
+    slots: Slots<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> TryFrom<BruteForceSnapshot<Data>> for BruteForce<Data> {
+    type Error = &'static str;
+
+    fn try_from(snapshot: BruteForceSnapshot<Data>) -> Result<Self, Self::Error> {
+        if snapshot.handles.len() != snapshot.elements.len() {
+            return Err("elements and their handles differ in length");
+        }
+
+        snapshot.slots.check(snapshot.handles.iter().enumerate().map(|(index, handle)| (*handle, index)))?;
+
+        Ok(BruteForce {
+            elements: snapshot.elements,
+            handles: snapshot.handles,
+            slots: snapshot.slots,
+        })
+    }
+}
@@ -0,0 +1,703 @@
+use std::ops::ControlFlow;
+use crate::config::ConfigError;
+use crate::handle::{Handle, Slots};
+use crate::shape::Shape;
+
+/// marks a missing parent or child
+const NULL: usize = usize::MAX;
+
+/// the (min_x, min_y) and (max_x, max_y) corner of a box
+type Bounds = ((f64, f64), (f64, f64));
+
+/// A dynamic bounding volume hierarchy of boxes e.g. the broadphase of a physics engine
+///
+/// Every box is a leaf of a binary tree and each branch covers its two children. Leaves store the
+/// box enlarged by a margin, so a box which moves only a little stays in its leaf and
+/// [`Bvh::move_box()`] does not have to touch the tree. Inserting, removing and moving boxes is
+/// O(log n), branches are rotated to keep the tree balanced
+///
+/// Boxes are passed as their (min_x, min_y) and (max_x, max_y) corners and include their borders
+///
+/// A Bvh is `Send` and `Sync` whenever `Data` is, so it can be queried from many threads at once
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "BvhSnapshot<Data>"))]
+pub struct Bvh<Data: Copy> {
+    /// leaves and branches, removed nodes are reused
+    nodes: Vec<Node<Data>>,
+    free: Vec<usize>,
+    root: usize,
+    /// the index in `nodes` of the leaf of each box, leaves never move to another index
+    slots: Slots<usize>,
+
+    count: usize,
+
+    /// how far the box of each leaf reaches past its box on every side
+    margin: f64,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Node<Data> {
+    /// the enlarged box of a leaf or the union of both children of a branch
+    bounds: Bounds,
+    parent: usize,
+    /// both are NULL for leaves
+    children: [usize; 2],
+    /// 0 for leaves, the longest path to a leaf for branches
+    height: u32,
+    /// the exact box and data of a leaf
+    item: Option<(Bounds, Data)>,
+}
+
+impl<Data: Copy> Bvh<Data> {
+    /// creates an empty Bvh, boxes can be anywhere
+    ///
+    /// # Arguments
+    ///
+    /// * `margin`: how far the stored box of each leaf reaches past its box, boxes which move less
+    ///   than this do not change the tree
+    ///
+    /// panics if the margin is not valid, [`Bvh::try_new()`] returns the reason instead
+    ///
+    pub fn new(margin: f64) -> Bvh<Data> {
+        Bvh::try_new(margin).unwrap_or_else(|error| panic!("tried to create a Bvh from invalid settings: {}", error))
+    }
+
+    /// like [`Bvh::new()`] but returns a ConfigError for a negative or infinite margin
+    ///
+    /// # Arguments
+    ///
+    /// * `margin`: how far the stored box of each leaf reaches past its box, at least 0
+    ///
+    /// returns: Result<Bvh<Data>, ConfigError>
+    ///
+    pub fn try_new(margin: f64) -> Result<Bvh<Data>, ConfigError> {
+        // NaN fails the comparison as well
+        if !(margin >= 0.0 && margin.is_finite()) {
+            return Err(ConfigError::InvalidMargin(margin));
+        }
+
+        Ok(Bvh {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: NULL,
+            slots: Slots::new(),
+            count: 0,
+            margin,
+        })
+    }
+
+    /// inserts a box
+    ///
+    /// # Arguments
+    ///
+    /// * `min`: (min_x, min_y) corner of the box
+    /// * `max`: (max_x, max_y) corner of the box
+    /// * `data`: the DataValue which will be return when queued
+    ///
+    /// returns: Handle to move or remove the box later
+    ///
+    pub fn insert(&mut self, min: (f64, f64), max: (f64, f64), data: Data) -> Handle {
+        let leaf = self.allocate(Node {
+            bounds: self.fatten((min, max)),
+            parent: NULL,
+            children: [NULL; 2],
+            height: 0,
+            item: Some(((min, max), data)),
+        });
+
+        self.insert_leaf(leaf);
+        self.count += 1;
+
+        self.slots.insert(leaf)
+    }
+
+    /// removes a box
+    ///
+    /// # Arguments
+    ///
+    /// * `handle`: the handle returned by [`Bvh::insert()`]
+    ///
+    /// returns: Option<Data> None if the box was already removed
+    ///
+    pub fn remove(&mut self, handle: Handle) -> Option<Data> {
+        let leaf = self.slots.remove(handle)?;
+        let data = self.nodes[leaf].item?.1;
+
+        self.remove_leaf(leaf);
+        self.release(leaf);
+        self.count -= 1;
+
+        Some(data)
+    }
+
+    /// changes the box behind `handle`, the tree only changes if the box leaves the enlarged box of its leaf
+    ///
+    /// # Arguments
+    ///
+    /// * `handle`: the handle returned by [`Bvh::insert()`]
+    /// * `min`: new (min_x, min_y) corner of the box
+    /// * `max`: new (max_x, max_y) corner of the box
+    ///
+    /// returns: Option<bool> true if the leaf had to be moved in the tree, None if the box was removed
+    ///
+    pub fn move_box(&mut self, handle: Handle, min: (f64, f64), max: (f64, f64)) -> Option<bool> {
+        let leaf = self.slots.get(handle)?;
+
+        if let Some(item) = &mut self.nodes[leaf].item {
+            item.0 = (min, max);
+        }
+
+        if covers(self.nodes[leaf].bounds, (min, max)) {
+            return Some(false);
+        }
+
+        self.remove_leaf(leaf);
+        self.nodes[leaf].bounds = self.fatten((min, max));
+        self.insert_leaf(leaf);
+
+        Some(true)
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// * `handle`: the handle returned by [`Bvh::insert()`]
+    ///
+    /// returns: Option<(((f64, f64), (f64, f64)), Data)> the (min, max) corners and data of the box, None if it was removed
+    ///
+    pub fn get(&self, handle: Handle) -> Option<(Bounds, Data)> {
+        self.nodes[self.slots.get(handle)?].item
+    }
+
+    /// returns the count of boxes which are currently stored
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// returns the longest path from the root to a leaf, 0 if there is at most one box
+    pub fn height(&self) -> u32 {
+        if self.root == NULL {
+            return 0;
+        }
+
+        self.nodes[self.root].height
+    }
+
+    /// removes all boxes, their handles do not refer to anything afterwards
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.slots.clear();
+
+        self.root = NULL;
+        self.count = 0;
+    }
+
+    /// gets all DataValues whose box intersects the shape as far as [`Shape::intersects_box()`] can tell
+    ///
+    /// # Arguments
+    ///
+    /// * `shape`: the area which gets searched
+    ///
+    /// returns: Vec<Data>
+    ///
+    pub fn query_intersecting<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let _ = self.search(self.root, &|bounds| shape.intersects_box(bounds.0, bounds.1), &mut |item| {
+            data.push(item.1);
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    /// counts the DataValues whose box intersects the shape, more info here [`Bvh::query_intersecting()`]
+    pub fn count_intersecting<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let _ = self.search(self.root, &|bounds| shape.intersects_box(bounds.0, bounds.1), &mut |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    /// checks if there is any box which intersects the shape, more info here [`Bvh::query_intersecting()`]
+    pub fn any_intersecting<S: Shape>(&self, shape: &S) -> bool {
+        self.search(self.root, &|bounds| shape.intersects_box(bounds.0, bounds.1), &mut |_| ControlFlow::Break(())).is_break()
+    }
+
+    /// gets every pair of boxes which overlap or touch, each pair is returned once
+    ///
+    /// # Arguments
+    ///
+    /// returns: Vec<(Data, Data)>
+    ///
+    pub fn overlap_pairs(&self) -> Vec<(Data, Data)> {
+        let mut pairs = Vec::new();
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let Some((bounds, data)) = node.item else {
+                continue;
+            };
+
+            // the tree is searched with the larger box, so only the partners behind it are taken
+            let _ = self.search_leaves(self.root, &|other| intersects(other, bounds), &mut |other| {
+                if other > index && self.nodes[other].item.is_some_and(|item| intersects(item.0, bounds)) {
+                    pairs.push((data, self.nodes[other].item.unwrap().1));
+                }
+
+                ControlFlow::Continue(())
+            });
+        }
+
+        pairs
+    }
+
+    /// gets all DataValues whose box is hit by the ray from `origin` to `origin + direction * max_distance`
+    ///
+    /// # Arguments
+    ///
+    /// * `origin`: the position the ray starts at
+    /// * `direction`: the direction of the ray, distances are measured in its length
+    /// * `max_distance`: how far the ray reaches
+    ///
+    /// returns: Vec<Data> in no particular order
+    ///
+    pub fn query_ray(&self, origin: (f64, f64), direction: (f64, f64), max_distance: f64) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let _ = self.search(self.root, &|bounds| ray_distance(origin, direction, max_distance, bounds).is_some(), &mut |item| {
+            data.push(item.1);
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    /// gets the DataValue whose box is hit first by the ray, more info here [`Bvh::query_ray()`]
+    ///
+    /// returns: Option<(Data, f64)> the DataValue and the distance at which the ray enters its box,
+    /// 0 if the origin lies in the box
+    ///
+    pub fn ray_cast(&self, origin: (f64, f64), direction: (f64, f64), max_distance: f64) -> Option<(Data, f64)> {
+        let mut closest = None;
+        let mut max_distance = max_distance;
+
+        let mut stack = vec![self.root];
+
+        while let Some(index) = stack.pop() {
+            if index == NULL {
+                continue;
+            }
+
+            let node = &self.nodes[index];
+
+            if ray_distance(origin, direction, max_distance, node.bounds).is_none() {
+                continue;
+            }
+
+            match node.item {
+                Some((bounds, data)) => {
+                    // every later box has to be hit before this one to matter
+                    if let Some(distance) = ray_distance(origin, direction, max_distance, bounds) {
+                        closest = Some((data, distance));
+                        max_distance = distance;
+                    }
+                }
+                None => stack.extend(node.children),
+            }
+        }
+
+        closest
+    }
+
+    fn fatten(&self, bounds: Bounds) -> Bounds {
+        ((bounds.0.0 - self.margin, bounds.0.1 - self.margin), (bounds.1.0 + self.margin, bounds.1.1 + self.margin))
+    }
+
+    fn allocate(&mut self, node: Node<Data>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+
+                index
+            }
+            None => {
+                self.nodes.push(node);
+
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// frees the node so [`Bvh::allocate()`] can reuse it
+    fn release(&mut self, index: usize) {
+        let node = &mut self.nodes[index];
+
+        node.item = None;
+        node.children = [NULL; 2];
+
+        self.free.push(index);
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        if self.root == NULL {
+            self.root = leaf;
+            self.nodes[leaf].parent = NULL;
+            return;
+        }
+
+        let bounds = self.nodes[leaf].bounds;
+        let sibling = self.choose_sibling(bounds);
+
+        let old_parent = self.nodes[sibling].parent;
+        let parent = self.allocate(Node {
+            bounds: union(bounds, self.nodes[sibling].bounds),
+            parent: old_parent,
+            children: [sibling, leaf],
+            height: self.nodes[sibling].height + 1,
+            item: None,
+        });
+
+        self.nodes[sibling].parent = parent;
+        self.nodes[leaf].parent = parent;
+
+        if old_parent == NULL {
+            self.root = parent;
+        } else {
+            self.replace_child(old_parent, sibling, parent);
+        }
+
+        self.refit_ancestors(old_parent);
+    }
+
+    /// goes down to the node which grows the tree the least when it gets `bounds` as sibling
+    fn choose_sibling(&self, bounds: Bounds) -> usize {
+        let mut index = self.root;
+
+        while self.nodes[index].item.is_none() {
+            let node = &self.nodes[index];
+            let combined = margin(union(node.bounds, bounds));
+
+            // the new branch replaces this node, all its ancestors grow anyway
+            let cost = 2.0 * combined;
+            let inherited = 2.0 * (combined - margin(node.bounds));
+
+            let child_cost = |child: usize| {
+                let child = &self.nodes[child];
+                let grown = margin(union(child.bounds, bounds));
+
+                match child.item {
+                    Some(_) => grown + inherited,
+                    None => grown - margin(child.bounds) + inherited,
+                }
+            };
+
+            let [first, second] = node.children;
+            let (first_cost, second_cost) = (child_cost(first), child_cost(second));
+
+            if cost < first_cost && cost < second_cost {
+                break;
+            }
+
+            index = if first_cost < second_cost { first } else { second };
+        }
+
+        index
+    }
+
+    /// takes the leaf out of the tree and frees its parent, the leaf itself is kept
+    fn remove_leaf(&mut self, leaf: usize) {
+        if leaf == self.root {
+            self.root = NULL;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent;
+        let grandparent = self.nodes[parent].parent;
+        let sibling = self.sibling(parent, leaf);
+
+        self.nodes[sibling].parent = grandparent;
+
+        if grandparent == NULL {
+            self.root = sibling;
+        } else {
+            self.replace_child(grandparent, parent, sibling);
+        }
+
+        self.release(parent);
+        self.refit_ancestors(grandparent);
+    }
+
+    /// updates the boxes and heights from `index` up to the root and rotates where it is unbalanced
+    fn refit_ancestors(&mut self, mut index: usize) {
+        while index != NULL {
+            self.refit(index);
+            index = self.balance(index);
+            index = self.nodes[index].parent;
+        }
+    }
+
+    fn refit(&mut self, index: usize) {
+        let [first, second] = self.nodes[index].children;
+
+        self.nodes[index].bounds = union(self.nodes[first].bounds, self.nodes[second].bounds);
+        self.nodes[index].height = 1 + self.nodes[first].height.max(self.nodes[second].height);
+    }
+
+    /// rotates the taller child up if the heights of the children differ by more than one
+    ///
+    /// returns: usize the node which now takes the place of `index`
+    ///
+    fn balance(&mut self, index: usize) -> usize {
+        let [first, second] = self.nodes[index].children;
+        let (first_height, second_height) = (self.nodes[first].height, self.nodes[second].height);
+
+        if second_height > first_height + 1 {
+            return self.rotate(index, 1);
+        }
+
+        if first_height > second_height + 1 {
+            return self.rotate(index, 0);
+        }
+
+        index
+    }
+
+    /// moves the child at `slot` into the place of `index`, `index` keeps the shorter grandchild
+    fn rotate(&mut self, index: usize, slot: usize) -> usize {
+        let up = self.nodes[index].children[slot];
+        let parent = self.nodes[index].parent;
+        let [first, second] = self.nodes[up].children;
+
+        let (keep, give) = if self.nodes[first].height > self.nodes[second].height {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        self.nodes[up].parent = parent;
+        self.nodes[up].children = [index, keep];
+
+        self.nodes[index].parent = up;
+        self.nodes[index].children[slot] = give;
+        self.nodes[give].parent = index;
+
+        if parent == NULL {
+            self.root = up;
+        } else {
+            self.replace_child(parent, index, up);
+        }
+
+        self.refit(index);
+        self.refit(up);
+
+        up
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        let children = &mut self.nodes[parent].children;
+        let slot = if children[0] == old { 0 } else { 1 };
+
+        children[slot] = new;
+    }
+
+    fn sibling(&self, parent: usize, child: usize) -> usize {
+        let [first, second] = self.nodes[parent].children;
+
+        if first == child { second } else { first }
+    }
+
+    /// visits the exact box and data of every leaf whose boxes are accepted by `test`
+    fn search(&self, index: usize, test: &impl Fn(Bounds) -> bool, visit: &mut impl FnMut((Bounds, Data)) -> ControlFlow<()>) -> ControlFlow<()> {
+        self.search_leaves(index, test, &mut |leaf| {
+            let item = self.nodes[leaf].item.unwrap();
+
+            if test(item.0) {
+                visit(item)?;
+            }
+
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// visits the index of every leaf whose enlarged box and ancestors are accepted by `test`
+    fn search_leaves(&self, index: usize, test: &impl Fn(Bounds) -> bool, visit: &mut impl FnMut(usize) -> ControlFlow<()>) -> ControlFlow<()> {
+        if index == NULL {
+            return ControlFlow::Continue(());
+        }
+
+        let node = &self.nodes[index];
+
+        if !test(node.bounds) {
+            return ControlFlow::Continue(());
+        }
+
+        if node.item.is_some() {
+            return visit(index);
+        }
+
+        self.search_leaves(node.children[0], test, visit)?;
+        self.search_leaves(node.children[1], test, visit)
+    }
+}
+
+/// the fields of a [Bvh] as they are deserialized, [`Bvh::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BvhSnapshot<Data> {
+    nodes: Vec<Node<Data>>,
+    free: Vec<usize>,
+    root: usize,
+    slots: Slots<usize>,
+    count: usize,
+    margin: f64,
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> TryFrom<BvhSnapshot<Data>> for Bvh<Data> {
+    type Error = &'static str;
+
+    fn try_from(snapshot: BvhSnapshot<Data>) -> Result<Self, Self::Error> {
+        let bvh = Bvh {
+            nodes: snapshot.nodes,
+            free: snapshot.free,
+            root: snapshot.root,
+            slots: snapshot.slots,
+            count: snapshot.count,
+            margin: snapshot.margin,
+        };
+
+        bvh.validate()?;
+
+        Ok(bvh)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> Bvh<Data> {
+    /// checks everything the other methods rely on, so a corrupted snapshot can not make them panic
+    /// or miss boxes
+    fn validate(&self) -> Result<(), &'static str> {
+        if !(self.margin >= 0.0 && self.margin.is_finite()) {
+            return Err("margin is not valid");
+        }
+
+        // every node is either free or reached exactly once from the root
+        let mut reached = vec![false; self.nodes.len()];
+        let mut leaves = vec![false; self.nodes.len()];
+        let mut reach = |index: usize| match reached.get_mut(index) {
+            Some(reached) if !*reached => {
+                *reached = true;
+                true
+            }
+            _ => false,
+        };
+
+        if !self.free.iter().all(|index| reach(*index)) {
+            return Err("free node list is not valid");
+        }
+
+        let mut stack = if self.root == NULL { Vec::new() } else { vec![(self.root, NULL)] };
+
+        while let Some((index, parent)) = stack.pop() {
+            if !reach(index) {
+                return Err("node points to invalid children");
+            }
+
+            let node = &self.nodes[index];
+
+            if node.parent != parent {
+                return Err("node points to the wrong parent");
+            }
+
+            if let Some(item) = node.item {
+                if node.children != [NULL; 2] || node.height != 0 || !covers(node.bounds, item.0) {
+                    return Err("leaf does not cover its box");
+                }
+
+                leaves[index] = true;
+                continue;
+            }
+
+            let [first, second] = node.children;
+            let (Some(first_node), Some(second_node)) = (self.nodes.get(first), self.nodes.get(second)) else {
+                return Err("node points to invalid children");
+            };
+
+            if node.height != 1 + first_node.height.max(second_node.height) || !covers(node.bounds, union(first_node.bounds, second_node.bounds)) {
+                return Err("branch does not cover its children");
+            }
+
+            stack.extend([(first, index), (second, index)]);
+        }
+
+        if reached.contains(&false) {
+            return Err("node is not part of the tree");
+        }
+
+        if leaves.iter().filter(|leaf| **leaf).count() != self.count {
+            return Err("count does not match the leaves");
+        }
+
+        // each leaf is the location of exactly one slot
+        for leaf in self.slots.locations() {
+            match leaves.get_mut(leaf) {
+                Some(leaf) if *leaf => *leaf = false,
+                _ => return Err("slot does not point to a leaf"),
+            }
+        }
+
+        if leaves.contains(&true) {
+            return Err("leaf has no slot");
+        }
+
+        Ok(())
+    }
+}
+
+fn union(a: Bounds, b: Bounds) -> Bounds {
+    ((a.0.0.min(b.0.0), a.0.1.min(b.0.1)), (a.1.0.max(b.1.0), a.1.1.max(b.1.1)))
+}
+
+/// true if `inner` lies completely inside `outer`
+fn covers(outer: Bounds, inner: Bounds) -> bool {
+    outer.0.0 <= inner.0.0 && outer.0.1 <= inner.0.1 && outer.1.0 >= inner.1.0 && outer.1.1 >= inner.1.1
+}
+
+fn intersects(a: Bounds, b: Bounds) -> bool {
+    a.0.0 <= b.1.0 && b.0.0 <= a.1.0 && a.0.1 <= b.1.1 && b.0.1 <= a.1.1
+}
+
+fn margin(bounds: Bounds) -> f64 {
+    (bounds.1.0 - bounds.0.0) + (bounds.1.1 - bounds.0.1)
+}
+
+/// the distance along the ray at which it enters the box, None if it misses the box
+fn ray_distance(origin: (f64, f64), direction: (f64, f64), max_distance: f64, bounds: Bounds) -> Option<f64> {
+    let mut enter = 0.0f64;
+    let mut exit = max_distance;
+
+    for (origin, direction, min, max) in [(origin.0, direction.0, bounds.0.0, bounds.1.0), (origin.1, direction.1, bounds.0.1, bounds.1.1)] {
+        if direction == 0.0 {
+            // a ray parallel to the slab either runs inside of it or misses it
+            if origin < min || origin > max {
+                return None;
+            }
+
+            continue;
+        }
+
+        let first = (min - origin) / direction;
+        let second = (max - origin) / direction;
+
+        enter = enter.max(first.min(second));
+        exit = exit.min(first.max(second));
+    }
+
+    (enter <= exit).then_some(enter)
+}
@@ -0,0 +1,97 @@
+//! Errors of the fallible constructors e.g. [`Grid::try_with_cell_count()`](crate::grid::Grid::try_with_cell_count) and the builders e.g. [`QuadTree::builder()`](crate::quad_tree::QuadTree::builder)
+
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// Why a structure could not be built from its settings
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// the builder was built before the bounds were set
+    MissingBounds,
+    /// the range of an axis is empty, reversed or not finite
+    InvalidRange { axis: Axis, range: Range<f64> },
+    /// one of the axes has no cells
+    ZeroCellCount((usize, usize)),
+    /// count_x * count_y does not fit into a usize
+    TooManyCells((usize, usize)),
+    /// one of the cell sizes is zero, negative, not finite or so small that the cells can not be counted
+    InvalidCellSize((f64, f64)),
+    /// a node can not hold a single element
+    ZeroCapacity,
+    /// one of the weights of a [Weighted](crate::metric::Weighted) metric is zero, negative or not finite
+    InvalidWeight((f64, f64)),
+    /// the margin of a [Bvh](crate::bvh::Bvh) is negative or not finite
+    InvalidMargin(f64),
+}
+
+/// An axis of the area in which data can be inserted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+impl Display for Axis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Axis::X => write!(f, "x"),
+            Axis::Y => write!(f, "y"),
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::MissingBounds => write!(f, "the bounds were not set"),
+            ConfigError::InvalidRange { axis, range } => {
+                let reason = if range.start == range.end {
+                    "is empty"
+                } else if range.start > range.end {
+                    "is reversed, its start has to lie below its end"
+                } else {
+                    "is not finite"
+                };
+
+                write!(f, "the {} range {:?} {}", axis, range, reason)
+            }
+            ConfigError::ZeroCellCount(cell_count) => {
+                let axis = if cell_count.0 == 0 { Axis::X } else { Axis::Y };
+
+                write!(f, "the cell_count {:?} has no cells along {}", cell_count, axis)
+            }
+            ConfigError::TooManyCells(cell_count) => write!(f, "the cell_count {:?} has more cells than fit into memory", cell_count),
+            ConfigError::InvalidCellSize(cell_size) => write!(f, "the cell_size {:?} is not a positive finite size", cell_size),
+            ConfigError::ZeroCapacity => write!(f, "the capacity has to be at least 1"),
+            ConfigError::InvalidWeight(weight) => write!(f, "the weights {:?} are not positive and finite", weight),
+            ConfigError::InvalidMargin(margin) => write!(f, "the margin {:?} is not a finite size of at least 0", margin),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// the start of both ranges has to lie below their end and their extent has to be finite
+pub(crate) fn check_bounds(x: &Range<f64>, y: &Range<f64>) -> Result<(), ConfigError> {
+    for (axis, range) in [(Axis::X, x), (Axis::Y, y)] {
+        // NaN fails the comparison as well
+        if !(range.start < range.end && (range.end - range.start).is_finite()) {
+            return Err(ConfigError::InvalidRange { axis, range: range.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+/// both axes need at least one cell and the cells have to be countable
+pub(crate) fn check_cell_count(cell_count: (usize, usize)) -> Result<(), ConfigError> {
+    if cell_count.0 == 0 || cell_count.1 == 0 {
+        return Err(ConfigError::ZeroCellCount(cell_count));
+    }
+
+    if cell_count.0.checked_mul(cell_count.1).is_none() {
+        return Err(ConfigError::TooManyCells(cell_count));
+    }
+
+    Ok(())
+}
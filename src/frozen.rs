@@ -0,0 +1,1016 @@
+//! A flat, versioned binary format for [Grid] and [QuadTree] which can be queried without deserialising
+//!
+//! The views only borrow the bytes, so they can be used directly on a memory-mapped file
+//!
+//! ```
+//! use spatial_neighbors::frozen::FrozenQuadTree;
+//! use spatial_neighbors::quad_tree::QuadTree;
+//! use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+//!
+//! let mut quad_tree = QuadTree::with_capacity(0.0..10.0, 0.0..10.0, 4);
+//! quad_tree.insert((1.0, 2.0), 7u32);
+//!
+//! let bytes = quad_tree.to_frozen_bytes();
+//! let frozen = FrozenQuadTree::<u32>::from_bytes(&bytes).unwrap();
+//!
+//! assert_eq!(frozen.in_circle((1.0, 1.0), 1.5), vec![7]);
+//! ```
+//!
+//! # Layout
+//!
+//! All values are little endian
+//!
+//! | header | size |
+//! |---|---|
+//! | [MAGIC] | 4 |
+//! | [VERSION] | 2 |
+//! | kind (1 QuadTree, 2 Grid) | 2 |
+//! | size of each Data | 4 |
+//! | min_x, max_x, min_y, max_y as f64 | 32 |
+//!
+//! A QuadTree continues with its capacity (u64), the node count (u64), the element count (u64),
+//! the nodes and the elements. Nodes are stored breadth first so the four children of a node are
+//! next to each other. Elements are stored depth first so the elements of a node and all of its
+//! children form one range
+//!
+//! | node | size |
+//! |---|---|
+//! | center, size as f64 | 32 |
+//! | index of the first element (u64) | 8 |
+//! | count of elements in the node (u64) | 8 |
+//! | count of elements in the node and all of its children (u64) | 8 |
+//! | index of the first child, 0 without children (u64) | 8 |
+//!
+//! A Grid continues with its cell count (2 u64), its cell size (2 f64), the element count (u64),
+//! the index of the first element of each cell plus the element count (u64 each) and the elements
+//!
+//! Each element is its position as two f64 followed by its [FrozenData]
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::ops::{ControlFlow, Range};
+use crate::grid::Grid;
+use crate::quad_tree::{QuadTree, QuadTreeNode};
+use crate::shape::Shape;
+use crate::util::{border_box, child_sides, is_edge_cell, nearest_in_box, CellAxis, BORDER};
+use crate::SpatialIndex;
+
+/// the first bytes of every file
+pub const MAGIC: [u8; 4] = *b"SNBF";
+
+/// the version of the layout which gets written and is the only one which can be read
+pub const VERSION: u16 = 1;
+
+const KIND_QUAD_TREE: u16 = 1;
+const KIND_GRID: u16 = 2;
+
+const HEADER_SIZE: usize = 44;
+const QUAD_TREE_HEADER_SIZE: usize = HEADER_SIZE + 24;
+const GRID_HEADER_SIZE: usize = HEADER_SIZE + 40;
+const NODE_SIZE: usize = 64;
+
+/// Data which can be stored with a fixed size in the binary format
+pub trait FrozenData: Copy {
+    /// the count of bytes written for each value
+    const SIZE: usize;
+
+    /// writes the value into `bytes` which is exactly `SIZE` long
+    fn write(&self, bytes: &mut [u8]);
+
+    /// reads a value from `bytes` which is exactly `SIZE` long
+    fn read(bytes: &[u8]) -> Self;
+}
+
+macro_rules! frozen_data {
+    ($($data:ty),*) => {
+        $(
+            impl FrozenData for $data {
+                const SIZE: usize = std::mem::size_of::<$data>();
+
+                fn write(&self, bytes: &mut [u8]) {
+                    bytes.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn read(bytes: &[u8]) -> Self {
+                    <$data>::from_le_bytes(bytes.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+frozen_data!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// stored as u64 so files do not depend on the platform
+impl FrozenData for usize {
+    const SIZE: usize = 8;
+
+    fn write(&self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&(*self as u64).to_le_bytes());
+    }
+
+    fn read(bytes: &[u8]) -> Self {
+        u64::from_le_bytes(bytes.try_into().unwrap()) as usize
+    }
+}
+
+/// Why bytes could not be read as a frozen structure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrozenError {
+    /// the bytes end before all announced values were read
+    UnexpectedEnd,
+    /// the bytes do not start with [MAGIC]
+    BadMagic,
+    /// the bytes were written with a different [VERSION]
+    UnsupportedVersion(u16),
+    /// the bytes contain a different structure e.g. a Grid read as FrozenQuadTree
+    WrongKind(u16),
+    /// the stored Data has a different size than the requested one
+    DataSize { expected: usize, found: usize },
+    /// the values contradict each other
+    Corrupt(&'static str),
+}
+
+impl Display for FrozenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrozenError::UnexpectedEnd => write!(f, "unexpected end of frozen data"),
+            FrozenError::BadMagic => write!(f, "frozen data does not start with the magic bytes"),
+            FrozenError::UnsupportedVersion(version) => write!(f, "unsupported frozen data version {} expected {}", version, VERSION),
+            FrozenError::WrongKind(kind) => write!(f, "frozen data contains a different structure (kind {})", kind),
+            FrozenError::DataSize { expected, found } => write!(f, "frozen data stores {} bytes per Data but {} were expected", found, expected),
+            FrozenError::Corrupt(reason) => write!(f, "corrupt frozen data: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for FrozenError {}
+
+impl<Data: FrozenData> QuadTree<Data> {
+    /// writes the QuadTree in the binary format, which can be read with [FrozenQuadTree]
+    pub fn write_frozen<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.to_frozen_bytes())
+    }
+
+    /// returns: Vec<u8> the QuadTree in the binary format, which can be read with [FrozenQuadTree]
+    pub fn to_frozen_bytes(&self) -> Vec<u8> {
+        // breadth first so children are next to each other, the first element of each node
+        // follows the depth first order of the elements
+        let mut nodes: Vec<(QuadTreeNode<Data>, usize)> = vec![(self.node(), 0)];
+        let mut index = 0;
+
+        while index < nodes.len() {
+            let (node, first) = nodes[index];
+
+            if let Some(children) = node.nodes() {
+                let mut first = first + node.data().len();
+
+                for child in children {
+                    nodes.push((child, first));
+                    first += child.count();
+                }
+            }
+
+            index += 1;
+        }
+
+        let mut bytes = header(KIND_QUAD_TREE, Data::SIZE, self.bounds());
+
+        bytes.extend((self.capacity() as u64).to_le_bytes());
+        bytes.extend((nodes.len() as u64).to_le_bytes());
+        bytes.extend((self.count() as u64).to_le_bytes());
+
+        let mut children = 1;
+
+        for (node, first) in &nodes {
+            bytes.extend(node.center().0.to_le_bytes());
+            bytes.extend(node.center().1.to_le_bytes());
+            bytes.extend(node.size().0.to_le_bytes());
+            bytes.extend(node.size().1.to_le_bytes());
+            bytes.extend((*first as u64).to_le_bytes());
+            bytes.extend((node.data().len() as u64).to_le_bytes());
+            bytes.extend((node.count() as u64).to_le_bytes());
+
+            if node.nodes().is_some() {
+                bytes.extend((children as u64).to_le_bytes());
+                children += 4;
+            } else {
+                bytes.extend(0u64.to_le_bytes());
+            }
+        }
+
+        write_elements_depth_first(self.node(), &mut bytes);
+
+        bytes
+    }
+}
+
+fn write_elements_depth_first<Data: FrozenData>(node: QuadTreeNode<Data>, bytes: &mut Vec<u8>) {
+    write_elements(node.data(), bytes);
+
+    if let Some(children) = node.nodes() {
+        children.into_iter().for_each(|child| write_elements_depth_first(child, bytes));
+    }
+}
+
+impl<Data: FrozenData> Grid<Data> {
+    /// writes the Grid in the binary format, which can be read with [FrozenGrid]
+    pub fn write_frozen<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.to_frozen_bytes())
+    }
+
+    /// returns: Vec<u8> the Grid in the binary format, which can be read with [FrozenGrid]
+    pub fn to_frozen_bytes(&self) -> Vec<u8> {
+        let cell_count = self.cell_count();
+
+        let mut bytes = header(KIND_GRID, Data::SIZE, self.bounds());
+
+        bytes.extend((cell_count.0 as u64).to_le_bytes());
+        bytes.extend((cell_count.1 as u64).to_le_bytes());
+        bytes.extend(self.cell_size().0.to_le_bytes());
+        bytes.extend(self.cell_size().1.to_le_bytes());
+        bytes.extend((self.count() as u64).to_le_bytes());
+
+        let mut first = 0u64;
+
+        for y in 0..cell_count.1 {
+            for x in 0..cell_count.0 {
+                bytes.extend(first.to_le_bytes());
+                first += self.cell((x, y)).len() as u64;
+            }
+        }
+
+        bytes.extend(first.to_le_bytes());
+
+        for y in 0..cell_count.1 {
+            for x in 0..cell_count.0 {
+                write_elements(self.cell((x, y)), &mut bytes);
+            }
+        }
+
+        bytes
+    }
+}
+
+fn header(kind: u16, data_size: usize, bounds: (Range<f64>, Range<f64>)) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    bytes.extend(MAGIC);
+    bytes.extend(VERSION.to_le_bytes());
+    bytes.extend(kind.to_le_bytes());
+    bytes.extend((data_size as u32).to_le_bytes());
+    bytes.extend(bounds.0.start.to_le_bytes());
+    bytes.extend(bounds.0.end.to_le_bytes());
+    bytes.extend(bounds.1.start.to_le_bytes());
+    bytes.extend(bounds.1.end.to_le_bytes());
+
+    bytes
+}
+
+fn write_elements<Data: FrozenData>(elements: &[((f64, f64), Data)], bytes: &mut Vec<u8>) {
+    for (position, data) in elements {
+        bytes.extend(position.0.to_le_bytes());
+        bytes.extend(position.1.to_le_bytes());
+
+        let start = bytes.len();
+        bytes.resize(start + Data::SIZE, 0);
+        data.write(&mut bytes[start..]);
+    }
+}
+
+/// checks the common header and returns the bounds
+fn read_header(bytes: &[u8], kind: u16, data_size: usize) -> Result<(Range<f64>, Range<f64>), FrozenError> {
+    if bytes.len() < 4 || bytes[0..4] != MAGIC {
+        return Err(if MAGIC.starts_with(&bytes[..bytes.len().min(4)]) { FrozenError::UnexpectedEnd } else { FrozenError::BadMagic });
+    }
+
+    if bytes.len() < HEADER_SIZE {
+        return Err(FrozenError::UnexpectedEnd);
+    }
+
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+
+    if version != VERSION {
+        return Err(FrozenError::UnsupportedVersion(version));
+    }
+
+    let found_kind = u16::from_le_bytes([bytes[6], bytes[7]]);
+
+    if found_kind != kind {
+        return Err(FrozenError::WrongKind(found_kind));
+    }
+
+    let found_size = read_u32(bytes, 8) as usize;
+
+    if found_size != data_size {
+        return Err(FrozenError::DataSize { expected: data_size, found: found_size });
+    }
+
+    Ok((read_f64(bytes, 12)..read_f64(bytes, 20), read_f64(bytes, 28)..read_f64(bytes, 36)))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_f64(bytes: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// reads a count or an index, they have to fit into a usize to address the bytes
+fn read_usize(bytes: &[u8], offset: usize) -> Result<usize, FrozenError> {
+    usize::try_from(read_u64(bytes, offset)).map_err(|_| FrozenError::Corrupt("count does not fit into a usize"))
+}
+
+/// the byte length of `count` values with `size` bytes each, None if it does not fit
+fn length(count: usize, size: usize) -> Option<usize> {
+    count.checked_mul(size)
+}
+
+/// splits of `length` bytes and fails if there are not enough
+fn take(bytes: &[u8], length: usize) -> Result<(&[u8], &[u8]), FrozenError> {
+    if bytes.len() < length {
+        return Err(FrozenError::UnexpectedEnd);
+    }
+
+    Ok(bytes.split_at(length))
+}
+
+/// elements stored one after another as position and Data
+#[derive(Debug, Clone, Copy)]
+struct Elements<'a, Data: FrozenData> {
+    bytes: &'a [u8],
+    data: PhantomData<Data>,
+}
+
+impl<'a, Data: FrozenData> Elements<'a, Data> {
+    const SIZE: usize = 16 + Data::SIZE;
+
+    fn position(&self, index: usize) -> (f64, f64) {
+        let offset = index * Self::SIZE;
+
+        (read_f64(self.bytes, offset), read_f64(self.bytes, offset + 8))
+    }
+
+    fn data(&self, index: usize) -> Data {
+        let offset = index * Self::SIZE;
+
+        Data::read(&self.bytes[offset + 16..offset + Self::SIZE])
+    }
+}
+
+enum Hit {
+    /// every element in the range lies in the shape
+    All(Range<usize>),
+    One(usize),
+}
+
+struct FrozenNode {
+    min: (f64, f64),
+    max: (f64, f64),
+
+    first: usize,
+    len: usize,
+    count: usize,
+    children: usize,
+}
+
+/// A read-only view of a [QuadTree] written with [QuadTree::to_frozen_bytes]
+#[derive(Debug, Clone, Copy)]
+pub struct FrozenQuadTree<'a, Data: FrozenData> {
+    capacity: u16,
+
+    nodes: &'a [u8],
+    elements: Elements<'a, Data>,
+
+    x: (f64, f64),
+    y: (f64, f64),
+
+    /// whether a node on the border holds an element outside of the bounds
+    outside: bool,
+}
+
+impl<'a, Data: FrozenData> FrozenQuadTree<'a, Data> {
+    /// reads and validates the bytes, nothing gets copied
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: a QuadTree written with [QuadTree::to_frozen_bytes] e.g. a memory-mapped file
+    ///
+    /// returns: Result<FrozenQuadTree<Data>, FrozenError>
+    ///
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<FrozenQuadTree<'a, Data>, FrozenError> {
+        let (x, y) = read_header(bytes, KIND_QUAD_TREE, Data::SIZE)?;
+
+        if bytes.len() < QUAD_TREE_HEADER_SIZE {
+            return Err(FrozenError::UnexpectedEnd);
+        }
+
+        let capacity = u16::try_from(read_u64(bytes, HEADER_SIZE)).map_err(|_| FrozenError::Corrupt("capacity does not fit into a u16"))?;
+        let node_count = read_usize(bytes, HEADER_SIZE + 8)?;
+        let element_count = read_usize(bytes, HEADER_SIZE + 16)?;
+
+        if capacity == 0 {
+            return Err(FrozenError::Corrupt("capacity of zero"));
+        }
+
+        let (nodes, rest) = take(&bytes[QUAD_TREE_HEADER_SIZE..], length(node_count, NODE_SIZE).ok_or(FrozenError::UnexpectedEnd)?)?;
+        let (elements, rest) = take(rest, length(element_count, Elements::<Data>::SIZE).ok_or(FrozenError::UnexpectedEnd)?)?;
+
+        if !rest.is_empty() {
+            return Err(FrozenError::Corrupt("trailing bytes after the elements"));
+        }
+
+        let mut frozen = FrozenQuadTree {
+            capacity,
+            nodes,
+            elements: Elements { bytes: elements, data: PhantomData },
+            x: (x.start, x.end),
+            y: (y.start, y.end),
+            outside: false,
+        };
+
+        frozen.validate(node_count, element_count)?;
+        frozen.outside = frozen.has_outside_elements();
+
+        Ok(frozen)
+    }
+
+    /// only the nodes on the border are read, a QuadTree stores the elements outside of its bounds in the closest node
+    fn has_outside_elements(&self) -> bool {
+        let inside = |position: (f64, f64)| (self.x.0..self.x.1).contains(&position.0) && (self.y.0..self.y.1).contains(&position.1);
+        let mut stack = vec![(0, BORDER)];
+
+        while let Some((index, sides)) = stack.pop() {
+            let node = self.node(index);
+
+            if (node.first..node.first + node.len).any(|element| !inside(self.elements.position(element))) {
+                return true;
+            }
+
+            if node.children != 0 {
+                stack.extend((0..4).map(|i| (node.children + i, child_sides(sides, i))).filter(|(_, sides)| *sides != 0));
+            }
+        }
+
+        false
+    }
+
+    /// the min and max corner of the node, the sides of nodes on the border are taken from `bounds`
+    fn node_box(&self, node: &FrozenNode, sides: u8, bounds: ((f64, f64), (f64, f64))) -> ((f64, f64), (f64, f64)) {
+        border_box((node.min, node.max), sides, bounds)
+    }
+
+    /// the exact bounds, the center and half size of the root are rounded for most bounds
+    fn exact_bounds(&self) -> ((f64, f64), (f64, f64)) {
+        ((self.x.0, self.y.0), (self.x.1, self.y.1))
+    }
+
+    /// makes sure every node only points to later nodes and its element ranges match its children
+    fn validate(&self, node_count: usize, element_count: usize) -> Result<(), FrozenError> {
+        if node_count == 0 {
+            return Err(FrozenError::Corrupt("missing root node"));
+        }
+
+        // node() casts the values to usize, which is lossless once none of them exceeds the counts
+        for index in 0..node_count {
+            let value = |field: usize| read_u64(self.nodes, index * NODE_SIZE + 32 + field * 8);
+
+            if value(0) > element_count as u64 || value(1) > element_count as u64 || value(2) > element_count as u64 || value(3) > node_count as u64 {
+                return Err(FrozenError::Corrupt("node exceeds the element or node count"));
+            }
+        }
+
+        let root = self.node(0);
+
+        if root.first != 0 || root.count != element_count {
+            return Err(FrozenError::Corrupt("root node does not contain all elements"));
+        }
+
+        let mut next_children = 1;
+
+        for index in 0..node_count {
+            let node = self.node(index);
+
+            if node.len > node.count {
+                return Err(FrozenError::Corrupt("node stores more elements than its subtree"));
+            }
+
+            if node.children == 0 {
+                if node.len != node.count {
+                    return Err(FrozenError::Corrupt("leaf node has elements outside of its range"));
+                }
+
+                continue;
+            }
+
+            // breadth first order hands out the children in ascending blocks of four
+            if node.children != next_children || node.children <= index || node.children + 4 > node_count {
+                return Err(FrozenError::Corrupt("node points to invalid children"));
+            }
+
+            next_children += 4;
+
+            let overflow = FrozenError::Corrupt("element ranges overflow");
+            let mut first = node.first.checked_add(node.len).ok_or(overflow.clone())?;
+
+            for child in node.children..node.children + 4 {
+                let child = self.node(child);
+
+                if child.first != first {
+                    return Err(FrozenError::Corrupt("child elements do not follow their parent"));
+                }
+
+                first = first.checked_add(child.count).ok_or(overflow.clone())?;
+            }
+
+            if Some(first) != node.first.checked_add(node.count) {
+                return Err(FrozenError::Corrupt("children do not add up to the count of their parent"));
+            }
+        }
+
+        if next_children != node_count {
+            return Err(FrozenError::Corrupt("unreachable nodes"));
+        }
+
+        Ok(())
+    }
+
+    fn node(&self, index: usize) -> FrozenNode {
+        let offset = index * NODE_SIZE;
+
+        let center = (read_f64(self.nodes, offset), read_f64(self.nodes, offset + 8));
+        let size = (read_f64(self.nodes, offset + 16), read_f64(self.nodes, offset + 24));
+
+        FrozenNode {
+            min: (center.0 - size.0, center.1 - size.1),
+            max: (center.0 + size.0, center.1 + size.1),
+            first: read_u64(self.nodes, offset + 32) as usize,
+            len: read_u64(self.nodes, offset + 40) as usize,
+            count: read_u64(self.nodes, offset + 48) as usize,
+            children: read_u64(self.nodes, offset + 56) as usize,
+        }
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns : u16 the capacity of each TreeNode of the written QuadTree
+    ///
+    pub fn capacity(&self) -> u16 {
+        self.capacity
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns : (Range<f64>, Range<f64>) min_x..max_x and min_y..max_y the area of the written QuadTree
+    ///
+    pub fn bounds(&self) -> (Range<f64>, Range<f64>) {
+        (self.x.0..self.x.1, self.y.0..self.y.1)
+    }
+
+    /// gets the DataValue which is the closest to `position`
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: the position to search from
+    ///
+    /// returns: Option<Data> None if there are no DataValues
+    ///
+    pub fn nearest(&self, position: (f64, f64)) -> Option<Data> {
+        // the nodes on the border reach as far as the elements outside of the bounds
+        let bounds = match self.outside {
+            true => ((f64::NEG_INFINITY, f64::NEG_INFINITY), (f64::INFINITY, f64::INFINITY)),
+            false => self.exact_bounds(),
+        };
+
+        let mut best: Option<(f64, usize)> = None;
+        let mut queue = BinaryHeap::from([Candidate { distance: 0.0, index: 0, sides: BORDER }]);
+
+        while let Some(candidate) = queue.pop() {
+            if best.is_some_and(|best| best.0 <= candidate.distance) {
+                break;
+            }
+
+            let node = self.node(candidate.index);
+
+            for index in node.first..node.first + node.len {
+                let distance = distance_squared(position, self.elements.position(index));
+
+                if best.is_none_or(|best| distance < best.0) {
+                    best = Some((distance, index));
+                }
+            }
+
+            if node.children == 0 {
+                continue;
+            }
+
+            for (i, index) in (node.children..node.children + 4).enumerate() {
+                let child = self.node(index);
+                let sides = child_sides(candidate.sides, i);
+
+                if child.count > 0 {
+                    let (min, max) = self.node_box(&child, sides, bounds);
+
+                    queue.push(Candidate { distance: distance_squared(position, nearest_in_box(position, min, max)), index, sides });
+                }
+            }
+        }
+
+        best.map(|(_, index)| self.elements.data(index))
+    }
+
+    /// `sides` are the sides of the bounds the node lies on, see [BORDER]
+    fn search<S: Shape>(&self, index: usize, sides: u8, shape: &S, visit: &mut impl FnMut(Hit) -> ControlFlow<()>) -> ControlFlow<()> {
+        let node = self.node(index);
+
+        // testing the node only pays off if it saves enough distance checks, the box of a node on
+        // the border says nothing about the elements outside of the bounds
+        if node.count > 4 && !(self.outside && sides != 0) {
+            let (min, max) = self.node_box(&node, sides, self.exact_bounds());
+
+            if shape.contains_box(min, max) {
+                return visit(Hit::All(node.first..node.first + node.count));
+            }
+
+            if !shape.intersects_box(min, max) {
+                return ControlFlow::Continue(());
+            }
+        }
+
+        for element in node.first..node.first + node.len {
+            if shape.contains(self.elements.position(element)) {
+                visit(Hit::One(element))?;
+            }
+        }
+
+        if node.children != 0 {
+            for (i, child) in (node.children..node.children + 4).enumerate() {
+                if self.node(child).count > 0 {
+                    self.search(child, child_sides(sides, i), shape, visit)?;
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+impl<Data: FrozenData> SpatialIndex<Data> for FrozenQuadTree<'_, Data> {
+    fn count(&self) -> usize {
+        self.node(0).count
+    }
+
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let _ = self.search(0, BORDER, shape, &mut |hit| {
+            match hit {
+                Hit::All(range) => data.extend(range.map(|index| self.elements.data(index))),
+                Hit::One(index) => data.push(self.elements.data(index)),
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    fn query_count<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let _ = self.search(0, BORDER, shape, &mut |hit| {
+            match hit {
+                Hit::All(range) => count += range.len(),
+                Hit::One(_) => count += 1,
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    fn query_any<S: Shape>(&self, shape: &S) -> bool {
+        self.search(0, BORDER, shape, &mut |_| ControlFlow::Break(())).is_break()
+    }
+}
+
+/// A read-only view of a [Grid] written with [Grid::to_frozen_bytes]
+#[derive(Debug, Clone, Copy)]
+pub struct FrozenGrid<'a, Data: FrozenData> {
+    offsets: &'a [u8],
+    elements: Elements<'a, Data>,
+
+    cell_count: (usize, usize),
+    cell_size: (f64, f64),
+
+    x: (f64, f64),
+    y: (f64, f64),
+
+    count: usize,
+    /// whether an edge cell holds an element outside of the bounds
+    outside: bool,
+}
+
+impl<'a, Data: FrozenData> FrozenGrid<'a, Data> {
+    /// reads and validates the bytes, nothing gets copied
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: a Grid written with [Grid::to_frozen_bytes] e.g. a memory-mapped file
+    ///
+    /// returns: Result<FrozenGrid<Data>, FrozenError>
+    ///
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<FrozenGrid<'a, Data>, FrozenError> {
+        let (x, y) = read_header(bytes, KIND_GRID, Data::SIZE)?;
+
+        if bytes.len() < GRID_HEADER_SIZE {
+            return Err(FrozenError::UnexpectedEnd);
+        }
+
+        let cell_count = (read_usize(bytes, HEADER_SIZE)?, read_usize(bytes, HEADER_SIZE + 8)?);
+        let cell_size = (read_f64(bytes, HEADER_SIZE + 16), read_f64(bytes, HEADER_SIZE + 24));
+        let element_count = read_usize(bytes, HEADER_SIZE + 32)?;
+
+        if cell_count.0 == 0 || cell_count.1 == 0 {
+            return Err(FrozenError::Corrupt("grid without cells"));
+        }
+
+        let cells = cell_count.0.checked_mul(cell_count.1).and_then(|cells| cells.checked_add(1)).ok_or(FrozenError::UnexpectedEnd)?;
+
+        let (offsets, rest) = take(&bytes[GRID_HEADER_SIZE..], length(cells, 8).ok_or(FrozenError::UnexpectedEnd)?)?;
+        let (elements, rest) = take(rest, length(element_count, Elements::<Data>::SIZE).ok_or(FrozenError::UnexpectedEnd)?)?;
+
+        if !rest.is_empty() {
+            return Err(FrozenError::Corrupt("trailing bytes after the elements"));
+        }
+
+        let mut previous = 0;
+
+        // cell() casts the offsets to usize, which is lossless as they end at the element count
+        for cell in 0..cells {
+            let offset = read_u64(offsets, cell * 8);
+
+            if offset < previous || (cell == 0 && offset != 0) {
+                return Err(FrozenError::Corrupt("cell offsets are not ascending"));
+            }
+
+            previous = offset;
+        }
+
+        if previous != element_count as u64 {
+            return Err(FrozenError::Corrupt("cell offsets do not end at the element count"));
+        }
+
+        let mut grid = FrozenGrid {
+            offsets,
+            elements: Elements { bytes: elements, data: PhantomData },
+            cell_count,
+            cell_size,
+            x: (x.start, x.end),
+            y: (y.start, y.end),
+            count: element_count,
+            outside: false,
+        };
+
+        grid.outside = grid.has_outside_elements();
+
+        Ok(grid)
+    }
+
+    /// only the edge cells are read, a Grid stores the elements outside of its bounds in the closest cell
+    fn has_outside_elements(&self) -> bool {
+        let inside = |position: (f64, f64)| (self.x.0..self.x.1).contains(&position.0) && (self.y.0..self.y.1).contains(&position.1);
+
+        (0..self.cell_count.1).any(|y| {
+            (0..self.cell_count.0).filter(|x| is_edge_cell((*x, y), self.cell_count)).any(|x| {
+                self.cell(x, y).any(|element| !inside(self.elements.position(element)))
+            })
+        })
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: (usize, usize) the cell_count of the written Grid
+    ///
+    pub fn cell_count(&self) -> (usize, usize) {
+        self.cell_count
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: (f64, f64) width and height of each cell
+    ///
+    pub fn cell_size(&self) -> (f64, f64) {
+        self.cell_size
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: (Range<f64>, Range<f64>) min_x..max_x and min_y..max_y the area of the written Grid
+    ///
+    pub fn bounds(&self) -> (Range<f64>, Range<f64>) {
+        (self.x.0..self.x.1, self.y.0..self.y.1)
+    }
+
+    /// gets the DataValue which is the closest to `position`
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: the position to search from
+    ///
+    /// returns: Option<Data> None if there are no DataValues
+    ///
+    pub fn nearest(&self, position: (f64, f64)) -> Option<Data> {
+        let center = self.clamped_index(position);
+        let cell_size = self.cell_size.0.min(self.cell_size.1);
+
+        let mut best: Option<(f64, usize)> = None;
+
+        for ring in 0..self.cell_count.0.max(self.cell_count.1) {
+            // every cell of this ring is at least `ring - 1` cells away from `position`
+            let closest = (ring.saturating_sub(1) as f64 * cell_size).powi(2);
+
+            if best.is_some_and(|best| best.0 < closest) {
+                break;
+            }
+
+            let mut visit = |x: usize, y: usize| {
+                for index in self.cell(x, y) {
+                    let distance = distance_squared(position, self.elements.position(index));
+
+                    if best.is_none_or(|best| distance < best.0) {
+                        best = Some((distance, index));
+                    }
+                }
+            };
+
+            // only the cells on the perimeter of the ring, which lie inside of the grid
+            let rows = [center.1.checked_sub(ring), Some(center.1 + ring).filter(|y| ring > 0 && *y < self.cell_count.1)];
+            let columns = [center.0.checked_sub(ring), Some(center.0 + ring).filter(|x| ring > 0 && *x < self.cell_count.0)];
+
+            for y in rows.into_iter().flatten() {
+                for x in center.0.saturating_sub(ring)..(center.0 + ring + 1).min(self.cell_count.0) {
+                    visit(x, y);
+                }
+            }
+
+            // the corners were visited with the rows
+            for x in columns.into_iter().flatten() {
+                for y in (center.1 + 1).saturating_sub(ring)..(center.1 + ring).min(self.cell_count.1) {
+                    visit(x, y);
+                }
+            }
+        }
+
+        best.map(|(_, index)| self.elements.data(index))
+    }
+
+    /// the range of the elements of the cell
+    fn cell(&self, x: usize, y: usize) -> Range<usize> {
+        let index = x + y * self.cell_count.0;
+
+        read_u64(self.offsets, index * 8) as usize..read_u64(self.offsets, index * 8 + 8) as usize
+    }
+
+    fn search<S: Shape>(&self, shape: &S, visit: &mut impl FnMut(Hit) -> ControlFlow<()>) -> ControlFlow<()> {
+        let (min, max) = shape.bounds();
+
+        let min = self.clamped_index(min);
+        let max = self.clamped_index(max);
+        let (axis_x, axis_y) = self.axes();
+
+        for y in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                let elements = self.cell(x, y);
+
+                if elements.is_empty() {
+                    continue;
+                }
+
+                // testing the cell only pays off if it saves enough distance checks, the box of an
+                // edge cell says nothing about the elements outside of the grid
+                if elements.len() > 4 && !(self.outside && is_edge_cell((x, y), self.cell_count)) {
+                    let pos = (axis_x.edge(x), axis_y.edge(y));
+                    let end = (axis_x.edge(x + 1), axis_y.edge(y + 1));
+
+                    if shape.contains_box(pos, end) {
+                        visit(Hit::All(elements))?;
+                        continue;
+                    }
+
+                    if !shape.intersects_box(pos, end) {
+                        continue;
+                    }
+                }
+
+                for element in elements {
+                    if shape.contains(self.elements.position(element)) {
+                        visit(Hit::One(element))?;
+                    }
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn clamped_index(&self, position: (f64, f64)) -> (usize, usize) {
+        let (x, y) = self.axes();
+
+        (x.index(position.0), y.index(position.1))
+    }
+
+    /// the same edges the written Grid sorted its elements with
+    fn axes(&self) -> (CellAxis, CellAxis) {
+        (
+            CellAxis { start: self.x.0, end: self.x.1, cell_size: self.cell_size.0, count: self.cell_count.0 },
+            CellAxis { start: self.y.0, end: self.y.1, cell_size: self.cell_size.1, count: self.cell_count.1 },
+        )
+    }
+}
+
+impl<Data: FrozenData> SpatialIndex<Data> for FrozenGrid<'_, Data> {
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let _ = self.search(shape, &mut |hit| {
+            match hit {
+                Hit::All(range) => data.extend(range.map(|index| self.elements.data(index))),
+                Hit::One(index) => data.push(self.elements.data(index)),
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    fn query_count<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let _ = self.search(shape, &mut |hit| {
+            match hit {
+                Hit::All(range) => count += range.len(),
+                Hit::One(_) => count += 1,
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    fn query_any<S: Shape>(&self, shape: &S) -> bool {
+        self.search(shape, &mut |_| ControlFlow::Break(())).is_break()
+    }
+}
+
+/// a node waiting to be searched, the closest one gets popped first
+struct Candidate {
+    distance: f64,
+    index: usize,
+    /// the sides of the bounds the node lies on
+    sides: u8,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+fn distance_squared(pos1: (f64, f64), pos2: (f64, f64)) -> f64 {
+    (pos1.0 - pos2.0).powi(2) + (pos1.1 - pos2.1).powi(2)
+}
@@ -0,0 +1,314 @@
+use std::f64::consts::PI;
+use std::marker::PhantomData;
+use std::ops::Range;
+use crate::config::ConfigError;
+use crate::grid::Grid;
+use crate::handle::Handle;
+use crate::quad_tree::QuadTree;
+use crate::shape::Shape;
+use crate::SpatialIndexMut;
+
+/// mean radius of the earth in metres
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// a [Grid] storing (latitude, longitude) positions
+pub type GeoGrid<Data> = GeoIndex<Data, Grid<Data>>;
+
+/// a [QuadTree] storing (latitude, longitude) positions
+pub type GeoQuadTree<Data> = GeoIndex<Data, QuadTree<Data>>;
+
+/// Stores (latitude, longitude) positions in degrees and searches them by great-circle distance in metres
+///
+/// Longitude is used as x and latitude as y of the underlying [SpatialIndexMut],
+/// longitudes wrap around at ±180°
+pub struct GeoIndex<Data: Copy, Partitioner: SpatialIndexMut<Data>> {
+    partitioner: Partitioner,
+
+    data: PhantomData<Data>,
+}
+
+impl<Data: Copy, Partitioner: SpatialIndexMut<Data>> GeoIndex<Data, Partitioner> {
+    fn from_partitioner(partitioner: Partitioner) -> Self {
+        GeoIndex {
+            partitioner,
+            data: PhantomData,
+        }
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: &Partitioner the underlying SpatialIndexMut storing (longitude, latitude) positions
+    ///
+    pub fn partitioner(&self) -> &Partitioner {
+        &self.partitioner
+    }
+
+    /// inserts an obj and checks if the latitude is valid
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: (latitude, longitude) of the data in degrees, the longitude gets wrapped into -180..180
+    /// * `data`: the DataValue which will be return when queued
+    ///
+    /// returns: Handle to get, move or remove the obj later
+    ///
+    pub fn insert(&mut self, position: (f64, f64), data: Data) -> Handle {
+        check_position(position);
+
+        self.partitioner.insert((wrap_longitude(position.1), position.0), data)
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// * `handle`: the handle returned by [`GeoIndex::insert()`]
+    ///
+    /// returns: Option<((f64, f64), Data)> (latitude, longitude) and data of the obj, None if it was removed
+    ///
+    pub fn get(&self, handle: Handle) -> Option<((f64, f64), Data)> {
+        let (position, data) = self.partitioner.get(handle)?;
+
+        Some(((position.1, position.0), data))
+    }
+
+    /// removes an obj, more info here [`SpatialIndexMut::remove()`]
+    pub fn remove(&mut self, handle: Handle) -> Option<Data> {
+        self.partitioner.remove(handle)
+    }
+
+    /// moves an obj to a new (latitude, longitude) and checks if the latitude is valid, more info here [`SpatialIndexMut::update_position()`]
+    pub fn update_position(&mut self, handle: Handle, position: (f64, f64)) -> bool {
+        check_position(position);
+
+        self.partitioner.update_position(handle, (wrap_longitude(position.1), position.0))
+    }
+
+    /// returns the count of DataValues which are currently stored
+    pub fn count(&self) -> usize {
+        self.partitioner.count()
+    }
+
+    pub fn clear(&mut self) {
+        self.partitioner.clear()
+    }
+
+    /// gets all DataValues in the given search radius
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: (latitude, longitude) of the search center in degrees
+    /// * `radius`: radius of the search in metres
+    ///
+    /// returns: Vec<Data>
+    ///
+    pub fn in_radius(&self, position: (f64, f64), radius: f64) -> Vec<Data> {
+        self.partitioner.query(&GeoCircle::new(position, radius))
+    }
+
+    /// counts the DataValues in the given search radius, more info here [`GeoIndex::in_radius()`]
+    pub fn count_in_radius(&self, position: (f64, f64), radius: f64) -> usize {
+        self.partitioner.query_count(&GeoCircle::new(position, radius))
+    }
+
+    /// checks if there is any DataValue in the given search radius, more info here [`GeoIndex::in_radius()`]
+    pub fn any_in_radius(&self, position: (f64, f64), radius: f64) -> bool {
+        self.partitioner.query_any(&GeoCircle::new(position, radius))
+    }
+}
+
+impl<Data: Copy> GeoIndex<Data, Grid<Data>> {
+    /// create a GeoIndex with the default settings of [`Grid::new()`]
+    pub fn new() -> Self {
+        GeoIndex::from_partitioner(Grid::new(longitude(), latitude()))
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// * `cell_count`: (count_longitude, count_latitude) defines how many cell should be present
+    ///
+    /// panics if the cell_count is not valid, [`GeoIndex::try_with_cell_count()`] returns the reason instead
+    ///
+    pub fn with_cell_count(cell_count: (usize, usize)) -> Self {
+        GeoIndex::from_partitioner(Grid::with_cell_count(longitude(), latitude(), cell_count))
+    }
+
+    /// like [`GeoIndex::with_cell_count()`] but returns a ConfigError for a cell_count without cells
+    ///
+    /// # Arguments
+    ///
+    /// * `cell_count`: (count_longitude, count_latitude) defines how many cell should be present
+    ///
+    /// returns: Result<GeoIndex<Data, Grid<Data>>, ConfigError>
+    ///
+    pub fn try_with_cell_count(cell_count: (usize, usize)) -> Result<Self, ConfigError> {
+        Ok(GeoIndex::from_partitioner(Grid::try_with_cell_count(longitude(), latitude(), cell_count)?))
+    }
+}
+
+impl<Data: Copy> Default for GeoIndex<Data, Grid<Data>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Data: Copy> GeoIndex<Data, QuadTree<Data>> {
+    /// create a GeoIndex with the default settings of [`QuadTree::new()`]
+    pub fn new() -> Self {
+        GeoIndex::from_partitioner(QuadTree::new(longitude(), latitude()))
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: capacity of each TreeNode
+    ///
+    pub fn with_capacity(capacity: u16) -> Self {
+        GeoIndex::from_partitioner(QuadTree::with_capacity(longitude(), latitude(), capacity))
+    }
+
+    /// like [`GeoIndex::with_capacity()`] but returns a ConfigError for a capacity of 0
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: capacity of each TreeNode, at least 1
+    ///
+    /// returns: Result<GeoIndex<Data, QuadTree<Data>>, ConfigError>
+    ///
+    pub fn try_with_capacity(capacity: u16) -> Result<Self, ConfigError> {
+        Ok(GeoIndex::from_partitioner(QuadTree::try_with_capacity(longitude(), latitude(), capacity)?))
+    }
+}
+
+impl<Data: Copy> Default for GeoIndex<Data, QuadTree<Data>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// great-circle distance in metres between two (latitude, longitude) positions in degrees
+pub fn distance(from: (f64, f64), to: (f64, f64)) -> f64 {
+    2.0 * EARTH_RADIUS * haversine((from.1, from.0), (to.1, to.0)).sqrt().min(1.0).asin()
+}
+
+/// All (longitude, latitude) positions which are at most `radius` metres away from `position`
+/// measured along the surface of the earth
+///
+/// Used to search a [SpatialIndex](crate::SpatialIndex) whose x is the longitude and y the latitude
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoCircle {
+    /// (longitude, latitude) in degrees
+    position: (f64, f64),
+    /// haversine of the angular radius, compared against the haversine between two positions
+    haversine: f64,
+    /// the angular radius in degrees
+    angle: f64,
+}
+
+impl GeoCircle {
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: (latitude, longitude) of the center in degrees
+    /// * `radius`: radius in metres
+    ///
+    pub fn new(position: (f64, f64), radius: f64) -> GeoCircle {
+        let angle = (radius / EARTH_RADIUS).min(PI);
+
+        GeoCircle {
+            position: (wrap_longitude(position.1), position.0),
+            haversine: (angle / 2.0).sin().powi(2),
+            angle: angle.to_degrees(),
+        }
+    }
+
+    /// the longitudes covered by the circle as offset from its center, None if it covers a pole
+    fn longitude_extent(&self) -> Option<f64> {
+        if self.position.1 + self.angle >= 90.0 || self.position.1 - self.angle <= -90.0 {
+            return None;
+        }
+
+        Some((self.angle.to_radians().sin() / self.position.1.to_radians().cos()).min(1.0).asin().to_degrees())
+    }
+}
+
+impl Shape for GeoCircle {
+    fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        let min_latitude = (self.position.1 - self.angle).max(-90.0);
+        let max_latitude = (self.position.1 + self.angle).min(90.0);
+
+        match self.longitude_extent() {
+            Some(extent) if self.position.0 - extent >= -180.0 && self.position.0 + extent < 180.0 => {
+                ((self.position.0 - extent, min_latitude), (self.position.0 + extent, max_latitude))
+            }
+            // the circle wraps around the antimeridian or covers a pole
+            _ => ((-180.0, min_latitude), (180.0, max_latitude))
+        }
+    }
+
+    fn contains(&self, position: (f64, f64)) -> bool {
+        haversine(self.position, position) <= self.haversine
+    }
+
+    fn contains_box(&self, min: (f64, f64), max: (f64, f64)) -> bool {
+        // along a parallel the distance grows with the longitude offset and along a meridian it
+        // peaks at an end as long as the longitude offset stays within ±90°, so then checking the
+        // corners is enough
+        let start = min.0 - self.position.0;
+        let end = max.0 - self.position.0;
+
+        if ![-360.0, 0.0, 360.0].iter().any(|shift| start + shift >= -90.0 && end + shift <= 90.0) {
+            return false;
+        }
+
+        self.contains(min) && self.contains(max) && self.contains((min.0, max.1)) && self.contains((max.0, min.1))
+    }
+
+    fn intersects_box(&self, min: (f64, f64), max: (f64, f64)) -> bool {
+        if max.1 < self.position.1 - self.angle || min.1 > self.position.1 + self.angle {
+            return false;
+        }
+
+        let extent = match self.longitude_extent() {
+            None => return true,
+            Some(extent) => extent,
+        };
+
+        [-360.0, 0.0, 360.0].iter().any(|shift| min.0 + shift <= self.position.0 + extent && max.0 + shift >= self.position.0 - extent)
+    }
+}
+
+fn longitude() -> Range<f64> {
+    -180.0..180.0
+}
+
+/// latitudes include both poles
+fn latitude() -> Range<f64> {
+    -90.0..f64::next_up(90.0)
+}
+
+fn check_position(position: (f64, f64)) {
+    if !(-90.0..=90.0).contains(&position.0) || !position.1.is_finite() {
+        panic!("tried to insert position into GeoIndex which was out of bounce")
+    }
+}
+
+fn wrap_longitude(longitude: f64) -> f64 {
+    let longitude = (longitude + 180.0).rem_euclid(360.0) - 180.0;
+
+    // rem_euclid can round up to 360
+    if longitude >= 180.0 {
+        -180.0
+    } else {
+        longitude
+    }
+}
+
+/// haversine of the angle between two (longitude, latitude) positions in degrees
+fn haversine(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let latitude = ((to.1 - from.1).to_radians() / 2.0).sin().powi(2);
+    let longitude = ((to.0 - from.0).to_radians() / 2.0).sin().powi(2);
+
+    latitude + from.1.to_radians().cos() * to.1.to_radians().cos() * longitude
+}
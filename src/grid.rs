@@ -1,9 +1,38 @@
-use std::ops::Range;
-use crate::SpatialPartitioner;
-use crate::util::in_range;
-
+use std::marker::PhantomData;
+use std::ops::{ControlFlow, Range};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use crate::{SpatialIndex, SpatialIndexMut};
+use crate::config::{check_bounds, check_cell_count, ConfigError};
+use crate::handle::{Handle, Slots};
+use crate::shape::{Circle, Shape};
+use crate::stats::{bucket, GridStats, QueryStats};
+use crate::util::{is_edge_cell, CellAxis, Visit};
+
+/// Divides the area into equally sized cells
+///
+/// The handle of each element is stored next to it, so removing an element or moving it into
+/// another cell is O(1)
+///
+/// Cells with many elements also keep the x and y of their elements in separate `Vec`s, which lets
+/// [`Shape::filter()`] test many positions of a cell at once
+///
+/// Positions outside of the bounds, which can only be stored with `insert_unchecked`, end up in
+/// the closest edge cell and are still found by every query which contains them. Positions with a
+/// NaN coordinate end up in the first cell and are never found
+///
+/// A Grid is `Send` and `Sync` whenever `Data` is, so it can be queried from many threads at once
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "GridSnapshot<Data>"))]
 pub struct Grid<Data: Copy> {
     cells: Vec<Vec<((f64, f64), Data)>>,
+    /// x and y of the elements of each cell which reached [LANE_CELL_LEN] elements since the last
+    /// clear, which lets [`Shape::filter()`] test many positions of the cell at once
+    positions: Vec<Option<Box<Positions>>>,
+    /// the handle of each element at the same index in `cells`
+    handles: Vec<Vec<Handle>>,
+    /// (cell, index in the cell) of each element
+    slots: Slots<(usize, usize)>,
 
     cell_count: (usize, usize),
     cell_size: (f64, f64),
@@ -12,9 +41,23 @@ pub struct Grid<Data: Copy> {
     y: Range<f64>,
 
     count: usize,
+    /// count of elements outside of the bounds, as long as there are any the edge cells can not be
+    /// accepted or rejected as a whole
+    outside: usize,
 }
 
 impl<Data: Copy> Grid<Data> {
+    /// create a Grid with a default cell_count of (100,100), more info here [`Grid::with_cell_count()`]
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    pub fn new(x: Range<f64>, y: Range<f64>) -> Grid<Data> {
+        Grid::with_cell_count(x, y, (100, 100))
+    }
+
     ///
     /// # Arguments
     ///
@@ -22,34 +65,87 @@ impl<Data: Copy> Grid<Data> {
     /// * `y`: min_y..max_y defines the area in wich data can be inserted
     /// * `cell_count`: (count_x, count_y) defines how many cell should be present
     ///
+    /// panics if the settings are not valid, [`Grid::try_with_cell_count()`] returns the reason instead
+    ///
     pub fn with_cell_count(x: Range<f64>, y: Range<f64>, cell_count: (usize, usize)) -> Grid<Data> {
+        Grid::try_with_cell_count(x, y, cell_count).unwrap_or_else(|error| panic!("tried to create a Grid from invalid settings: {}", error))
+    }
+
+    /// like [`Grid::new()`] but returns a ConfigError if the bounds are not valid
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    /// returns: Result<Grid<Data>, ConfigError>
+    ///
+    pub fn try_new(x: Range<f64>, y: Range<f64>) -> Result<Grid<Data>, ConfigError> {
+        Grid::try_with_cell_count(x, y, (100, 100))
+    }
+
+    /// like [`Grid::with_cell_count()`] but returns a ConfigError for empty, reversed or infinite
+    /// ranges and for a cell_count without cells
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `cell_count`: (count_x, count_y) defines how many cell should be present
+    ///
+    /// returns: Result<Grid<Data>, ConfigError>
+    ///
+    pub fn try_with_cell_count(x: Range<f64>, y: Range<f64>, cell_count: (usize, usize)) -> Result<Grid<Data>, ConfigError> {
+        check_bounds(&x, &y)?;
+        check_cell_count(cell_count)?;
+
         let mut cells = Vec::new();
 
         for i in 0..(cell_count.0 * cell_count.1) {
-            cells.insert(i as usize, Vec::new());
+            cells.insert(i, Vec::new());
         };
 
-        Grid {
+        Ok(Grid {
+            handles: vec![Vec::new(); cells.len()],
+            positions: vec![None; cells.len()],
             cells,
+            slots: Slots::new(),
             cell_count,
-            cell_size: ((x.end - x.start) as f64 / cell_count.0 as f64, (y.end - y.start) as f64 / cell_count.1 as f64),
+            cell_size: ((x.end - x.start) / cell_count.0 as f64, (y.end - y.start) / cell_count.1 as f64),
             x,
             y,
             count: 0,
+            outside: 0,
+        })
+    }
+
+    /// configures a Grid step by step, the settings are checked by [`GridBuilder::build()`]
+    ///
+    /// # Arguments
+    ///
+    /// returns: GridBuilder<Data> a builder with a default cell_count of (100,100) and no bounds
+    ///
+    pub fn builder() -> GridBuilder<Data> {
+        GridBuilder {
+            bounds: None,
+            cells: Cells::Count((100, 100)),
+            data: PhantomData,
         }
     }
 
     ///
     /// # Arguments
     ///
-    /// * `indexes`: (x,y) cord of the cell
+    /// * `indexes`: (x,y) cord of the cell, panics if it lies outside of the cell_count
     ///
     /// returns: &Vec<((f64, f64), Data), Global> the data stored in the cell
     ///
     pub fn cell(&self, indexes: (usize, usize)) -> &Vec<((f64, f64), Data)> {
-        let index = indexes.0 + (indexes.1 * self.cell_count.0);
+        if indexes.0 >= self.cell_count.0 || indexes.1 >= self.cell_count.1 {
+            panic!("tried to get cell {:?} of a Grid with a cell_count of {:?}", indexes, self.cell_count)
+        }
 
-        self.cells.get(index).unwrap()
+        &self.cells[indexes.0 + (indexes.1 * self.cell_count.0)]
     }
 
     ///
@@ -70,98 +166,626 @@ impl<Data: Copy> Grid<Data> {
         self.cell_size
     }
 
-    fn pos_to_index(&self, position: (f64, f64)) -> (usize, usize) {
-        let x = ((position.0 - self.x.start) / self.cell_size.0).floor() as usize;
-        let y = ((position.1 - self.y.start) / self.cell_size.1).floor() as usize;
+    ///
+    /// # Arguments
+    ///
+    /// returns: (Range<f64>, Range<f64>) min_x..max_x and min_y..max_y the area in wich data can be inserted
+    ///
+    pub fn bounds(&self) -> (Range<f64>, Range<f64>) {
+        (self.x.clone(), self.y.clone())
+    }
 
-        (x, y)
+    ///
+    /// # Arguments
+    ///
+    /// returns: GridStats the occupancy of the cells, walks through every cell
+    ///
+    pub fn stats(&self) -> GridStats {
+        let max_load = self.cells.iter().map(|cell| cell.len()).max().unwrap_or(0);
+        let mut occupancy = vec![0; bucket(max_load) + 1];
+
+        for cell in &self.cells {
+            occupancy[bucket(cell.len())] += 1;
+        }
+
+        GridStats {
+            cell_count: self.cell_count,
+            count: self.count,
+            outside: self.outside,
+            empty_cells: occupancy[0],
+            occupancy,
+            max_load,
+        }
     }
 
-    fn index_to_pos(&self, position: (usize, usize)) -> (f64, f64) {
-        let x = self.cell_size.0 * position.0 as f64 + self.x.start;
-        let y = self.cell_size.1 * position.1 as f64 + self.y.start;
+    /// like [`SpatialIndex::query()`] and counts what the query did into `stats`
+    ///
+    /// a query without stats does not pay anything for them
+    ///
+    /// # Arguments
+    ///
+    /// * `shape`: the area which gets searched
+    /// * `stats`: the counters are added to, so it can sum up many queries
+    ///
+    /// returns: Vec<Data>
+    ///
+    pub fn query_with_stats<S: Shape>(&self, shape: &S, stats: &mut QueryStats) -> Vec<Data> {
+        let data = self.query_traced(shape, &mut |_, visit| stats.record(visit));
+        stats.returned += data.len();
 
-        (x, y)
+        data
     }
-}
 
-impl<Data: Copy> SpatialPartitioner<Data> for Grid<Data> {
+    /// like [`SpatialIndex::in_circle()`] and counts what the query did into `stats`
     ///
-    /// create a Grid with a default size of (100,100). More info here [`Grid::new()`]
+    /// # Arguments
     ///
-    fn new(x: Range<f64>, y: Range<f64>) -> Self {
-        Grid::with_cell_count(x, y, (100, 100))
+    /// * `position`: center of the search circle
+    /// * `radius`: radius of the search circle
+    /// * `stats`: the counters are added to, so it can sum up many queries
+    ///
+    /// returns: Vec<Data>
+    ///
+    pub fn in_circle_with_stats(&self, position: (f64, f64), radius: f64, stats: &mut QueryStats) -> Vec<Data> {
+        self.query_with_stats(&Circle::new(position, radius), stats)
     }
 
-    fn insert(&mut self, position: (f64, f64), data: Data) {
-        if position.0 < self.x.start || position.0 >= self.x.end || position.1 < self.y.start || position.1 >= self.y.end {
+    fn check_bounds(&self, position: (f64, f64)) {
+        if !self.is_inside(position) {
             panic!("tried to insert position into SpatialHash which was out of bounce")
         }
+    }
+
+    /// adds the element to the end of the cell
+    fn push(&mut self, cell: usize, element: ((f64, f64), Data), handle: Handle) {
+        self.cells[cell].push(element);
+        self.handles[cell].push(handle);
+
+        match &mut self.positions[cell] {
+            Some(positions) => {
+                positions.xs.push(element.0.0);
+                positions.ys.push(element.0.1);
+            }
+            None => self.positions[cell] = Positions::of(&self.cells[cell]),
+        }
+    }
+
+    /// false for positions outside of the bounds and positions with a NaN coordinate
+    fn is_inside(&self, position: (f64, f64)) -> bool {
+        self.x.contains(&position.0) && self.y.contains(&position.1)
+    }
+
+    /// takes the element out of its cell, the last element of the cell takes its place
+    fn take(&mut self, (cell, index): (usize, usize)) -> ((f64, f64), Data) {
+        let element = self.cells[cell].swap_remove(index);
+        self.handles[cell].swap_remove(index);
+
+        if let Some(positions) = &mut self.positions[cell] {
+            positions.xs.swap_remove(index);
+            positions.ys.swap_remove(index);
+        }
+
+        if !self.is_inside(element.0) {
+            self.outside -= 1;
+        }
+
+        if let Some(moved) = self.handles[cell].get(index) {
+            self.slots.set(*moved, (cell, index));
+        }
 
-        self.insert_unchecked(position, data);
+        element
     }
 
-    fn insert_unchecked(&mut self, position: (f64, f64), data: Data) {
+    fn cell_index(&self, position: (f64, f64)) -> usize {
         let index_position = self.pos_to_index(position);
 
-        self.cells.get_mut((index_position.0 + (index_position.1 * self.cell_count.0)) as usize).unwrap().push((position, data));
-        self.count += 1;
+        index_position.0 + (index_position.1 * self.cell_count.0)
+    }
+
+    /// the cell of the position, positions outside of the grid are moved to the closest cell
+    ///
+    /// positions right below the end stay in the last cell, NaN ends up in the first cell
+    fn pos_to_index(&self, position: (f64, f64)) -> (usize, usize) {
+        let (x, y) = self.axes();
+
+        (x.index(position.0), y.index(position.1))
+    }
+
+    pub(crate) fn axes(&self) -> (CellAxis, CellAxis) {
+        (
+            CellAxis { start: self.x.start, end: self.x.end, cell_size: self.cell_size.0, count: self.cell_count.0 },
+            CellAxis { start: self.y.start, end: self.y.end, cell_size: self.cell_size.1, count: self.cell_count.1 },
+        )
+    }
+}
+
+/// Configures a [Grid] step by step, created by [`Grid::builder()`]
+///
+/// ```
+/// use spatial_neighbors::grid::Grid;
+///
+/// let grid: Grid<u32> = Grid::builder()
+///     .bounds(0.0..100.0, 0.0..50.0)
+///     .cell_size((10.0, 10.0))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(grid.cell_count(), (10, 5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GridBuilder<Data: Copy> {
+    bounds: Option<(Range<f64>, Range<f64>)>,
+    cells: Cells,
+    data: PhantomData<Data>,
+}
+
+/// the last of `cell_count` and `cell_size` which was set
+#[derive(Debug, Clone, Copy)]
+enum Cells {
+    Count((usize, usize)),
+    Size((f64, f64)),
+}
+
+impl<Data: Copy> GridBuilder<Data> {
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    pub fn bounds(mut self, x: Range<f64>, y: Range<f64>) -> Self {
+        self.bounds = Some((x, y));
+        self
+    }
+
+    /// replaces an earlier `cell_size`
+    ///
+    /// # Arguments
+    ///
+    /// * `cell_count`: (count_x, count_y) defines how many cell should be present
+    ///
+    pub fn cell_count(mut self, cell_count: (usize, usize)) -> Self {
+        self.cells = Cells::Count(cell_count);
+        self
+    }
+
+    /// replaces an earlier `cell_count`, the cell_count is rounded up so the cells cover the bounds
+    /// and the cells end up at most this large
+    ///
+    /// # Arguments
+    ///
+    /// * `cell_size`: (width, height) the largest size of each cell
+    ///
+    pub fn cell_size(mut self, cell_size: (f64, f64)) -> Self {
+        self.cells = Cells::Size(cell_size);
+        self
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: Result<Grid<Data>, ConfigError> the Grid or the first setting which was not valid
+    ///
+    pub fn build(self) -> Result<Grid<Data>, ConfigError> {
+        let (x, y) = self.bounds.ok_or(ConfigError::MissingBounds)?;
+        check_bounds(&x, &y)?;
+
+        let cell_count = match self.cells {
+            Cells::Count(cell_count) => cell_count,
+            Cells::Size(cell_size) => {
+                let count = |extent: f64, size: f64| {
+                    let count = (extent / size).ceil().max(1.0);
+
+                    // ranges with an inf extent are already rejected, so only tiny sizes overflow here
+                    (size > 0.0 && size.is_finite() && count < usize::MAX as f64).then_some(count as usize)
+                };
+
+                match (count(x.end - x.start, cell_size.0), count(y.end - y.start, cell_size.1)) {
+                    (Some(count_x), Some(count_y)) if count_x.checked_mul(count_y).is_some() => (count_x, count_y),
+                    _ => return Err(ConfigError::InvalidCellSize(cell_size)),
+                }
+            }
+        };
+
+        Grid::try_with_cell_count(x, y, cell_count)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<Data: Copy + Send + Sync> Grid<Data> {
+    /// builds a Grid from many elements at once, the elements get sorted into their cells in parallel
+    ///
+    /// the result is the same as inserting the elements one after another
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `cell_count`: (count_x, count_y) defines how many cell should be present
+    /// * `elements`: (position, data) of each element, all of them have to lie inside of the bounds
+    ///
+    /// panics if the settings are not valid, [`Grid::try_par_from_elements()`] returns the reason instead
+    ///
+    pub fn par_from_elements(x: Range<f64>, y: Range<f64>, cell_count: (usize, usize), elements: &[((f64, f64), Data)]) -> Grid<Data> {
+        Grid::try_par_from_elements(x, y, cell_count, elements).unwrap_or_else(|error| panic!("tried to create a Grid from invalid settings: {}", error))
+    }
+
+    /// like [`Grid::par_from_elements()`] but returns a ConfigError for empty, reversed or infinite
+    /// ranges and for a cell_count without cells
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `cell_count`: (count_x, count_y) defines how many cell should be present
+    /// * `elements`: (position, data) of each element, panics if one lies outside of the bounds
+    ///
+    /// returns: Result<Grid<Data>, ConfigError>
+    ///
+    pub fn try_par_from_elements(x: Range<f64>, y: Range<f64>, cell_count: (usize, usize), elements: &[((f64, f64), Data)]) -> Result<Grid<Data>, ConfigError> {
+        let mut grid = Grid::try_with_cell_count(x, y, cell_count)?;
+
+        let mut indexed: Vec<_> = elements.par_iter().enumerate().map(|(i, element)| {
+            grid.check_bounds(element.0);
+
+            (grid.cell_index(element.0), *element, i)
+        }).collect();
+
+        // the sort is stable so each cell keeps the insertion order
+        indexed.par_sort_by_key(|element| element.0);
+
+        let mut buckets = vec![&indexed[0..0]; grid.cells.len()];
+        let mut locations = vec![(0, 0); elements.len()];
+
+        for bucket in indexed.chunk_by(|a, b| a.0 == b.0) {
+            buckets[bucket[0].0] = bucket;
+
+            for (index, element) in bucket.iter().enumerate() {
+                locations[element.2] = (element.0, index);
+            }
+        }
+
+        grid.cells.par_iter_mut().zip(grid.handles.par_iter_mut()).zip(buckets).for_each(|((cell, handles), bucket)| {
+            cell.extend(bucket.iter().map(|element| element.1));
+            handles.extend(bucket.iter().map(|element| Handle::nth(element.2)));
+        });
+
+        grid.positions = grid.cells.iter().map(|cell| Positions::of(cell)).collect();
+
+        // the handles are given out in the order of the elements
+        for location in locations {
+            grid.slots.insert(location);
+        }
+
+        grid.count = elements.len();
+
+        Ok(grid)
     }
+}
 
+impl<Data: Copy> SpatialIndex<Data> for Grid<Data> {
     fn count(&self) -> usize {
         self.count
     }
 
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        self.query_traced(shape, &mut |_, _| {})
+    }
+
+    fn query_count<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let _ = self.search(shape, &mut |_, _| {}, &mut |hit| {
+            match hit {
+                Hit::All(elements) => count += elements.len(),
+                Hit::Lanes(_, positions, shape) => {
+                    let _ = shape.filter(&positions.xs, &positions.ys, &mut |_| {
+                        count += 1;
+                        ControlFlow::Continue(())
+                    });
+                }
+                Hit::One(_) => count += 1,
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    fn query_any<S: Shape>(&self, shape: &S) -> bool {
+        self.search(shape, &mut |_, _| {}, &mut |hit| match hit {
+            Hit::Lanes(_, positions, shape) => shape.filter(&positions.xs, &positions.ys, &mut |_| ControlFlow::Break(())),
+            _ => ControlFlow::Break(()),
+        }).is_break()
+    }
+}
+
+impl<Data: Copy> SpatialIndexMut<Data> for Grid<Data> {
+    fn insert(&mut self, position: (f64, f64), data: Data) -> Handle {
+        self.check_bounds(position);
+
+        self.insert_unchecked(position, data)
+    }
+
+    fn insert_unchecked(&mut self, position: (f64, f64), data: Data) -> Handle {
+        let index = self.cell_index(position);
+        let handle = self.slots.insert((index, self.cells[index].len()));
+
+        self.push(index, (position, data), handle);
+        self.count += 1;
+
+        if !self.is_inside(position) {
+            self.outside += 1;
+        }
+
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<((f64, f64), Data)> {
+        let (cell, index) = self.slots.get(handle)?;
+
+        Some(self.cells[cell][index])
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<Data> {
+        let location = self.slots.remove(handle)?;
+
+        self.count -= 1;
+
+        Some(self.take(location).1)
+    }
+
+    fn update_position(&mut self, handle: Handle, position: (f64, f64)) -> bool {
+        self.check_bounds(position);
+
+        let Some((cell, index)) = self.slots.get(handle) else {
+            return false;
+        };
+
+        let target = self.cell_index(position);
+
+        if target == cell {
+            if !self.is_inside(self.cells[cell][index].0) {
+                self.outside -= 1;
+            }
+
+            self.cells[cell][index].0 = position;
+
+            if let Some(positions) = &mut self.positions[cell] {
+                positions.xs[index] = position.0;
+                positions.ys[index] = position.1;
+            }
+
+            return true;
+        }
+
+        let (_, data) = self.take((cell, index));
+
+        self.slots.set(handle, (target, self.cells[target].len()));
+        self.push(target, (position, data), handle);
+
+        true
+    }
+
     fn clear(&mut self) {
         self.count = 0;
-        self.cells.iter_mut().for_each(|cell| cell.clear())
+        self.outside = 0;
+        self.cells.iter_mut().for_each(|cell| cell.clear());
+        self.handles.iter_mut().for_each(|handles| handles.clear());
+        self.positions.iter_mut().for_each(|positions| *positions = None);
+        self.slots.clear();
     }
+}
 
-    fn in_circle(&self, position: (f64, f64), radius: f64) -> Vec<Data> {
-        let radius_x = (radius / ((self.x.end - self.x.start) as f64 / self.cell_count.0 as f64)).ceil().min(self.cell_count.0 as f64) as i32;
-        let radius_y = (radius / ((self.y.end - self.y.start) as f64 / self.cell_count.1 as f64)).ceil().min(self.cell_count.1 as f64) as i32;
+/// from this many elements on a cell also keeps x and y in separate `Vec`s, below it a branch for
+/// each element is faster than testing them in lanes
+const LANE_CELL_LEN: usize = 256;
 
-        let index_position = self.pos_to_index(position);
+/// x and y of the elements of a cell in the same order as the cell
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Positions {
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+}
+
+impl Positions {
+    /// None for cells with less than [LANE_CELL_LEN] elements
+    fn of<Data>(cell: &[((f64, f64), Data)]) -> Option<Box<Positions>> {
+        (cell.len() >= LANE_CELL_LEN).then(|| Box::new(Positions {
+            xs: cell.iter().map(|element| element.0.0).collect(),
+            ys: cell.iter().map(|element| element.0.1).collect(),
+        }))
+    }
+}
+
+enum Hit<'a, Data, S> {
+    /// every element of the cell lies in the shape
+    All(&'a [((f64, f64), Data)]),
+    /// the elements of the cell whose position lies in the shape, the positions still have to be
+    /// tested with [`Shape::filter()`]
+    Lanes(&'a [((f64, f64), Data)], &'a Positions, &'a S),
+    One(&'a ((f64, f64), Data)),
+}
 
+impl<Data: Copy> Grid<Data> {
+    /// calls `trace` with the index of every non-empty cell a query for the shape reaches
+    #[cfg(feature = "svg")]
+    pub(crate) fn trace<S: Shape>(&self, shape: &S, mut trace: impl FnMut(usize, Visit)) {
+        let _ = self.search(shape, &mut trace, &mut |_| ControlFlow::Continue(()));
+    }
+
+    fn query_traced<S: Shape>(&self, shape: &S, trace: &mut impl FnMut(usize, Visit)) -> Vec<Data> {
         let mut data = Vec::new();
 
-        for x in -radius_x..(radius_x + 1) {
-            for y in -radius_y..(radius_y + 1) {
-                let x = index_position.0 as i32 + x;
-                let y = index_position.1 as i32 + y;
+        let _ = self.search(shape, trace, &mut |hit| {
+            match hit {
+                Hit::All(elements) => data.extend(elements.iter().map(|x| x.1)),
+                Hit::Lanes(elements, positions, shape) => {
+                    let _ = shape.filter(&positions.xs, &positions.ys, &mut |i| {
+                        data.push(elements[i].1);
+                        ControlFlow::Continue(())
+                    });
+                }
+                Hit::One(element) => data.push(element.1),
+            }
+
+            ControlFlow::Continue(())
+        });
 
-                if x < 0 || x >= self.cell_count.0 as i32 || y < 0 || y >= self.cell_count.1 as i32 {
+        data
+    }
+
+    fn search<'a, S: Shape>(&'a self, shape: &'a S, trace: &mut impl FnMut(usize, Visit), visit: &mut impl FnMut(Hit<'a, Data, S>) -> ControlFlow<()>) -> ControlFlow<()> {
+        let (min, max) = shape.bounds();
+
+        let min = self.pos_to_index(min);
+        let max = self.pos_to_index(max);
+        let (axis_x, axis_y) = self.axes();
+
+        for y in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                let index = x + y * self.cell_count.0;
+                let elements = &self.cells[index];
+
+                if elements.is_empty() {
                     continue;
                 }
 
-                let index = x + (y * self.cell_count.0 as i32);
-
-                match self.cells.get(index as usize) {
-                    None => {}
-                    Some(elements) => {
-                        if elements.len() > 4 {
-                            let pos = self.index_to_pos((x as usize, y as usize));
-
-                            if in_range(position, (pos.0 + self.cell_size.0, pos.1), radius)
-                                && in_range(position, (pos.0 + self.cell_size.0, pos.1 + self.cell_size.1), radius)
-                                && in_range(position, (pos.0, pos.1), radius)
-                                && in_range(position, (pos.0, pos.1 + self.cell_size.1), radius) {
-                                data.extend(elements.iter().map(|x| x.1));
-
-                                continue;
-                            }
-                        }
-
-                        for element in elements {
-                            if in_range(element.0, position, radius) {
-                                data.push(element.1);
-                            }
-                        }
+                // testing the cell only pays off if it saves enough distance checks, the box of an
+                // edge cell says nothing about the elements outside of the grid
+                if elements.len() > 4 && !(self.outside > 0 && is_edge_cell((x, y), self.cell_count)) {
+                    let pos = (axis_x.edge(x), axis_y.edge(y));
+                    let end = (axis_x.edge(x + 1), axis_y.edge(y + 1));
+
+                    if shape.contains_box(pos, end) {
+                        trace(index, Visit::Accepted);
+                        visit(Hit::All(elements))?;
+                        continue;
                     }
-                };
+
+                    if !shape.intersects_box(pos, end) {
+                        trace(index, Visit::Rejected);
+                        continue;
+                    }
+                }
+
+                trace(index, Visit::Tested(elements.len()));
+
+                // only large cells have positions, which saves looking them up for all others
+                if elements.len() >= LANE_CELL_LEN {
+                    if let Some(positions) = &self.positions[index] {
+                        visit(Hit::Lanes(elements, positions, shape))?;
+                        continue;
+                    }
+                }
+
+                for element in elements {
+                    if shape.contains(element.0) {
+                        visit(Hit::One(element))?;
+                    }
+                }
             }
         }
 
-        data
+        ControlFlow::Continue(())
+    }
+}
+
+/// the fields of a [Grid] as they are deserialized, [`Grid::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GridSnapshot<Data> {
+    cells: Vec<Vec<((f64, f64), Data)>>,
+    positions: Vec<Option<Box<Positions>>>,
+    handles: Vec<Vec<Handle>>,
+    slots: Slots<(usize, usize)>,
+    cell_count: (usize, usize),
+    cell_size: (f64, f64),
+    x: Range<f64>,
+    y: Range<f64>,
+    count: usize,
+    outside: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> TryFrom<GridSnapshot<Data>> for Grid<Data> {
+    type Error = &'static str;
+
+    fn try_from(snapshot: GridSnapshot<Data>) -> Result<Self, Self::Error> {
+        let grid = Grid {
+            cells: snapshot.cells,
+            positions: snapshot.positions,
+            handles: snapshot.handles,
+            slots: snapshot.slots,
+            cell_count: snapshot.cell_count,
+            cell_size: snapshot.cell_size,
+            x: snapshot.x,
+            y: snapshot.y,
+            count: snapshot.count,
+            outside: snapshot.outside,
+        };
+
+        grid.validate()?;
+
+        Ok(grid)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> Grid<Data> {
+    /// checks everything the other methods rely on, so a corrupted snapshot can not make them panic
+    /// or miss elements
+    fn validate(&self) -> Result<(), &'static str> {
+        if check_bounds(&self.x, &self.y).is_err() || check_cell_count(self.cell_count).is_err() {
+            return Err("bounds or cell_count are not valid");
+        }
+
+        if !(self.cell_size.0 > 0.0 && self.cell_size.0.is_finite() && self.cell_size.1 > 0.0 && self.cell_size.1.is_finite()) {
+            return Err("cell_size is not valid");
+        }
+
+        let cells = self.cell_count.0 * self.cell_count.1;
+
+        if self.cells.len() != cells || self.handles.len() != cells || self.positions.len() != cells {
+            return Err("count of cells does not match the cell_count");
+        }
+
+        let (mut count, mut outside) = (0, 0);
+
+        for (index, cell) in self.cells.iter().enumerate() {
+            if self.handles[index].len() != cell.len() {
+                return Err("cell and its handles differ in length");
+            }
+
+            if let Some(positions) = &self.positions[index] {
+                // the bits are compared so NaN equals itself
+                let xs = positions.xs.iter().map(|x| x.to_bits()).eq(cell.iter().map(|element| element.0.0.to_bits()));
+                let ys = positions.ys.iter().map(|y| y.to_bits()).eq(cell.iter().map(|element| element.0.1.to_bits()));
+
+                if !xs || !ys {
+                    return Err("positions of a cell differ from its elements");
+                }
+            }
+
+            for element in cell {
+                if self.cell_index(element.0) != index {
+                    return Err("element lies in the wrong cell");
+                }
+
+                if !self.is_inside(element.0) {
+                    outside += 1;
+                }
+            }
+
+            count += cell.len();
+        }
+
+        if count != self.count || outside != self.outside {
+            return Err("counts do not match the elements");
+        }
+
+        self.slots.check(self.handles.iter().enumerate().flat_map(|(cell, handles)| handles.iter().enumerate().map(move |(index, handle)| (*handle, (cell, index)))))
+    }
+}
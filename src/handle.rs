@@ -0,0 +1,175 @@
+//! Handles which are returned by [`SpatialIndexMut::insert()`](crate::SpatialIndexMut::insert)
+//! and stay valid while the element moves inside of the structure
+
+/// refers to an element of a [SpatialIndexMut](crate::SpatialIndexMut) until it gets removed
+///
+/// A handle stores an index and a generation, the generation of a slot counts up whenever its
+/// element gets removed. So a handle of a removed element never refers to a later element
+///
+/// Structures which are built from many elements at once give the element at index `i` the handle
+/// [`Handle::nth(i)`](Handle::nth), the same one inserting the elements one after another would return
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+impl Handle {
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: index of the element in the elements a structure was built from
+    ///
+    /// returns: Handle the handle of the element in a structure which was built from many elements at once
+    ///
+    pub fn nth(index: usize) -> Handle {
+        Handle {
+            index: u32::try_from(index).expect("can not store more than u32::MAX elements"),
+            generation: 0,
+        }
+    }
+}
+
+/// A generational slot map which stores the location of each element
+///
+/// Structures keep the handle of each element next to it and call [`Slots::set()`] whenever an
+/// element gets moved, so [`Slots::get()`] always finds it in O(1)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "SlotsSnapshot<Location>"))]
+pub(crate) struct Slots<Location> {
+    slots: Vec<Slot<Location>>,
+    /// indexes of the slots which are not used, the last one gets used first
+    free: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Slot<Location> {
+    location: Option<Location>,
+    generation: u32,
+}
+
+impl<Location: Copy> Slots<Location> {
+    pub(crate) fn new() -> Slots<Location> {
+        Slots {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, location: Location) -> Handle {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.location = Some(location);
+
+                Handle { index, generation: slot.generation }
+            }
+            None => {
+                let handle = Handle::nth(self.slots.len());
+                self.slots.push(Slot { location: Some(location), generation: 0 });
+
+                handle
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, handle: Handle) -> Option<Location> {
+        let slot = self.slots.get(handle.index as usize)?;
+
+        if slot.generation != handle.generation {
+            return None;
+        }
+
+        slot.location
+    }
+
+    /// moves the element of a handle which is known to be valid
+    pub(crate) fn set(&mut self, handle: Handle, location: Location) {
+        self.slots[handle.index as usize].location = Some(location);
+    }
+
+    pub(crate) fn remove(&mut self, handle: Handle) -> Option<Location> {
+        let location = self.get(handle)?;
+
+        let slot = &mut self.slots[handle.index as usize];
+        slot.location = None;
+        slot.generation = slot.generation.wrapping_add(1);
+
+        self.free.push(handle.index);
+
+        Some(location)
+    }
+
+    /// removes all elements, none of the handles which were returned so far stay valid
+    pub(crate) fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.location.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Location: Copy + PartialEq> Slots<Location> {
+    /// checks that the slots store exactly the location of each of the `elements`
+    pub(crate) fn check(&self, elements: impl IntoIterator<Item=(Handle, Location)>) -> Result<(), &'static str> {
+        let mut count = 0;
+
+        for (handle, location) in elements {
+            if self.get(handle) != Some(location) {
+                return Err("slot does not point to its element");
+            }
+
+            count += 1;
+        }
+
+        if count != self.slots.len() - self.free.len() {
+            return Err("slot points to a missing element");
+        }
+
+        Ok(())
+    }
+
+    /// the location of every element in no particular order
+    pub(crate) fn locations(&self) -> impl Iterator<Item=Location> + '_ {
+        self.slots.iter().filter_map(|slot| slot.location)
+    }
+}
+
+/// the fields of [Slots] as they are deserialized, [`Slots::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SlotsSnapshot<Location> {
+    slots: Vec<Slot<Location>>,
+    free: Vec<u32>,
+}
+
+#[cfg(feature = "serde")]
+impl<Location> TryFrom<SlotsSnapshot<Location>> for Slots<Location> {
+    type Error = &'static str;
+
+    /// each slot without a location has to be listed once in `free`
+    fn try_from(snapshot: SlotsSnapshot<Location>) -> Result<Self, Self::Error> {
+        let mut listed = vec![false; snapshot.slots.len()];
+
+        for &index in &snapshot.free {
+            match snapshot.slots.get(index as usize) {
+                Some(slot) if slot.location.is_none() && !listed[index as usize] => listed[index as usize] = true,
+                _ => return Err("free slot list is not valid"),
+            }
+        }
+
+        if snapshot.slots.iter().filter(|slot| slot.location.is_none()).count() != snapshot.free.len() {
+            return Err("free slot list is not valid");
+        }
+
+        Ok(Slots {
+            slots: snapshot.slots,
+            free: snapshot.free,
+        })
+    }
+}
@@ -0,0 +1,603 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::{ControlFlow, Range};
+use crate::{SpatialIndex, SpatialIndexMut};
+use crate::config::{check_bounds, ConfigError};
+use crate::handle::{Handle, Slots};
+use crate::shape::Shape;
+
+/// inserted elements are only moved into the tree once there are at least this many of them
+const REBUILD_MIN: usize = 64;
+
+/// Splits the elements at their median, alternating to whichever axis is spread wider
+///
+/// The tree is implicit: the element in the middle of a range splits it and the halves to its left
+/// and right are its children. So the tree is always balanced, which suits clustered elements
+///
+/// Inserted elements are kept in a small unsorted buffer and the whole tree gets rebuilt once the
+/// buffer holds a quarter of the tree, [`KdTree::from_elements()`] builds it in one go. Removed
+/// elements are only marked until a quarter of the tree is removed
+///
+/// Positions outside of the bounds, which can only be stored with `insert_unchecked`, are still
+/// found by every query which contains them
+///
+/// A KdTree is `Send` and `Sync` whenever `Data` is, so it can be queried from many threads at once
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "KdTreeSnapshot<Data>"))]
+pub struct KdTree<Data: Copy> {
+    elements: Vec<((f64, f64), Data)>,
+    /// the axis the element at the same index splits on, true for y
+    axes: Vec<bool>,
+    /// the handle of each element at the same index in `elements`
+    handles: Vec<Handle>,
+
+    /// true for the elements which were removed but are still part of the tree
+    removed: Vec<bool>,
+    removed_count: usize,
+
+    /// inserted elements which are not part of the tree yet
+    pending: Vec<((f64, f64), Data)>,
+    pending_handles: Vec<Handle>,
+
+    slots: Slots<Location>,
+
+    /// min and max corner of the elements in the tree, which reaches past the bounds for elements
+    /// added with `insert_unchecked`
+    extent: ((f64, f64), (f64, f64)),
+
+    x: Range<f64>,
+    y: Range<f64>,
+}
+
+impl<Data: Copy> KdTree<Data> {
+    /// create an empty KdTree
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    /// panics if the bounds are not valid, [`KdTree::try_new()`] returns the reason instead
+    ///
+    pub fn new(x: Range<f64>, y: Range<f64>) -> KdTree<Data> {
+        KdTree::try_new(x, y).unwrap_or_else(|error| panic!("tried to create a KdTree from invalid settings: {}", error))
+    }
+
+    /// like [`KdTree::new()`] but returns a ConfigError if the bounds are not valid
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    /// returns: Result<KdTree<Data>, ConfigError>
+    ///
+    pub fn try_new(x: Range<f64>, y: Range<f64>) -> Result<KdTree<Data>, ConfigError> {
+        check_bounds(&x, &y)?;
+
+        Ok(KdTree {
+            elements: Vec::new(),
+            axes: Vec::new(),
+            handles: Vec::new(),
+            removed: Vec::new(),
+            removed_count: 0,
+            pending: Vec::new(),
+            pending_handles: Vec::new(),
+            slots: Slots::new(),
+            extent: extent(&[], &|position: &(f64, f64)| *position),
+            x,
+            y,
+        })
+    }
+
+    /// builds a KdTree from many elements at once in O(n log n)
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `elements`: (position, data) of each element, all of them have to lie inside of the bounds
+    ///
+    /// panics if the bounds are not valid, [`KdTree::try_from_elements()`] returns the reason instead
+    ///
+    pub fn from_elements(x: Range<f64>, y: Range<f64>, elements: &[((f64, f64), Data)]) -> KdTree<Data> {
+        KdTree::try_from_elements(x, y, elements).unwrap_or_else(|error| panic!("tried to create a KdTree from invalid settings: {}", error))
+    }
+
+    /// like [`KdTree::from_elements()`] but returns a ConfigError if the bounds are not valid
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `elements`: (position, data) of each element, panics if one lies outside of the bounds
+    ///
+    /// returns: Result<KdTree<Data>, ConfigError>
+    ///
+    pub fn try_from_elements(x: Range<f64>, y: Range<f64>, elements: &[((f64, f64), Data)]) -> Result<KdTree<Data>, ConfigError> {
+        let mut kd_tree = KdTree::try_new(x, y)?;
+
+        // the handles are given out in the order of the elements
+        for element in elements {
+            kd_tree.check_bounds(element.0);
+
+            let handle = kd_tree.slots.insert(Location::Pending(kd_tree.pending.len()));
+
+            kd_tree.pending.push(*element);
+            kd_tree.pending_handles.push(handle);
+        }
+
+        kd_tree.rebuild();
+
+        Ok(kd_tree)
+    }
+
+    /// moves all inserted elements into the tree, drops the removed ones and balances it
+    pub fn rebuild(&mut self) {
+        let mut entries: Vec<_> = self.elements.iter().zip(&self.handles).zip(&self.removed)
+            .filter(|(_, removed)| !**removed)
+            .map(|((element, handle), _)| (*element, *handle))
+            .chain(self.pending.drain(..).zip(self.pending_handles.drain(..)))
+            .collect();
+
+        self.axes = vec![false; entries.len()];
+        self.extent = extent(&entries, &|entry| entry.0.0);
+        build(&mut entries, &mut self.axes, &|entry| entry.0.0);
+
+        (self.elements, self.handles) = entries.into_iter().unzip();
+        self.removed = vec![false; self.elements.len()];
+        self.removed_count = 0;
+
+        for (index, handle) in self.handles.iter().enumerate() {
+            self.slots.set(*handle, Location::Tree(index));
+        }
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: (Range<f64>, Range<f64>) min_x..max_x and min_y..max_y the area in wich data can be inserted
+    ///
+    pub fn bounds(&self) -> (Range<f64>, Range<f64>) {
+        (self.x.clone(), self.y.clone())
+    }
+
+    /// gets the DataValue which is the closest to `position`
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: the position to search from
+    ///
+    /// returns: Option<Data> None if there are no DataValues
+    ///
+    pub fn nearest(&self, position: (f64, f64)) -> Option<Data> {
+        self.k_nearest(position, 1).first().copied()
+    }
+
+    /// gets the `k` DataValues which are the closest to `position`
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: the position to search from
+    /// * `k`: how many DataValues should be returned at most
+    ///
+    /// returns: Vec<Data> sorted from the closest to the farthest
+    ///
+    pub fn k_nearest(&self, position: (f64, f64), k: usize) -> Vec<Data> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut best = BinaryHeap::with_capacity(k + 1);
+
+        self.search_nearest(0..self.elements.len(), position, k, &mut best);
+
+        for element in &self.pending {
+            offer(&mut best, k, Candidate { distance: distance_squared(position, element.0), data: element.1 });
+        }
+
+        best.into_sorted_vec().into_iter().map(|candidate| candidate.data).collect()
+    }
+
+    fn check_bounds(&self, position: (f64, f64)) {
+        if position.0 < self.x.start || position.0 >= self.x.end || position.1 < self.y.start || position.1 >= self.y.end {
+            panic!("tried to insert position into KdTree which was out of bounce")
+        }
+    }
+
+    /// rebuilds the tree once enough elements were inserted or removed since the last build
+    fn rebuild_if_needed(&mut self) {
+        let inserted = self.pending.len() >= REBUILD_MIN && self.pending.len() * 4 >= self.elements.len();
+        let removed = self.removed_count > 0 && self.removed_count * 4 >= self.elements.len();
+
+        if inserted || removed {
+            self.rebuild();
+        }
+    }
+
+    /// takes the element out of the tree or the pending elements, its handle is not removed
+    fn take(&mut self, location: Location) -> ((f64, f64), Data) {
+        match location {
+            Location::Tree(index) => {
+                self.removed[index] = true;
+                self.removed_count += 1;
+
+                self.elements[index]
+            }
+            Location::Pending(index) => {
+                self.pending_handles.swap_remove(index);
+
+                if let Some(moved) = self.pending_handles.get(index) {
+                    self.slots.set(*moved, Location::Pending(index));
+                }
+
+                self.pending.swap_remove(index)
+            }
+        }
+    }
+
+    fn search_nearest(&self, range: Range<usize>, position: (f64, f64), k: usize, best: &mut BinaryHeap<Candidate<Data>>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mid = range.start + range.len() / 2;
+        let element = &self.elements[mid];
+
+        if !self.removed[mid] {
+            offer(best, k, Candidate { distance: distance_squared(position, element.0), data: element.1 });
+        }
+
+        let offset = coordinate(position, self.axes[mid]) - coordinate(element.0, self.axes[mid]);
+
+        let (near, far) = if offset < 0.0 {
+            (range.start..mid, mid + 1..range.end)
+        } else {
+            (mid + 1..range.end, range.start..mid)
+        };
+
+        self.search_nearest(near, position, k, best);
+
+        // the far side can only hold closer elements if the split is closer than the current worst
+        if best.len() < k || best.peek().is_some_and(|worst| offset.powi(2) <= worst.distance) {
+            self.search_nearest(far, position, k, best);
+        }
+    }
+
+    /// searches the elements in `range` which all lie in the box from `min` to `max`
+    fn search<S: Shape>(&self, range: Range<usize>, min: (f64, f64), max: (f64, f64), shape: &S, visit: &mut impl FnMut(Hit<Data>) -> ControlFlow<()>) -> ControlFlow<()> {
+        if range.is_empty() {
+            return ControlFlow::Continue(());
+        }
+
+        // testing the box only pays off if it saves enough distance checks
+        if range.len() > 4 {
+            // removed elements have to be skipped one by one
+            if self.removed_count == 0 && shape.contains_box(min, max) {
+                return visit(Hit::All(&self.elements[range]));
+            }
+
+            if !shape.intersects_box(min, max) {
+                return ControlFlow::Continue(());
+            }
+        }
+
+        let mid = range.start + range.len() / 2;
+        let element = &self.elements[mid];
+
+        if !self.removed[mid] && shape.contains(element.0) {
+            visit(Hit::One(element))?;
+        }
+
+        let (left_max, right_min) = if self.axes[mid] {
+            ((max.0, element.0.1), (min.0, element.0.1))
+        } else {
+            ((element.0.0, max.1), (element.0.0, min.1))
+        };
+
+        self.search(range.start..mid, min, left_max, shape, visit)?;
+        self.search(mid + 1..range.end, right_min, max, shape, visit)
+    }
+
+    fn search_all<S: Shape>(&self, shape: &S, visit: &mut impl FnMut(Hit<Data>) -> ControlFlow<()>) -> ControlFlow<()> {
+        self.search(0..self.elements.len(), self.extent.0, self.extent.1, shape, visit)?;
+
+        for element in &self.pending {
+            if shape.contains(element.0) {
+                visit(Hit::One(element))?;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+impl<Data: Copy> SpatialIndex<Data> for KdTree<Data> {
+    fn count(&self) -> usize {
+        self.elements.len() - self.removed_count + self.pending.len()
+    }
+
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let _ = self.search_all(shape, &mut |hit| {
+            match hit {
+                Hit::All(elements) => data.extend(elements.iter().map(|x| x.1)),
+                Hit::One(element) => data.push(element.1),
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    fn query_count<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let _ = self.search_all(shape, &mut |hit| {
+            match hit {
+                Hit::All(elements) => count += elements.len(),
+                Hit::One(_) => count += 1,
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    fn query_any<S: Shape>(&self, shape: &S) -> bool {
+        self.search_all(shape, &mut |_| ControlFlow::Break(())).is_break()
+    }
+}
+
+impl<Data: Copy> SpatialIndexMut<Data> for KdTree<Data> {
+    fn insert(&mut self, position: (f64, f64), data: Data) -> Handle {
+        self.check_bounds(position);
+
+        self.insert_unchecked(position, data)
+    }
+
+    fn insert_unchecked(&mut self, position: (f64, f64), data: Data) -> Handle {
+        let handle = self.slots.insert(Location::Pending(self.pending.len()));
+
+        self.pending.push((position, data));
+        self.pending_handles.push(handle);
+
+        self.rebuild_if_needed();
+
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<((f64, f64), Data)> {
+        match self.slots.get(handle)? {
+            Location::Tree(index) => Some(self.elements[index]),
+            Location::Pending(index) => Some(self.pending[index]),
+        }
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<Data> {
+        let location = self.slots.remove(handle)?;
+        let (_, data) = self.take(location);
+
+        self.rebuild_if_needed();
+
+        Some(data)
+    }
+
+    fn update_position(&mut self, handle: Handle, position: (f64, f64)) -> bool {
+        self.check_bounds(position);
+
+        let Some(location) = self.slots.get(handle) else {
+            return false;
+        };
+
+        let (_, data) = self.take(location);
+
+        self.slots.set(handle, Location::Pending(self.pending.len()));
+        self.pending.push((position, data));
+        self.pending_handles.push(handle);
+
+        self.rebuild_if_needed();
+
+        true
+    }
+
+    fn clear(&mut self) {
+        self.elements.clear();
+        self.axes.clear();
+        self.handles.clear();
+        self.removed.clear();
+        self.removed_count = 0;
+        self.pending.clear();
+        self.pending_handles.clear();
+        self.slots.clear();
+    }
+}
+
+/// where the element of a handle is stored
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Location {
+    /// index in `elements`
+    Tree(usize),
+    /// index in `pending`
+    Pending(usize),
+}
+
+/// the fields of a [KdTree] as they are deserialized, [`KdTree::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct KdTreeSnapshot<Data> {
+    elements: Vec<((f64, f64), Data)>,
+    axes: Vec<bool>,
+    handles: Vec<Handle>,
+    removed: Vec<bool>,
+    removed_count: usize,
+    pending: Vec<((f64, f64), Data)>,
+    pending_handles: Vec<Handle>,
+    slots: Slots<Location>,
+    extent: ((f64, f64), (f64, f64)),
+    x: Range<f64>,
+    y: Range<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> TryFrom<KdTreeSnapshot<Data>> for KdTree<Data> {
+    type Error = &'static str;
+
+    fn try_from(snapshot: KdTreeSnapshot<Data>) -> Result<Self, Self::Error> {
+        let kd_tree = KdTree {
+            elements: snapshot.elements,
+            axes: snapshot.axes,
+            handles: snapshot.handles,
+            removed: snapshot.removed,
+            removed_count: snapshot.removed_count,
+            pending: snapshot.pending,
+            pending_handles: snapshot.pending_handles,
+            slots: snapshot.slots,
+            extent: snapshot.extent,
+            x: snapshot.x,
+            y: snapshot.y,
+        };
+
+        kd_tree.validate()?;
+
+        Ok(kd_tree)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> KdTree<Data> {
+    /// checks everything the other methods rely on, so a corrupted snapshot can not make them panic
+    /// or miss elements
+    fn validate(&self) -> Result<(), &'static str> {
+        if check_bounds(&self.x, &self.y).is_err() {
+            return Err("bounds are not valid");
+        }
+
+        if self.axes.len() != self.elements.len() || self.handles.len() != self.elements.len() || self.removed.len() != self.elements.len() {
+            return Err("elements, axes, handles and removed differ in length");
+        }
+
+        if self.pending_handles.len() != self.pending.len() {
+            return Err("pending elements and their handles differ in length");
+        }
+
+        if self.removed.iter().filter(|removed| **removed).count() != self.removed_count {
+            return Err("removed_count does not match the removed elements");
+        }
+
+        if !self.splits(0..self.elements.len(), self.extent.0, self.extent.1) {
+            return Err("element lies outside of its subtree");
+        }
+
+        let tree = self.handles.iter().zip(&self.removed).enumerate()
+            .filter(|(_, (_, removed))| !**removed)
+            .map(|(index, (handle, _))| (*handle, Location::Tree(index)));
+        let pending = self.pending_handles.iter().enumerate().map(|(index, handle)| (*handle, Location::Pending(index)));
+
+        self.slots.check(tree.chain(pending))
+    }
+
+    /// whether every element in `range` lies in the box from `min` to `max` and in the box its
+    /// splits leave for it, which are the boxes `search` prunes with
+    fn splits(&self, range: Range<usize>, min: (f64, f64), max: (f64, f64)) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+
+        let mid = range.start + range.len() / 2;
+        let position = self.elements[mid].0;
+
+        // comparisons with NaN fail, so neither a NaN position nor a split at NaN counts as outside
+        let outside = position.0 < min.0 || position.0 > max.0 || position.1 < min.1 || position.1 > max.1;
+
+        let (left_max, right_min) = if self.axes[mid] {
+            ((max.0, position.1), (min.0, position.1))
+        } else {
+            ((position.0, max.1), (position.0, min.1))
+        };
+
+        !outside && self.splits(range.start..mid, min, left_max) && self.splits(mid + 1..range.end, right_min, max)
+    }
+}
+
+enum Hit<'a, Data> {
+    /// every element of the subtree lies in the shape
+    All(&'a [((f64, f64), Data)]),
+    One(&'a ((f64, f64), Data)),
+}
+
+/// moves the median to the middle of `elements` and builds both halves the same way
+fn build<T>(elements: &mut [T], axes: &mut [bool], position: &impl Fn(&T) -> (f64, f64)) {
+    if elements.is_empty() {
+        return;
+    }
+
+    let (min, max) = extent(elements, position);
+
+    let axis = max.1 - min.1 > max.0 - min.0;
+    let mid = elements.len() / 2;
+
+    elements.select_nth_unstable_by(mid, |a, b| coordinate(position(a), axis).total_cmp(&coordinate(position(b), axis)));
+    axes[mid] = axis;
+
+    let (left, right) = elements.split_at_mut(mid);
+    let (left_axes, right_axes) = axes.split_at_mut(mid);
+
+    build(left, left_axes, position);
+    build(&mut right[1..], &mut right_axes[1..], position);
+}
+
+/// the min and max corner of the positions, infinitely small without any
+fn extent<T>(elements: &[T], position: &impl Fn(&T) -> (f64, f64)) -> ((f64, f64), (f64, f64)) {
+    elements.iter().map(position).fold(((f64::INFINITY, f64::INFINITY), (f64::NEG_INFINITY, f64::NEG_INFINITY)), |(min, max), position| {
+        ((min.0.min(position.0), min.1.min(position.1)), (max.0.max(position.0), max.1.max(position.1)))
+    })
+}
+
+fn coordinate(position: (f64, f64), axis: bool) -> f64 {
+    if axis { position.1 } else { position.0 }
+}
+
+/// keeps the `k` closest candidates
+fn offer<Data>(best: &mut BinaryHeap<Candidate<Data>>, k: usize, candidate: Candidate<Data>) {
+    if best.len() < k {
+        best.push(candidate);
+    } else if best.peek().is_some_and(|worst| candidate.distance < worst.distance) {
+        best.pop();
+        best.push(candidate);
+    }
+}
+
+/// an element found by a nearest search, the farthest one gets popped first
+struct Candidate<Data> {
+    distance: f64,
+    data: Data,
+}
+
+impl<Data> PartialEq for Candidate<Data> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Data> Eq for Candidate<Data> {}
+
+impl<Data> PartialOrd for Candidate<Data> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Data> Ord for Candidate<Data> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance)
+    }
+}
+
+fn distance_squared(pos1: (f64, f64), pos2: (f64, f64)) -> f64 {
+    (pos1.0 - pos2.0).powi(2) + (pos1.1 - pos2.1).powi(2)
+}
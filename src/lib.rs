@@ -7,59 +7,282 @@
 //! DataStructures
 //! - [QuadTree]
 //! - Grid | SpatialHash
+//! - LinearQuadTree sorted by [Morton code](https://en.wikipedia.org/wiki/Z-order_curve)
+//! - [KdTree] for clustered elements and nearest neighbour searches
+//! - [RTree] for boxes instead of positions
+//! - [Bvh] for boxes which move every frame e.g. the broadphase of a physics engine
+//!
+//! [BruteForce](brute_force::BruteForce) tests every element for each query, it is the reference the other structures get tested against
+//!
+//! Read-heavy workloads can build a [StaticGrid](static_grid::StaticGrid) once, which stores all elements in one contiguous array
+//!
+//! All structures are searched through [SpatialIndex], the ones which can be changed after they were
+//! built also implement [SpatialIndexMut]. Inserting returns a [Handle](handle::Handle) which gets,
+//! moves or removes the element later on
+//!
+//! [Grid::builder()](grid::Grid::builder) and [QuadTree::builder()](quad_tree::QuadTree::builder) configure a structure
+//! step by step and return a [ConfigError](config::ConfigError) instead of panicking for settings which are not valid
+//!
+//! Queries take a [Shape](shape::Shape) e.g. [Circle](shape::Circle), [Ring](shape::Ring) or [Rect](shape::Rect)
+//! and circles can measure their distance with any [Metric](metric::Metric)
+//!
+//! [`Grid::stats()`](grid::Grid::stats) and [`QuadTree::stats()`](quad_tree::QuadTree::stats) report how the elements are divided and
+//! [`Grid::query_with_stats()`](grid::Grid::query_with_stats) counts what a single query did, see [stats]
+//!
+//! A built [Grid](grid::Grid) or [QuadTree](quad_tree::QuadTree) can be written to a flat binary format and queried
+//! straight from its bytes with the views in [frozen]
+//!
+//! (latitude, longitude) positions can be searched by great-circle distance with [GeoGrid](geo::GeoGrid) and [GeoQuadTree](geo::GeoQuadTree)
+//!
+//! # Features
+//!
+//! - `rayon`: parallel batch queries and parallel bulk construction
+//! - `testing`: [testing] runs random workloads against two structures and compares their answers
+//! - `proptest`: [strategy] generates bounds, positions, queries and workloads for [proptest](https://docs.rs/proptest), implies `testing`
+//! - `svg`: [svg] draws a [Grid](grid::Grid) or [QuadTree](quad_tree::QuadTree) and the cells or nodes a query circle reaches as an SVG image
+//! - `serde`: `Serialize` and `Deserialize` for [Grid](grid::Grid), [StaticGrid](static_grid::StaticGrid), [QuadTree](quad_tree::QuadTree), [LinearQuadTree](linear_quad_tree::LinearQuadTree), [KdTree](kd_tree::KdTree), [RTree](r_tree::RTree) and [Bvh](bvh::Bvh), deserializing checks the structure and rejects corrupted data with an error
 //!
 //! [QuadTree]: https://en.wikipedia.org/wiki/Quadtree
+//! [KdTree]: https://en.wikipedia.org/wiki/K-d_tree
+//! [RTree]: https://en.wikipedia.org/wiki/R*-tree
+//! [Bvh]: https://en.wikipedia.org/wiki/Bounding_volume_hierarchy
 
 pub(crate) mod util;
+pub mod brute_force;
+pub mod bvh;
+pub mod config;
+pub mod frozen;
+pub mod geo;
 pub mod grid;
+pub mod handle;
+pub mod kd_tree;
+pub mod linear_quad_tree;
+pub mod metric;
 pub mod quad_tree;
+pub mod r_tree;
+pub mod shape;
+pub mod static_grid;
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "svg")]
+pub mod svg;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 extern crate core;
 
 use std::ops::Range;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use crate::handle::Handle;
+use crate::metric::Metric;
+use crate::shape::{Circle, Rect, Ring, Shape};
+
+/// Searches the stored DataValues, implemented by every structure which stores positions
+///
+/// Generic code which only reads can take `&impl SpatialIndex<Data>`, so it also works with
+/// read-only structures like [StaticGrid](static_grid::StaticGrid) and the views in [frozen]
+pub trait SpatialIndex<Data: Copy> {
+    /// returns the count of DataValues which are currently stored
+    fn count(&self) -> usize;
+
+    /// gets all DataValues in the given shape
+    ///
+    /// # Arguments
+    ///
+    /// * `shape`: the area which gets searched
+    ///
+    /// returns: Vec<Data>
+    ///
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data>;
+
+    /// counts the DataValues in the given shape without collecting them
+    ///
+    /// # Arguments
+    ///
+    /// * `shape`: the area which gets searched
+    ///
+    /// returns: usize
+    ///
+    fn query_count<S: Shape>(&self, shape: &S) -> usize;
+
+    /// checks if there is any DataValue in the given shape, stops at the first one found
+    ///
+    /// # Arguments
+    ///
+    /// * `shape`: the area which gets searched
+    ///
+    /// returns: bool
+    ///
+    fn query_any<S: Shape>(&self, shape: &S) -> bool;
+
+    /// gets all DataValues in the given search circle
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the search circle
+    /// * `radius`: radius of the search circle
+    ///
+    /// returns: Vec<Data>
+    ///
+    fn in_circle(&self, position: (f64, f64), radius: f64) -> Vec<Data> {
+        self.query(&Circle::new(position, radius))
+    }
+
+    /// gets all DataValues in the given search circle measured with a custom [Metric]
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the search circle
+    /// * `radius`: radius of the search circle
+    /// * `metric`: how the distance to `position` is measured e.g. [Manhattan](metric::Manhattan)
+    ///
+    /// returns: Vec<Data>
+    ///
+    fn in_circle_with<M: Metric>(&self, position: (f64, f64), radius: f64, metric: M) -> Vec<Data> {
+        self.query(&Circle::with_metric(position, radius, metric))
+    }
+
+    /// gets all DataValues in the given search ring
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the search ring
+    /// * `inner`: radius of the excluded inner circle, DataValues closer than this are not returned
+    /// * `outer`: radius of the outer circle
+    ///
+    /// returns: Vec<Data>
+    ///
+    fn in_ring(&self, position: (f64, f64), inner: f64, outer: f64) -> Vec<Data> {
+        self.query(&Ring::new(position, inner, outer))
+    }
+
+    /// gets all DataValues in the given search rectangle
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x of the search rectangle
+    /// * `y`: min_y..max_y of the search rectangle
+    ///
+    /// returns: Vec<Data>
+    ///
+    fn in_rect(&self, x: Range<f64>, y: Range<f64>) -> Vec<Data> {
+        self.query(&Rect::new(x, y))
+    }
+
+    /// counts the DataValues in the given search circle
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the search circle
+    /// * `radius`: radius of the search circle
+    ///
+    /// returns: usize
+    ///
+    fn count_in_circle(&self, position: (f64, f64), radius: f64) -> usize {
+        self.query_count(&Circle::new(position, radius))
+    }
+
+    /// counts the DataValues in the given search rectangle
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x of the search rectangle
+    /// * `y`: min_y..max_y of the search rectangle
+    ///
+    /// returns: usize
+    ///
+    fn count_in_rect(&self, x: Range<f64>, y: Range<f64>) -> usize {
+        self.query_count(&Rect::new(x, y))
+    }
 
-pub trait SpatialPartitioner<Data: Copy> {
-    /// create a new SpatialPartitioner which can handle positions in the given ranges
+    /// checks if there is any DataValue in the given search circle
     ///
     /// # Arguments
     ///
-    /// * `x`: min_x..max_x defines the area in wich data can be inserted
-    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `position`: center of the search circle
+    /// * `radius`: radius of the search circle
+    ///
+    /// returns: bool
     ///
+    fn any_in_circle(&self, position: (f64, f64), radius: f64) -> bool {
+        self.query_any(&Circle::new(position, radius))
+    }
 
-    fn new(x: Range<f64>, y: Range<f64>) -> Self;
+    /// gets the DataValues of many search circles in parallel
+    ///
+    /// # Arguments
+    ///
+    /// * `queries`: (position, radius) of each search circle
+    ///
+    /// returns: Vec<Vec<Data>> the result of each search circle in the same order as `queries`
+    ///
+    #[cfg(feature = "rayon")]
+    fn par_in_circle_batch(&self, queries: &[((f64, f64), f64)]) -> Vec<Vec<Data>> where Self: Sync, Data: Send {
+        queries.par_iter().map(|(position, radius)| self.in_circle(*position, *radius)).collect()
+    }
+}
+
+/// Inserts, moves and removes DataValues, implemented by every structure which can be changed after it was built
+///
+/// Each structure has its own constructors e.g. [`Grid::with_cell_count()`](grid::Grid::with_cell_count)
+pub trait SpatialIndexMut<Data: Copy>: SpatialIndex<Data> {
     /// inserts an obj and checks if position is out of bounce
     ///
     /// # Arguments
     ///
     /// * `position`: position of the data (x,y)
     /// * `data`: the DataValue which will be return when queued
-    /// e.g index of an other list which stores the "real" data
+    ///   e.g index of an other list which stores the "real" data
+    ///
+    /// returns: Handle to get, move or remove the obj later
     ///
-    fn insert(&mut self, position: (f64, f64), data: Data);
+    fn insert(&mut self, position: (f64, f64), data: Data) -> Handle;
     /// inserts an obj and DOES NOT check if position is out of bounce
     ///
+    /// positions outside of the bounds are not guaranteed to be found by queries
+    ///
     /// # Arguments
     ///
     /// * `position`: position of the data (x,y)
     /// * `data`: the DataValue which will be return when queued.
-    /// e.g index of an other list which stores the "real" data
+    ///   e.g index of an other list which stores the "real" data
     ///
-    fn insert_unchecked(&mut self, position: (f64, f64), data: Data);
+    /// returns: Handle to get, move or remove the obj later
+    ///
+    fn insert_unchecked(&mut self, position: (f64, f64), data: Data) -> Handle;
 
-    /// returns the count of DataValues which are currently stored
-    fn count(&self) -> usize;
+    ///
+    /// # Arguments
+    ///
+    /// * `handle`: the handle returned by [`SpatialIndexMut::insert()`]
+    ///
+    /// returns: Option<((f64, f64), Data)> position and data of the obj, None if it was removed
+    ///
+    fn get(&self, handle: Handle) -> Option<((f64, f64), Data)>;
 
-    fn clear(&mut self);
+    /// removes an obj
+    ///
+    /// # Arguments
+    ///
+    /// * `handle`: the handle returned by [`SpatialIndexMut::insert()`]
+    ///
+    /// returns: Option<Data> None if the obj was already removed
+    ///
+    fn remove(&mut self, handle: Handle) -> Option<Data>;
 
-    /// gets all DataValues in the given search circle
+    /// moves an obj to a new position and checks if position is out of bounce, the handle stays valid
     ///
     /// # Arguments
     ///
-    /// * `position`: center of the search circle
-    /// * `radius`: radius of the search circle
+    /// * `handle`: the handle returned by [`SpatialIndexMut::insert()`]
+    /// * `position`: the new position of the obj (x,y)
     ///
-    /// returns: Vec<Data>
+    /// returns: bool false if the obj was already removed
     ///
-    fn in_circle(&self, position: (f64, f64), radius: f64) -> Vec<Data>;
-}
\ No newline at end of file
+    fn update_position(&mut self, handle: Handle, position: (f64, f64)) -> bool;
+
+    /// removes all objs, none of the handles which were returned so far stay valid
+    fn clear(&mut self);
+}
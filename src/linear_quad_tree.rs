@@ -0,0 +1,521 @@
+use std::ops::{ControlFlow, Range};
+use crate::{SpatialIndex, SpatialIndexMut};
+use crate::config::{check_bounds, ConfigError};
+use crate::handle::{Handle, Slots};
+use crate::shape::Shape;
+use crate::util::CellAxis;
+
+/// bits of the Morton code which are used by each axis
+const DEPTH: u32 = 32;
+
+/// nodes with at most this many elements test their elements instead of being split further
+const LEAF_SIZE: usize = 32;
+
+/// A QuadTree which stores its elements sorted by their Morton code (Z-order) in flat arrays
+///
+/// Every node of the implicit tree is a contiguous range of codes, so a query splits the shape into
+/// nodes and finds the elements of each node with a binary search. The sorted arrays can be
+/// scanned or uploaded as they are with [`LinearQuadTree::codes()`] and [`LinearQuadTree::elements()`]
+///
+/// Inserting and removing shifts all elements with a larger code, so [`LinearQuadTree::from_elements()`]
+/// should be preferred for many elements
+///
+/// Positions outside of the bounds, which can only be stored with `insert_unchecked`, get the code
+/// of the closest node on the border and are still found by every query which contains them.
+/// Positions with a NaN coordinate are never found
+///
+/// A LinearQuadTree is `Send` and `Sync` whenever `Data` is, so it can be queried from many threads at once
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "LinearQuadTreeSnapshot<Data>"))]
+pub struct LinearQuadTree<Data: Copy> {
+    codes: Vec<u64>,
+    elements: Vec<((f64, f64), Data)>,
+    /// the handle of each element at the same index in `elements`
+    handles: Vec<Handle>,
+    /// the index in `elements` of each element
+    slots: Slots<usize>,
+
+    /// width and height of the smallest node
+    cell_size: (f64, f64),
+
+    x: Range<f64>,
+    y: Range<f64>,
+
+    /// count of elements outside of the bounds, as long as there are any the nodes on the border
+    /// can not be accepted or rejected as a whole
+    outside: usize,
+}
+
+impl<Data: Copy> LinearQuadTree<Data> {
+    /// create an empty LinearQuadTree
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    /// panics if the bounds are not valid, [`LinearQuadTree::try_new()`] returns the reason instead
+    ///
+    pub fn new(x: Range<f64>, y: Range<f64>) -> LinearQuadTree<Data> {
+        LinearQuadTree::try_new(x, y).unwrap_or_else(|error| panic!("tried to create a LinearQuadTree from invalid settings: {}", error))
+    }
+
+    /// like [`LinearQuadTree::new()`] but returns a ConfigError if the bounds are not valid
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    /// returns: Result<LinearQuadTree<Data>, ConfigError>
+    ///
+    pub fn try_new(x: Range<f64>, y: Range<f64>) -> Result<LinearQuadTree<Data>, ConfigError> {
+        check_bounds(&x, &y)?;
+
+        let cells = (1u64 << DEPTH) as f64;
+
+        Ok(LinearQuadTree {
+            codes: Vec::new(),
+            elements: Vec::new(),
+            handles: Vec::new(),
+            slots: Slots::new(),
+            cell_size: ((x.end - x.start) / cells, (y.end - y.start) / cells),
+            x,
+            y,
+            outside: 0,
+        })
+    }
+
+    /// builds a LinearQuadTree from many elements at once with a single sort
+    ///
+    /// the result is the same as inserting the elements one after another
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `elements`: (position, data) of each element, all of them have to lie inside of the bounds
+    ///
+    /// panics if the bounds are not valid, [`LinearQuadTree::try_from_elements()`] returns the reason instead
+    ///
+    pub fn from_elements(x: Range<f64>, y: Range<f64>, elements: &[((f64, f64), Data)]) -> LinearQuadTree<Data> {
+        LinearQuadTree::try_from_elements(x, y, elements).unwrap_or_else(|error| panic!("tried to create a LinearQuadTree from invalid settings: {}", error))
+    }
+
+    /// like [`LinearQuadTree::from_elements()`] but returns a ConfigError if the bounds are not valid
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `elements`: (position, data) of each element, panics if one lies outside of the bounds
+    ///
+    /// returns: Result<LinearQuadTree<Data>, ConfigError>
+    ///
+    pub fn try_from_elements(x: Range<f64>, y: Range<f64>, elements: &[((f64, f64), Data)]) -> Result<LinearQuadTree<Data>, ConfigError> {
+        let mut linear_quad_tree = LinearQuadTree::try_new(x, y)?;
+
+        let mut coded: Vec<_> = elements.iter().enumerate().map(|(i, element)| {
+            linear_quad_tree.check_bounds(element.0);
+
+            (linear_quad_tree.code(element.0), *element, Handle::nth(i))
+        }).collect();
+
+        // the sort is stable so equal codes keep the insertion order
+        coded.sort_by_key(|element| element.0);
+
+        linear_quad_tree.codes = coded.iter().map(|element| element.0).collect();
+        linear_quad_tree.elements = coded.iter().map(|element| element.1).collect();
+        linear_quad_tree.handles = coded.iter().map(|element| element.2).collect();
+
+        // the handles are given out in the order of the elements
+        for _ in elements {
+            linear_quad_tree.slots.insert(0);
+        }
+
+        linear_quad_tree.relocate(0);
+
+        Ok(linear_quad_tree)
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: position inside the bounds, positions outside are moved to the closest border
+    ///
+    /// returns: u64 the Morton code of the position, x uses the even and y the odd bits
+    ///
+    pub fn code(&self, position: (f64, f64)) -> u64 {
+        let (x, y) = self.axes();
+
+        spread(x.index(position.0) as u32) | (spread(y.index(position.1) as u32) << 1)
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: &[u64] the Morton code of each element in ascending order
+    ///
+    pub fn codes(&self) -> &[u64] {
+        &self.codes
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: &[((f64, f64), Data)] the Elements in the same order as their codes
+    ///
+    pub fn elements(&self) -> &[((f64, f64), Data)] {
+        &self.elements
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: (Range<f64>, Range<f64>) min_x..max_x and min_y..max_y the area in wich data can be inserted
+    ///
+    pub fn bounds(&self) -> (Range<f64>, Range<f64>) {
+        (self.x.clone(), self.y.clone())
+    }
+
+    fn check_bounds(&self, position: (f64, f64)) {
+        if position.0 < self.x.start || position.0 >= self.x.end || position.1 < self.y.start || position.1 >= self.y.end {
+            panic!("tried to insert position into LinearQuadTree which was out of bounce")
+        }
+    }
+
+    /// false for positions outside of the bounds and positions with a NaN coordinate
+    fn is_inside(&self, position: (f64, f64)) -> bool {
+        self.x.contains(&position.0) && self.y.contains(&position.1)
+    }
+
+    /// stores the index of every element from `start` on in its slot
+    fn relocate(&mut self, start: usize) {
+        for (index, handle) in self.handles.iter().enumerate().skip(start) {
+            self.slots.set(*handle, index);
+        }
+    }
+
+    fn place(&mut self, position: (f64, f64), data: Data, handle: Handle) {
+        let code = self.code(position);
+        let index = self.codes.partition_point(|other| *other <= code);
+
+        self.codes.insert(index, code);
+        self.elements.insert(index, (position, data));
+        self.handles.insert(index, handle);
+
+        self.relocate(index);
+    }
+
+    fn take(&mut self, index: usize) -> ((f64, f64), Data) {
+        self.codes.remove(index);
+        self.handles.remove(index);
+        let element = self.elements.remove(index);
+
+        self.relocate(index);
+
+        if !self.is_inside(element.0) {
+            self.outside -= 1;
+        }
+
+        element
+    }
+
+    /// searches the node whose codes start at `first` and which covers `2^level` by `2^level` of the smallest nodes
+    ///
+    /// `elements` is the range of the elements of the node
+    fn search<S: Shape>(&self, first: u64, level: u32, elements: Range<usize>, shape: &S, visit: &mut impl FnMut(Hit<Data>) -> ControlFlow<()>) -> ControlFlow<()> {
+        if elements.is_empty() {
+            return ControlFlow::Continue(());
+        }
+
+        // the box of a node on the border says nothing about the elements outside of the bounds
+        if !(self.outside > 0 && is_border_node(first, level)) {
+            let (min, max) = self.node_box(first, level);
+
+            if !shape.intersects_box(min, max) {
+                return ControlFlow::Continue(());
+            }
+
+            if shape.contains_box(min, max) {
+                return visit(Hit::All(&self.elements[elements]));
+            }
+        }
+
+        if elements.len() <= LEAF_SIZE || level == 0 {
+            for element in &self.elements[elements] {
+                if shape.contains(element.0) {
+                    visit(Hit::One(element))?;
+                }
+            }
+
+            return ControlFlow::Continue(());
+        }
+
+        let child_size = 1 << (2 * (level - 1));
+        let codes = &self.codes[elements.clone()];
+
+        let mut start = elements.start;
+
+        for child in 0..4 {
+            let end = match child {
+                3 => elements.end,
+                _ => elements.start + codes.partition_point(|code| *code < first + (child + 1) * child_size),
+            };
+
+            self.search(first + child * child_size, level - 1, start..end, shape, visit)?;
+
+            start = end;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// the first code, level and elements of the smallest node which contains the bounds of the shape
+    fn start<S: Shape>(&self, shape: &S) -> (u64, u32, Range<usize>) {
+        let (min, max) = shape.bounds();
+
+        let min = self.code(min);
+        let max = self.code(max);
+
+        // the node has to cover every bit in which the codes of both corners differ
+        let level = (64 - (min ^ max).leading_zeros()).div_ceil(2);
+        let size = 1u64.checked_shl(2 * level).map_or(u64::MAX, |size| size - 1);
+
+        let first = min & !size;
+        let last = first + size;
+
+        (first, level, self.codes.partition_point(|code| *code < first)..self.codes.partition_point(|code| *code <= last))
+    }
+
+    /// the min and max corner of the node, which uses the same edges as `code` so every element
+    /// lies inside the box of its node
+    fn node_box(&self, first: u64, level: u32) -> ((f64, f64), (f64, f64)) {
+        let (axis_x, axis_y) = self.axes();
+        let x = compact(first) as u64;
+        let y = compact(first >> 1) as u64;
+        let size = 1u64 << level;
+
+        ((axis_x.edge(cell(x)), axis_y.edge(cell(y))), (axis_x.edge(cell(x + size)), axis_y.edge(cell(y + size))))
+    }
+
+    /// the smallest nodes along each axis
+    fn axes(&self) -> (CellAxis, CellAxis) {
+        let count = cell(1u64 << DEPTH);
+
+        (
+            CellAxis { start: self.x.start, end: self.x.end, cell_size: self.cell_size.0, count },
+            CellAxis { start: self.y.start, end: self.y.end, cell_size: self.cell_size.1, count },
+        )
+    }
+}
+
+impl<Data: Copy> SpatialIndex<Data> for LinearQuadTree<Data> {
+    fn count(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let (first, level, elements) = self.start(shape);
+
+        let _ = self.search(first, level, elements, shape, &mut |hit| {
+            match hit {
+                Hit::All(elements) => data.extend(elements.iter().map(|x| x.1)),
+                Hit::One(element) => data.push(element.1),
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    fn query_count<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let (first, level, elements) = self.start(shape);
+
+        let _ = self.search(first, level, elements, shape, &mut |hit| {
+            match hit {
+                Hit::All(elements) => count += elements.len(),
+                Hit::One(_) => count += 1,
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    fn query_any<S: Shape>(&self, shape: &S) -> bool {
+        let (first, level, elements) = self.start(shape);
+
+        self.search(first, level, elements, shape, &mut |_| ControlFlow::Break(())).is_break()
+    }
+}
+
+impl<Data: Copy> SpatialIndexMut<Data> for LinearQuadTree<Data> {
+    fn insert(&mut self, position: (f64, f64), data: Data) -> Handle {
+        self.check_bounds(position);
+
+        self.insert_unchecked(position, data)
+    }
+
+    fn insert_unchecked(&mut self, position: (f64, f64), data: Data) -> Handle {
+        let handle = self.slots.insert(0);
+
+        self.place(position, data, handle);
+
+        if !self.is_inside(position) {
+            self.outside += 1;
+        }
+
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<((f64, f64), Data)> {
+        Some(self.elements[self.slots.get(handle)?])
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<Data> {
+        let index = self.slots.remove(handle)?;
+
+        Some(self.take(index).1)
+    }
+
+    fn update_position(&mut self, handle: Handle, position: (f64, f64)) -> bool {
+        self.check_bounds(position);
+
+        let Some(index) = self.slots.get(handle) else {
+            return false;
+        };
+
+        let (_, data) = self.take(index);
+        self.place(position, data, handle);
+
+        true
+    }
+
+    fn clear(&mut self) {
+        self.codes.clear();
+        self.elements.clear();
+        self.handles.clear();
+        self.slots.clear();
+        self.outside = 0;
+    }
+}
+
+/// the fields of a [LinearQuadTree] as they are deserialized, [`LinearQuadTree::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct LinearQuadTreeSnapshot<Data> {
+    codes: Vec<u64>,
+    elements: Vec<((f64, f64), Data)>,
+    handles: Vec<Handle>,
+    slots: Slots<usize>,
+    cell_size: (f64, f64),
+    x: Range<f64>,
+    y: Range<f64>,
+    outside: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> TryFrom<LinearQuadTreeSnapshot<Data>> for LinearQuadTree<Data> {
+    type Error = &'static str;
+
+    fn try_from(snapshot: LinearQuadTreeSnapshot<Data>) -> Result<Self, Self::Error> {
+        let linear_quad_tree = LinearQuadTree {
+            codes: snapshot.codes,
+            elements: snapshot.elements,
+            handles: snapshot.handles,
+            slots: snapshot.slots,
+            cell_size: snapshot.cell_size,
+            x: snapshot.x,
+            y: snapshot.y,
+            outside: snapshot.outside,
+        };
+
+        linear_quad_tree.validate()?;
+
+        Ok(linear_quad_tree)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> LinearQuadTree<Data> {
+    /// checks everything the other methods rely on, so a corrupted snapshot can not make them panic
+    /// or miss elements
+    fn validate(&self) -> Result<(), &'static str> {
+        if check_bounds(&self.x, &self.y).is_err() {
+            return Err("bounds are not valid");
+        }
+
+        if !(self.cell_size.0 > 0.0 && self.cell_size.0.is_finite() && self.cell_size.1 > 0.0 && self.cell_size.1.is_finite()) {
+            return Err("cell_size is not valid");
+        }
+
+        if self.codes.len() != self.elements.len() || self.handles.len() != self.elements.len() {
+            return Err("codes, elements and handles differ in length");
+        }
+
+        // sorted codes which match their elements are sorted elements as well
+        if self.codes.windows(2).any(|pair| pair[0] > pair[1]) || self.codes.iter().zip(&self.elements).any(|(code, element)| *code != self.code(element.0)) {
+            return Err("codes are not the sorted codes of the elements");
+        }
+
+        if self.elements.iter().filter(|element| !self.is_inside(element.0)).count() != self.outside {
+            return Err("count of elements outside of the bounds is wrong");
+        }
+
+        self.slots.check(self.handles.iter().enumerate().map(|(index, handle)| (*handle, index)))
+    }
+}
+
+enum Hit<'a, Data> {
+    /// every element of the node lies in the shape
+    All(&'a [((f64, f64), Data)]),
+    One(&'a ((f64, f64), Data)),
+}
+
+/// whether the node whose codes start at `first` and which covers `2^level` by `2^level` of the
+/// smallest nodes touches a side of the bounds
+fn is_border_node(first: u64, level: u32) -> bool {
+    let end = 1u64 << DEPTH;
+    let size = 1u64 << level;
+
+    [compact(first) as u64, compact(first >> 1) as u64].into_iter().any(|start| start == 0 || start + size == end)
+}
+
+/// the index of a smallest node along one axis as usize, on 32 bit targets the index `2^32` of the
+/// end does not fit and the last smallest node gets merged into the one before it
+fn cell(index: u64) -> usize {
+    usize::try_from(index).unwrap_or(usize::MAX)
+}
+
+/// moves the bits of `value` to the even bits of the result
+fn spread(value: u32) -> u64 {
+    let mut value = value as u64;
+
+    value = (value | (value << 16)) & 0x0000_FFFF_0000_FFFF;
+    value = (value | (value << 8)) & 0x00FF_00FF_00FF_00FF;
+    value = (value | (value << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | (value << 2)) & 0x3333_3333_3333_3333;
+    value = (value | (value << 1)) & 0x5555_5555_5555_5555;
+
+    value
+}
+
+/// the inverse of `spread`, collects the even bits of `value`
+fn compact(value: u64) -> u32 {
+    let mut value = value & 0x5555_5555_5555_5555;
+
+    value = (value | (value >> 1)) & 0x3333_3333_3333_3333;
+    value = (value | (value >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | (value >> 4)) & 0x00FF_00FF_00FF_00FF;
+    value = (value | (value >> 8)) & 0x0000_FFFF_0000_FFFF;
+    value = (value | (value >> 16)) & 0x0000_0000_FFFF_FFFF;
+
+    value as u32
+}
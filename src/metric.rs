@@ -0,0 +1,132 @@
+use crate::config::ConfigError;
+
+/// Measures the distance of an offset (|dx|, |dy|) between two positions
+///
+/// The distance must never shrink when |dx| or |dy| grows. Grid and QuadTree rely on this to
+/// decide if a whole cell or node lies inside or outside a [Circle](crate::shape::Circle) by only
+/// looking at its closest and farthest point
+pub trait Metric {
+    /// returns: f64 the length of the offset, both values of `offset` are positive
+    fn distance(&self, offset: (f64, f64)) -> f64;
+
+    /// returns: (f64, f64) half width and half height of the box which contains every offset with a distance up to `radius`
+    fn extent(&self, radius: f64) -> (f64, f64);
+
+    /// returns: bool true if the distance of the offset is at most `radius`, never for a negative or NaN radius
+    fn in_range(&self, offset: (f64, f64), radius: f64) -> bool {
+        self.distance(offset) <= radius
+    }
+
+    /// returns: bool true if the distance of the offset is less than `radius`
+    fn in_range_exclusive(&self, offset: (f64, f64), radius: f64) -> bool {
+        self.distance(offset) < radius
+    }
+}
+
+/// the straight line distance sqrt(dx² + dy²)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Euclidean;
+
+impl Metric for Euclidean {
+    fn distance(&self, offset: (f64, f64)) -> f64 {
+        (offset.0.powi(2) + offset.1.powi(2)).sqrt()
+    }
+
+    fn extent(&self, radius: f64) -> (f64, f64) {
+        (radius, radius)
+    }
+
+    // compared squared so no sqrt is needed, radius * |radius| keeps a negative radius from containing anything
+    fn in_range(&self, offset: (f64, f64), radius: f64) -> bool {
+        offset.0.powi(2) + offset.1.powi(2) <= radius * radius.abs()
+    }
+
+    fn in_range_exclusive(&self, offset: (f64, f64), radius: f64) -> bool {
+        offset.0.powi(2) + offset.1.powi(2) < radius * radius.abs()
+    }
+}
+
+/// the distance when only moving along the axes dx + dy
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Manhattan;
+
+impl Metric for Manhattan {
+    fn distance(&self, offset: (f64, f64)) -> f64 {
+        offset.0 + offset.1
+    }
+
+    fn extent(&self, radius: f64) -> (f64, f64) {
+        (radius, radius)
+    }
+}
+
+/// the distance when diagonal steps cost the same as straight ones max(dx, dy)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Chebyshev;
+
+impl Metric for Chebyshev {
+    fn distance(&self, offset: (f64, f64)) -> f64 {
+        offset.0.max(offset.1)
+    }
+
+    fn extent(&self, radius: f64) -> (f64, f64) {
+        (radius, radius)
+    }
+}
+
+/// the straight line distance with a weight for each axis sqrt(x * dx² + y * dy²)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weighted {
+    x: f64,
+    y: f64,
+}
+
+impl Weighted {
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: weight of the squared x offset, has to be positive
+    /// * `y`: weight of the squared y offset, has to be positive
+    ///
+    /// panics if a weight is not positive and finite, [`Weighted::try_new()`] returns the reason instead
+    ///
+    pub fn new(x: f64, y: f64) -> Weighted {
+        Weighted::try_new(x, y).unwrap_or_else(|error| panic!("tried to create a Weighted metric from invalid settings: {}", error))
+    }
+
+    /// like [`Weighted::new()`] but returns a ConfigError for weights which are zero, negative, NaN or infinite
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: weight of the squared x offset, has to be positive
+    /// * `y`: weight of the squared y offset, has to be positive
+    ///
+    /// returns: Result<Weighted, ConfigError>
+    ///
+    pub fn try_new(x: f64, y: f64) -> Result<Weighted, ConfigError> {
+        // NaN fails the comparison as well
+        if !(x > 0.0 && y > 0.0 && x.is_finite() && y.is_finite()) {
+            return Err(ConfigError::InvalidWeight((x, y)));
+        }
+
+        Ok(Weighted { x, y })
+    }
+}
+
+impl Metric for Weighted {
+    fn distance(&self, offset: (f64, f64)) -> f64 {
+        (self.x * offset.0.powi(2) + self.y * offset.1.powi(2)).sqrt()
+    }
+
+    fn extent(&self, radius: f64) -> (f64, f64) {
+        (radius / self.x.sqrt(), radius / self.y.sqrt())
+    }
+
+    fn in_range(&self, offset: (f64, f64), radius: f64) -> bool {
+        self.x * offset.0.powi(2) + self.y * offset.1.powi(2) <= radius * radius.abs()
+    }
+
+    fn in_range_exclusive(&self, offset: (f64, f64), radius: f64) -> bool {
+        self.x * offset.0.powi(2) + self.y * offset.1.powi(2) < radius * radius.abs()
+    }
+}
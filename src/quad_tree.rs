@@ -1,114 +1,955 @@
-use std::ops::Range;
-use crate::SpatialPartitioner;
-use crate::util::in_range;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::ops::{ControlFlow, Range};
+use crate::{SpatialIndex, SpatialIndexMut};
+use crate::config::{check_bounds, ConfigError};
+use crate::handle::{Handle, Slots};
+use crate::shape::{Circle, Shape};
+use crate::stats::{DepthStats, QuadTreeStats, QueryStats};
+use crate::util::{border_box, child_sides, Visit, BORDER};
 
+/// below this many elements the children of a node are built on the current thread
+#[cfg(feature = "rayon")]
+const PARALLEL_THRESHOLD: usize = 4096;
+
+/// Splits the area into four nodes whenever a node reaches its capacity
+///
+/// All nodes are stored in one `Vec` and refer to their children by index. Each node owns a block
+/// of `capacity` slots in one shared element `Vec`, which gets reserved when its first element
+/// is inserted. Nodes at the max_depth never split, their block grows instead. The same holds for
+/// nodes which are too small to be split any further with f64 precision
+///
+/// Removing an element moves the last element of its node into the free slot, nodes are not
+/// merged again. The block of a node which grew or lost its last element is kept for the next
+/// node which needs a block of the same size
+///
+/// Positions outside of the bounds, which can only be stored with `insert_unchecked`, end up in
+/// the closest node on the border and are still found by every query which contains them. Positions
+/// with a NaN coordinate are never found
+///
+/// A QuadTree is `Send` and `Sync` whenever `Data` is, so it can be queried from many threads at once
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "QuadTreeSnapshot<Data>"))]
 pub struct QuadTree<Data: Copy> {
-    node: QuadTreeNode<Data>,
+    nodes: Vec<Node>,
+    elements: Vec<((f64, f64), Data)>,
+    /// the handle of each element at the same index in `elements`
+    handles: Vec<Handle>,
+    /// the index in `elements` of each element
+    slots: Slots<usize>,
+    /// the first slot of each block which is not used by a node, `free_blocks[k]` holds the blocks
+    /// of `block_size() << k` slots
+    free_blocks: Vec<Vec<u32>>,
 
     capacity: u16,
+    /// nodes at this depth are never split, the root has a depth of 0
+    max_depth: u32,
 
     x: Range<f64>,
     y: Range<f64>,
 
+    /// count of elements outside of the bounds, as long as there are any the nodes on the border
+    /// can not be accepted or rejected as a whole
+    outside: usize,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Node {
+    center: (f64, f64),
+    size: (f64, f64),
+
+    /// index of the first slot of the block in `elements`
+    first: u32,
+    /// count of used slots in the block
+    len: u32,
+    /// count of reserved slots in the block, 0 until the first element is inserted
+    block: u32,
+    /// count of elements in this node and all of its children
     count: usize,
+
+    /// index of the first of the four children, 0 as long as the node is not full
+    children: u32,
+}
+
+impl Node {
+    fn new(center: (f64, f64), size: (f64, f64)) -> Node {
+        Node {
+            center,
+            size,
+            first: 0,
+            len: 0,
+            block: 0,
+            count: 0,
+            children: 0,
+        }
+    }
+
+    /// the root node covers the whole area, `size` is the half extent of a node
+    fn root(x: &Range<f64>, y: &Range<f64>) -> Node {
+        Node::new(((x.start + x.end) / 2.0, (y.start + y.end) / 2.0), ((x.end - x.start) / 2.0, (y.end - y.start) / 2.0))
+    }
+
+    fn min(&self) -> (f64, f64) {
+        (self.center.0 - self.size.0, self.center.1 - self.size.1)
+    }
+
+    fn max(&self) -> (f64, f64) {
+        (self.center.0 + self.size.0, self.center.1 + self.size.1)
+    }
+
+    /// false once the children would not be smaller than the node with f64 precision
+    fn can_split(&self) -> bool {
+        self.children().iter().all(|child| child.size.0 < self.size.0 && child.size.1 < self.size.1)
+    }
+
+    /// the four children in the order of [`Node::get_index()`], each one covers the part of the node
+    /// between a side and the center
+    fn children(&self) -> [Node; 4] {
+        let (min, max) = (self.min(), self.max());
+        let (low_x, high_x) = (cover(min.0, self.center.0), cover(self.center.0, max.0));
+        let (low_y, high_y) = (cover(min.1, self.center.1), cover(self.center.1, max.1));
+
+        [
+            Node::new((low_x.0, low_y.0), (low_x.1, low_y.1)),
+            Node::new((low_x.0, high_y.0), (low_x.1, high_y.1)),
+            Node::new((high_x.0, low_y.0), (high_x.1, low_y.1)),
+            Node::new((high_x.0, high_y.0), (high_x.1, high_y.1)),
+        ]
+    }
+
+    /// positions outside of the node go to the closest child and NaN to the last one
+    fn get_index(&self, location: (f64, f64)) -> usize {
+        if self.center.0 > location.0 {
+            if self.center.1 > location.1 {
+                0
+            } else {
+                1
+            }
+        } else if self.center.1 > location.1 {
+            2
+        } else {
+            3
+        }
+    }
+}
+
+/// the center and half size of a box from `start` to `end`, the half size is rounded up until the
+/// box contains both of them
+fn cover(start: f64, end: f64) -> (f64, f64) {
+    let center = (start + end) / 2.0;
+    let mut size = (end - start) / 2.0;
+
+    while center - size > start || center + size < end {
+        size = size.next_up();
+    }
+
+    (center, size)
 }
 
 impl<Data: Copy> QuadTree<Data> {
+    /// create a QuadTree with a default capacity of 50, more info here [`QuadTree::with_capacity()`]
     ///
     /// # Arguments
     ///
     /// * `x`: min_x..max_x defines the area in wich data can be inserted
     /// * `y`: min_y..max_y defines the area in wich data can be inserted
-    /// * `capacity`: capacity of each TreeNode
+    ///
+    pub fn new(x: Range<f64>, y: Range<f64>) -> QuadTree<Data> {
+        QuadTree::with_capacity(x, y, 50)
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `capacity`: capacity of each TreeNode, a capacity of 0 splits a TreeNode on every insert
+    ///
+    /// panics if the bounds are not valid, [`QuadTree::try_with_capacity()`] returns the reason instead
     ///
     pub fn with_capacity(x: Range<f64>, y: Range<f64>, capacity: u16) -> QuadTree<Data> {
+        if let Err(error) = check_bounds(&x, &y) {
+            panic!("tried to create a QuadTree from invalid settings: {}", error);
+        }
+
         QuadTree {
-            node: QuadTreeNode::new(((x.end - x.start) / 2.0, (y.end - y.start) / 2.0), ((x.end - x.start), (y.end - y.start)), capacity),
+            nodes: vec![Node::root(&x, &y)],
+            elements: Vec::new(),
+            handles: Vec::new(),
+            slots: Slots::new(),
+            free_blocks: Vec::new(),
             capacity,
+            max_depth: u32::MAX,
             x,
             y,
-            count: 0,
+            outside: 0,
         }
     }
 
+    /// like [`QuadTree::new()`] but returns a ConfigError if the bounds are not valid
     ///
     /// # Arguments
     ///
-    /// returns : &QuadTreeNode<Data> the root node of the QuadTree
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    /// returns: Result<QuadTree<Data>, ConfigError>
     ///
-    pub fn node(&self) -> &QuadTreeNode<Data> {
-        &self.node
+    pub fn try_new(x: Range<f64>, y: Range<f64>) -> Result<QuadTree<Data>, ConfigError> {
+        QuadTree::try_with_capacity(x, y, 50)
     }
-}
 
-impl<Data: Copy> SpatialPartitioner<Data> for QuadTree<Data> {
+    /// like [`QuadTree::with_capacity()`] but returns a ConfigError for empty, reversed or infinite
+    /// ranges and for a capacity of 0
     ///
-    /// create a QuadTree with a default capacity of 50. More info here [`QuadTree::new()`]
+    /// # Arguments
     ///
-    fn new(x: Range<f64>, y: Range<f64>) -> Self {
-        QuadTree::with_capacity(x, y, 50)
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `capacity`: capacity of each TreeNode, at least 1
+    ///
+    /// returns: Result<QuadTree<Data>, ConfigError>
+    ///
+    pub fn try_with_capacity(x: Range<f64>, y: Range<f64>, capacity: u16) -> Result<QuadTree<Data>, ConfigError> {
+        check_bounds(&x, &y)?;
+
+        if capacity == 0 {
+            return Err(ConfigError::ZeroCapacity);
+        }
+
+        Ok(QuadTree::with_capacity(x, y, capacity))
+    }
+
+    /// configures a QuadTree step by step, the settings are checked by [`QuadTreeBuilder::build()`]
+    ///
+    /// # Arguments
+    ///
+    /// returns: QuadTreeBuilder<Data> a builder with a default capacity of 50, no max_depth and no bounds
+    ///
+    pub fn builder() -> QuadTreeBuilder<Data> {
+        QuadTreeBuilder {
+            bounds: None,
+            capacity: 50,
+            max_depth: u32::MAX,
+            data: PhantomData,
+        }
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns : QuadTreeNode<Data> the root node of the QuadTree
+    ///
+    pub fn node(&self) -> QuadTreeNode<'_, Data> {
+        QuadTreeNode { quad_tree: self, index: 0 }
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns : u16 the capacity of each TreeNode
+    ///
+    pub fn capacity(&self) -> u16 {
+        self.capacity
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns : u32 the depth at which TreeNodes stop splitting, u32::MAX if there is no limit
+    ///
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns : (Range<f64>, Range<f64>) min_x..max_x and min_y..max_y the area in wich data can be inserted
+    ///
+    pub fn bounds(&self) -> (Range<f64>, Range<f64>) {
+        (self.x.clone(), self.y.clone())
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: QuadTreeStats the depths and fill of the nodes, walks through every node
+    ///
+    pub fn stats(&self) -> QuadTreeStats {
+        let mut stats = QuadTreeStats {
+            count: self.nodes[0].count,
+            capacity: self.capacity,
+            max_depth: self.max_depth,
+            node_count: self.nodes.len(),
+            leaf_count: 0,
+            empty_leaves: 0,
+            depths: Vec::new(),
+            internal_with_elements: 0,
+            internal_elements: 0,
+            max_len: 0,
+            leaf_fill: 0.0,
+            slots: self.elements.len(),
+        };
+
+        let mut leaf_elements = 0;
+        let mut stack = vec![(0, 0)];
+
+        while let Some((index, depth)) = stack.pop() {
+            let node = &self.nodes[index];
+            let len = node.len as usize;
+
+            if stats.depths.len() <= depth {
+                stats.depths.resize(depth + 1, DepthStats::default());
+            }
+
+            stats.depths[depth].nodes += 1;
+            stats.depths[depth].elements += len;
+            stats.max_len = stats.max_len.max(len);
+
+            if node.children == 0 {
+                stats.depths[depth].leaves += 1;
+                stats.leaf_count += 1;
+                leaf_elements += len;
+
+                if len == 0 {
+                    stats.empty_leaves += 1;
+                }
+
+                continue;
+            }
+
+            if len > 0 {
+                stats.internal_with_elements += 1;
+                stats.internal_elements += len;
+            }
+
+            stack.extend((0..4).map(|i| (node.children as usize + i, depth + 1)));
+        }
+
+        stats.leaf_fill = leaf_elements as f64 / stats.leaf_count as f64 / self.capacity.max(1) as f64;
+
+        stats
+    }
+
+    /// like [`SpatialIndex::query()`] and counts what the query did into `stats`
+    ///
+    /// a query without stats does not pay anything for them
+    ///
+    /// # Arguments
+    ///
+    /// * `shape`: the area which gets searched
+    /// * `stats`: the counters are added to, so it can sum up many queries
+    ///
+    /// returns: Vec<Data>
+    ///
+    pub fn query_with_stats<S: Shape>(&self, shape: &S, stats: &mut QueryStats) -> Vec<Data> {
+        let data = self.query_traced(shape, &mut |_, visit| stats.record(visit));
+        stats.returned += data.len();
+
+        data
     }
 
-    fn insert(&mut self, position: (f64, f64), data: Data) {
+    /// like [`SpatialIndex::in_circle()`] and counts what the query did into `stats`
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the search circle
+    /// * `radius`: radius of the search circle
+    /// * `stats`: the counters are added to, so it can sum up many queries
+    ///
+    /// returns: Vec<Data>
+    ///
+    pub fn in_circle_with_stats(&self, position: (f64, f64), radius: f64, stats: &mut QueryStats) -> Vec<Data> {
+        self.query_with_stats(&Circle::new(position, radius), stats)
+    }
+
+    fn check_bounds(&self, position: (f64, f64)) {
         if position.0 < self.x.start || position.0 >= self.x.end || position.1 < self.y.start || position.1 >= self.y.end {
             panic!("tried to insert position into QuadTree which was out of bounce")
         }
+    }
 
-        self.insert_unchecked(position, data);
+    /// false for positions outside of the bounds and positions with a NaN coordinate
+    fn is_inside(&self, position: (f64, f64)) -> bool {
+        self.x.contains(&position.0) && self.y.contains(&position.1)
     }
 
-    fn insert_unchecked(&mut self, position: (f64, f64), data: Data) {
-        self.count += 1;
-        self.node.insert(position, data);
+    /// a node always takes at least one element before it is full
+    fn block_size(&self) -> usize {
+        (self.capacity as usize).max(1)
     }
 
-    fn count(&self) -> usize {
-        self.count
+    fn node_elements(&self, node: &Node) -> &[((f64, f64), Data)] {
+        &self.elements[node.first as usize..(node.first + node.len) as usize]
     }
 
-    fn clear(&mut self) {
-        self.node = QuadTreeNode::new(((self.x.start - self.x.end) / 2.0, (self.y.start - self.y.end) / 2.0), ((self.x.start - self.x.end), (self.y.start - self.y.end)), self.capacity);
-        self.count = 0;
+    /// adds the element to the block of the node and reserves or grows the block first if needed
+    fn push_element(&mut self, index: usize, element: ((f64, f64), Data), handle: Handle) {
+        let node = &self.nodes[index];
+
+        if node.len == node.block {
+            self.grow_block(index, element, handle);
+        }
+
+        let node = &mut self.nodes[index];
+        let slot = (node.first + node.len) as usize;
+
+        self.elements[slot] = element;
+        self.handles[slot] = handle;
+        self.slots.set(handle, slot);
+        node.len += 1;
     }
 
-    fn in_circle(&self, position: (f64, f64), radius: f64) -> Vec<Data> {
+    /// reserves the first block of a node or doubles the block of a full node which can not split
+    fn grow_block(&mut self, index: usize, filler: ((f64, f64), Data), filler_handle: Handle) {
+        let node = &self.nodes[index];
+        let (first, len, block) = (node.first as usize, node.len as usize, node.block as usize);
+        let size = if block == 0 { self.block_size() } else { block * 2 };
+        let class = self.block_class(size);
+
+        // the smallest free block which is large enough gets reused, otherwise the last block grows
+        // in place and any other one moves behind the last block
+        let reused = (class..self.free_blocks.len()).find_map(|class| Some((class, self.free_blocks[class].pop()?)));
+
+        let (start, size) = match reused {
+            Some((class, start)) => (start as usize, self.block_size() << class),
+            None if block != 0 && first + block == self.elements.len() => (first, size),
+            None => (self.elements.len(), size),
+        };
+
+        let end = u32::try_from(start + size).expect("QuadTree can not store more than u32::MAX elements");
+
+        // the slots are only read once they are used, so the element itself is a good filler
+        if end as usize > self.elements.len() {
+            self.elements.resize(end as usize, filler);
+            self.handles.resize(end as usize, filler_handle);
+        }
+
+        if block != 0 && start != first {
+            self.free_block(first as u32, block);
+        }
+
+        if start != first {
+            for offset in 0..len {
+                self.elements[start + offset] = self.elements[first + offset];
+                self.handles[start + offset] = self.handles[first + offset];
+                self.slots.set(self.handles[start + offset], start + offset);
+            }
+        }
+
+        let node = &mut self.nodes[index];
+        node.first = start as u32;
+        node.block = size as u32;
+    }
+
+    /// the index in `free_blocks` of blocks with `size` slots, every block has `block_size()` slots
+    /// times a power of two
+    fn block_class(&self, size: usize) -> usize {
+        (size / self.block_size()).trailing_zeros() as usize
+    }
+
+    /// keeps the block for the next node which needs a block of the same size
+    fn free_block(&mut self, first: u32, size: usize) {
+        let class = self.block_class(size);
+
+        if self.free_blocks.len() <= class {
+            self.free_blocks.resize(class + 1, Vec::new());
+        }
+
+        self.free_blocks[class].push(first);
+    }
+
+    /// takes the element out of its node, the last element of the node takes its place
+    fn take(&mut self, slot: usize) -> ((f64, f64), Data) {
+        let element = self.elements[slot];
+
+        // the node holding the slot lies on the path to the position of the element
+        let mut index = 0;
+
+        loop {
+            let node = &mut self.nodes[index];
+            node.count -= 1;
+
+            if (node.first as usize..(node.first + node.len) as usize).contains(&slot) {
+                break;
+            }
+
+            index = node.children as usize + node.get_index(element.0);
+        }
+
+        let node = &mut self.nodes[index];
+        let last = (node.first + node.len - 1) as usize;
+        node.len -= 1;
+
+        if slot != last {
+            self.elements[slot] = self.elements[last];
+            self.handles[slot] = self.handles[last];
+            self.slots.set(self.handles[slot], slot);
+        }
+
+        // an empty node gets a new block with its next element
+        if node.len == 0 {
+            let (first, block) = (node.first, node.block as usize);
+
+            node.first = 0;
+            node.block = 0;
+
+            self.free_block(first, block);
+        }
+
+        if !self.is_inside(element.0) {
+            self.outside -= 1;
+        }
+
+        element
+    }
+
+    /// puts the element into the deepest node on its path
+    fn place(&mut self, position: (f64, f64), data: Data, handle: Handle) {
+        let mut index = 0;
+        let mut depth = 0;
+
+        while self.nodes[index].children != 0 {
+            self.nodes[index].count += 1;
+            index = self.nodes[index].children as usize + self.nodes[index].get_index(position);
+            depth += 1;
+        }
+
+        self.nodes[index].count += 1;
+        self.push_element(index, (position, data), handle);
+
+        if depth < self.max_depth && self.nodes[index].len as usize >= self.capacity as usize && self.nodes[index].can_split() {
+            self.split(index);
+        }
+    }
+
+    fn split(&mut self, index: usize) {
+        let children = u32::try_from(self.nodes.len()).expect("QuadTree can not store more than u32::MAX nodes");
+
+        let nodes = self.nodes[index].children();
+
+        self.nodes[index].children = children;
+        self.nodes.extend(nodes);
+    }
+
+    /// calls `trace` with the index of every non-empty node a query for the shape reaches
+    #[cfg(feature = "svg")]
+    pub(crate) fn trace<S: Shape>(&self, shape: &S, mut trace: impl FnMut(u32, Visit)) {
+        let _ = self.search(0, BORDER, shape, &mut trace, &mut |_| ControlFlow::Continue(()));
+    }
+
+    fn query_traced<S: Shape>(&self, shape: &S, trace: &mut impl FnMut(u32, Visit)) -> Vec<Data> {
         let mut data = Vec::new();
 
-        self.node.in_circle(position, radius, &mut data, false);
+        let _ = self.search(0, BORDER, shape, trace, &mut |hit| {
+            match hit {
+                Hit::All(node) => {
+                    data.reserve(node.count);
+                    self.collect(node, &mut data)
+                }
+                Hit::One(element) => data.push(element.1),
+            }
+
+            ControlFlow::Continue(())
+        });
 
         data
     }
+
+    /// `sides` are the sides of the bounds the node lies on, see [BORDER]
+    fn search<'a, S: Shape>(&'a self, index: u32, sides: u8, shape: &S, trace: &mut impl FnMut(u32, Visit), visit: &mut impl FnMut(Hit<'a, Data>) -> ControlFlow<()>) -> ControlFlow<()> {
+        let node = &self.nodes[index as usize];
+
+        // testing the node only pays off if it saves enough distance checks, the box of a node on
+        // the border says nothing about the elements outside of the bounds
+        if node.count > 4 && !(self.outside > 0 && sides != 0) {
+            let (min, max) = self.node_box(node, sides);
+
+            if shape.contains_box(min, max) {
+                trace(index, Visit::Accepted);
+                return visit(Hit::All(node));
+            }
+
+            if !shape.intersects_box(min, max) {
+                trace(index, Visit::Rejected);
+                return ControlFlow::Continue(());
+            }
+        }
+
+        trace(index, Visit::Tested(node.len as usize));
+
+        for element in self.node_elements(node) {
+            if shape.contains(element.0) {
+                visit(Hit::One(element))?;
+            }
+        }
+
+        if node.children != 0 {
+            for (i, child) in (node.children..node.children + 4).enumerate() {
+                if self.nodes[child as usize].count > 0 {
+                    self.search(child, child_sides(sides, i), shape, trace, visit)?;
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// the min and max corner of the node, the center and half size of the root are rounded for most
+    /// bounds so the sides of nodes on the border are taken from the bounds instead
+    fn node_box(&self, node: &Node, sides: u8) -> ((f64, f64), (f64, f64)) {
+        border_box((node.min(), node.max()), sides, ((self.x.start, self.y.start), (self.x.end, self.y.end)))
+    }
+
+    fn collect(&self, node: &Node, data: &mut Vec<Data>) {
+        data.extend(self.node_elements(node).iter().map(|x| x.1));
+
+        self.children(node).iter().for_each(|child| self.collect(child, data));
+    }
+
+    /// the four children of the node, empty if the node is not full yet
+    fn children(&self, node: &Node) -> &[Node] {
+        if node.children == 0 {
+            return &[];
+        }
+
+        &self.nodes[node.children as usize..node.children as usize + 4]
+    }
 }
 
-pub struct QuadTreeNode<Data: Copy> {
-    data: Vec<((f64, f64), Data)>,
+/// Configures a [QuadTree] step by step, created by [`QuadTree::builder()`]
+///
+/// ```
+/// use spatial_neighbors::quad_tree::QuadTree;
+///
+/// let quad_tree: QuadTree<u32> = QuadTree::builder()
+///     .bounds(0.0..100.0, 0.0..100.0)
+///     .capacity(16)
+///     .max_depth(8)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(quad_tree.max_depth(), 8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuadTreeBuilder<Data: Copy> {
+    bounds: Option<(Range<f64>, Range<f64>)>,
     capacity: u16,
+    max_depth: u32,
+    data: PhantomData<Data>,
+}
 
-    is_full: bool,
+impl<Data: Copy> QuadTreeBuilder<Data> {
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    ///
+    pub fn bounds(mut self, x: Range<f64>, y: Range<f64>) -> Self {
+        self.bounds = Some((x, y));
+        self
+    }
 
-    nodes: Option<Box<[QuadTreeNode<Data>; 4]>>,
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: capacity of each TreeNode, at least 1
+    ///
+    pub fn capacity(mut self, capacity: u16) -> Self {
+        self.capacity = capacity;
+        self
+    }
 
-    center: (f64, f64),
-    size: (f64, f64),
+    /// limits how often the area gets split, which keeps many elements at the same position
+    /// from building a long chain of nodes
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth`: TreeNodes at this depth store all of their elements instead of splitting, the root has a depth of 0
+    ///
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: Result<QuadTree<Data>, ConfigError> the QuadTree or the first setting which was not valid
+    ///
+    pub fn build(self) -> Result<QuadTree<Data>, ConfigError> {
+        let (x, y) = self.bounds.ok_or(ConfigError::MissingBounds)?;
+
+        let mut quad_tree = QuadTree::try_with_capacity(x, y, self.capacity)?;
+        quad_tree.max_depth = self.max_depth;
+
+        Ok(quad_tree)
+    }
 }
 
-impl<Data: Copy> QuadTreeNode<Data> {
+#[cfg(feature = "rayon")]
+impl<Data: Copy + Send> QuadTree<Data> {
+    /// builds a QuadTree from many elements at once, the four children of each node are built in parallel
+    ///
+    /// the result is the same as inserting the elements one after another
     ///
     /// # Arguments
     ///
-    /// returns : &Option<Box<[QuadTreeNode<Data>; 4]>> the nodes of the current node
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `capacity`: capacity of each TreeNode
+    /// * `elements`: (position, data) of each element, all of them have to lie inside of the bounds
     ///
-    pub fn nodes(&self) -> &Option<Box<[QuadTreeNode<Data>; 4]>> {
-        &self.nodes
+    /// panics if the bounds are not valid, [`QuadTree::try_par_from_elements()`] returns the reason instead
+    ///
+    pub fn par_from_elements(x: Range<f64>, y: Range<f64>, capacity: u16, elements: &[((f64, f64), Data)]) -> QuadTree<Data> {
+        let mut quad_tree = QuadTree::with_capacity(x, y, capacity);
+
+        for element in elements {
+            quad_tree.check_bounds(element.0);
+        }
+
+        // the handles are given out in the order of the elements
+        for _ in elements {
+            quad_tree.slots.insert(0);
+        }
+
+        let entries = elements.iter().enumerate().map(|(i, element)| (*element, Handle::nth(i))).collect();
+        let limits = BuildLimits { block_size: quad_tree.block_size(), max_depth: quad_tree.max_depth };
+        let root = BuildNode::build(&quad_tree.nodes[0], limits, 0, entries);
+
+        // the subtrees are built separately and moved into the shared storage afterwards
+        let mut queue = std::collections::VecDeque::from([(0, root)]);
+
+        while let Some((index, build)) = queue.pop_front() {
+            quad_tree.nodes[index].count = build.count;
+
+            for (element, handle) in &build.data {
+                quad_tree.push_element(index, *element, *handle);
+            }
+
+            if let Some(children) = build.children {
+                quad_tree.split(index);
+
+                let first = quad_tree.nodes[index].children as usize;
+                queue.extend(children.into_iter().enumerate().map(|(i, child)| (first + i, child)));
+            }
+        }
+
+        quad_tree
     }
 
+    /// like [`QuadTree::par_from_elements()`] but returns a ConfigError for empty, reversed or
+    /// infinite ranges and for a capacity of 0
     ///
     /// # Arguments
     ///
-    /// returns : &Vec<((f64, f64), Data)> the Elements stored in the current node
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `capacity`: capacity of each TreeNode, at least 1
+    /// * `elements`: (position, data) of each element, panics if one lies outside of the bounds
     ///
-    pub fn data(&self) -> &Vec<((f64, f64), Data)> {
-        &self.data
+    /// returns: Result<QuadTree<Data>, ConfigError>
+    ///
+    pub fn try_par_from_elements(x: Range<f64>, y: Range<f64>, capacity: u16, elements: &[((f64, f64), Data)]) -> Result<QuadTree<Data>, ConfigError> {
+        check_bounds(&x, &y)?;
+
+        if capacity == 0 {
+            return Err(ConfigError::ZeroCapacity);
+        }
+
+        Ok(QuadTree::par_from_elements(x, y, capacity, elements))
+    }
+}
+
+/// an element and its handle
+#[cfg(feature = "rayon")]
+type BuildEntry<Data> = (((f64, f64), Data), Handle);
+
+/// the settings of the QuadTree which decide when a node is split
+#[cfg(feature = "rayon")]
+#[derive(Clone, Copy)]
+struct BuildLimits {
+    block_size: usize,
+    max_depth: u32,
+}
+
+/// a node of a QuadTree which is built in parallel
+#[cfg(feature = "rayon")]
+struct BuildNode<Data> {
+    data: Vec<BuildEntry<Data>>,
+    children: Option<Box<[BuildNode<Data>; 4]>>,
+    count: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl<Data: Copy + Send> BuildNode<Data> {
+    /// fills the node the same way inserting the elements one after another would
+    fn build(node: &Node, limits: BuildLimits, depth: u32, mut elements: Vec<BuildEntry<Data>>) -> BuildNode<Data> {
+        let count = elements.len();
+
+        if elements.len() < limits.block_size || depth >= limits.max_depth || !node.can_split() {
+            return BuildNode { data: elements, children: None, count };
+        }
+
+        let rest = elements.split_off(limits.block_size);
+
+        let nodes = node.children();
+
+        let mut parts: [Vec<BuildEntry<Data>>; 4] = Default::default();
+
+        for entry in rest {
+            parts[node.get_index(entry.0.0)].push(entry);
+        }
+
+        let [part_0, part_1, part_2, part_3] = parts;
+        let [node_0, node_1, node_2, node_3] = &nodes;
+
+        let children = if count < PARALLEL_THRESHOLD {
+            [
+                BuildNode::build(node_0, limits, depth + 1, part_0),
+                BuildNode::build(node_1, limits, depth + 1, part_1),
+                BuildNode::build(node_2, limits, depth + 1, part_2),
+                BuildNode::build(node_3, limits, depth + 1, part_3),
+            ]
+        } else {
+            let ((child_0, child_1), (child_2, child_3)) = rayon::join(
+                || rayon::join(|| BuildNode::build(node_0, limits, depth + 1, part_0), || BuildNode::build(node_1, limits, depth + 1, part_1)),
+                || rayon::join(|| BuildNode::build(node_2, limits, depth + 1, part_2), || BuildNode::build(node_3, limits, depth + 1, part_3)),
+            );
+
+            [child_0, child_1, child_2, child_3]
+        };
+
+        BuildNode { data: elements, children: Some(Box::new(children)), count }
+    }
+}
+
+impl<Data: Copy> SpatialIndex<Data> for QuadTree<Data> {
+    fn count(&self) -> usize {
+        self.nodes[0].count
+    }
+
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        self.query_traced(shape, &mut |_, _| {})
+    }
+
+    fn query_count<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let _ = self.search(0, BORDER, shape, &mut |_, _| {}, &mut |hit| {
+            match hit {
+                Hit::All(node) => count += node.count,
+                Hit::One(_) => count += 1,
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    fn query_any<S: Shape>(&self, shape: &S) -> bool {
+        self.search(0, BORDER, shape, &mut |_, _| {}, &mut |_| ControlFlow::Break(())).is_break()
+    }
+}
+
+impl<Data: Copy> SpatialIndexMut<Data> for QuadTree<Data> {
+    fn insert(&mut self, position: (f64, f64), data: Data) -> Handle {
+        self.check_bounds(position);
+
+        self.insert_unchecked(position, data)
+    }
+
+    fn insert_unchecked(&mut self, position: (f64, f64), data: Data) -> Handle {
+        let handle = self.slots.insert(0);
+
+        self.place(position, data, handle);
+
+        if !self.is_inside(position) {
+            self.outside += 1;
+        }
+
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<((f64, f64), Data)> {
+        Some(self.elements[self.slots.get(handle)?])
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<Data> {
+        let slot = self.slots.remove(handle)?;
+
+        Some(self.take(slot).1)
+    }
+
+    fn update_position(&mut self, handle: Handle, position: (f64, f64)) -> bool {
+        self.check_bounds(position);
+
+        let Some(slot) = self.slots.get(handle) else {
+            return false;
+        };
+
+        let (_, data) = self.take(slot);
+        self.place(position, data, handle);
+
+        true
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.nodes.push(Node::root(&self.x, &self.y));
+        self.elements.clear();
+        self.handles.clear();
+        self.slots.clear();
+        self.free_blocks.clear();
+        self.outside = 0;
+    }
+}
+
+enum Hit<'a, Data: Copy> {
+    /// every element of the node and its children lies in the shape
+    All(&'a Node),
+    One(&'a ((f64, f64), Data)),
+}
+
+/// A lightweight handle to a node of a [QuadTree]
+#[derive(Clone, Copy)]
+pub struct QuadTreeNode<'a, Data: Copy> {
+    quad_tree: &'a QuadTree<Data>,
+    index: u32,
+}
+
+impl<'a, Data: Copy> QuadTreeNode<'a, Data> {
+    fn get(&self) -> &'a Node {
+        &self.quad_tree.nodes[self.index as usize]
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns : Option<[QuadTreeNode<Data>; 4]> the nodes of the current node
+    ///
+    pub fn nodes(&self) -> Option<[QuadTreeNode<'a, Data>; 4]> {
+        let children = self.get().children;
+
+        if children == 0 {
+            return None;
+        }
+
+        Some([0, 1, 2, 3].map(|i| QuadTreeNode { quad_tree: self.quad_tree, index: children + i }))
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns : &[((f64, f64), Data)] the Elements stored in the current node
+    ///
+    pub fn data(&self) -> &'a [((f64, f64), Data)] {
+        self.quad_tree.node_elements(self.get())
     }
 
     ///
@@ -117,112 +958,221 @@ impl<Data: Copy> QuadTreeNode<Data> {
     /// returns : &(f64, f64) the center of the current node
     ///
     pub fn center(&self) -> (f64, f64) {
-        self.center
+        self.get().center
     }
 
     ///
     /// # Arguments
     ///
-    /// returns : &(f64, f64) the size of the current node
+    /// returns : &(f64, f64) the size of the current node measured from its center (half width, half height)
     ///
     pub fn size(&self) -> (f64, f64) {
-        self.size
+        self.get().size
     }
 
-    fn new(center: (f64, f64), size: (f64, f64), capacity: u16) -> QuadTreeNode<Data> {
-        QuadTreeNode {
-            data: Vec::new(),
-            nodes: None,
-            center,
-            size,
-            capacity,
+    ///
+    /// # Arguments
+    ///
+    /// returns : usize the count of Elements stored in the current node and all of its children
+    ///
+    pub fn count(&self) -> usize {
+        self.get().count
+    }
 
-            is_full: false,
-        }
+    /// the index of the node in the QuadTree, the root has the index 0
+    #[cfg(feature = "svg")]
+    pub(crate) fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl<Data: Copy> Debug for QuadTreeNode<'_, Data> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuadTreeNode")
+            .field("center", &self.center())
+            .field("size", &self.size())
+            .field("len", &self.get().len)
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
+/// the fields of a [QuadTree] as they are deserialized, [`QuadTree::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct QuadTreeSnapshot<Data> {
+    nodes: Vec<Node>,
+    elements: Vec<((f64, f64), Data)>,
+    handles: Vec<Handle>,
+    slots: Slots<usize>,
+    free_blocks: Vec<Vec<u32>>,
+    capacity: u16,
+    max_depth: u32,
+    x: Range<f64>,
+    y: Range<f64>,
+    outside: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> TryFrom<QuadTreeSnapshot<Data>> for QuadTree<Data> {
+    type Error = &'static str;
+
+    fn try_from(snapshot: QuadTreeSnapshot<Data>) -> Result<Self, Self::Error> {
+        let quad_tree = QuadTree {
+            nodes: snapshot.nodes,
+            elements: snapshot.elements,
+            handles: snapshot.handles,
+            slots: snapshot.slots,
+            free_blocks: snapshot.free_blocks,
+            capacity: snapshot.capacity,
+            max_depth: snapshot.max_depth,
+            x: snapshot.x,
+            y: snapshot.y,
+            outside: snapshot.outside,
+        };
+
+        quad_tree.validate()?;
+
+        Ok(quad_tree)
     }
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> QuadTree<Data> {
+    /// checks everything the other methods rely on, so a corrupted snapshot can not make them panic
+    /// or miss elements
+    fn validate(&self) -> Result<(), &'static str> {
+        if check_bounds(&self.x, &self.y).is_err() {
+            return Err("bounds are not valid");
+        }
 
-    fn insert(&mut self, position: (f64, f64), data: Data) {
-        if !self.is_full {
-            self.data.push((position, data));
+        if self.nodes.is_empty() {
+            return Err("missing root node");
+        }
+
+        if self.handles.len() != self.elements.len() {
+            return Err("elements and their handles differ in length");
+        }
 
-            if self.data.len() >= self.capacity as usize {
-                self.is_full = true;
+        // the ends of the blocks are computed as u32
+        if u32::try_from(self.elements.len()).is_err() {
+            return Err("too many elements");
+        }
 
-                self.nodes = Some(Box::new([
-                    QuadTreeNode::new((self.center.0 - self.size.0 / 2.0, self.center.1 - self.size.1 / 2.0), (self.size.0 / 2.0, self.size.1 / 2.0), self.capacity),
-                    QuadTreeNode::new((self.center.0 - self.size.0 / 2.0, self.center.1 + self.size.1 / 2.0), (self.size.0 / 2.0, self.size.1 / 2.0), self.capacity),
-                    QuadTreeNode::new((self.center.0 + self.size.0 / 2.0, self.center.1 - self.size.1 / 2.0), (self.size.0 / 2.0, self.size.1 / 2.0), self.capacity),
-                    QuadTreeNode::new((self.center.0 + self.size.0 / 2.0, self.center.1 + self.size.1 / 2.0), (self.size.0 / 2.0, self.size.1 / 2.0), self.capacity),
-                ]))
+        // every node but the root is a child of exactly one node before it
+        let mut is_child = vec![false; self.nodes.len()];
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.children == 0 {
+                continue;
             }
-        } else {
-            let i = self.get_index(position);
-            self.nodes.as_mut().unwrap()[i].insert(position, data);
+
+            let children = node.children as usize..node.children as usize + 4;
+
+            if children.start <= index || children.end > self.nodes.len() || children.clone().any(|child| is_child[child]) {
+                return Err("node points to invalid children");
+            }
+
+            children.for_each(|child| is_child[child] = true);
         }
-    }
 
-    fn in_circle(&self, position: (f64, f64), radius: f64, data: &mut Vec<Data>, in_circle: bool) {
-        if in_circle || (self.data.len() > 4 && self.in_box(position, radius)) {
-            data.reserve(self.data.len());
+        if is_child.iter().skip(1).any(|is_child| !is_child) {
+            return Err("node is not part of the tree");
+        }
 
-            data.extend(self.data.iter().map(|x| x.1));
+        for node in &self.nodes {
+            let count = self.children(node).iter().try_fold(node.len as usize, |count, child| count.checked_add(child.count));
 
-            if self.nodes.is_none() {
-                return;
+            if count != Some(node.count) {
+                return Err("children do not add up to the count of their parent");
             }
+        }
 
-            self.nodes.as_ref().unwrap()[0].in_circle(position, radius, data, true);
-            self.nodes.as_ref().unwrap()[1].in_circle(position, radius, data, true);
-            self.nodes.as_ref().unwrap()[2].in_circle(position, radius, data, true);
-            self.nodes.as_ref().unwrap()[3].in_circle(position, radius, data, true);
-        } else {
-            for elements in &self.data {
-                if in_range(elements.0, position, radius) {
-                    data.push(elements.1)
+        // the blocks of the nodes and the free blocks must not overlap
+        let mut used = vec![false; self.elements.len()];
+        let mut claim = |first: usize, size: usize| {
+            let slots = first.checked_add(size).and_then(|end| used.get_mut(first..end)).ok_or("block lies outside of the elements")?;
+
+            if slots.iter().any(|used| *used) {
+                return Err("blocks overlap");
+            }
+
+            slots.fill(true);
+
+            Ok(())
+        };
+
+        for node in &self.nodes {
+            if node.len > node.block {
+                return Err("node holds more elements than its block");
+            }
+
+            if node.block != 0 {
+                let size = node.block as usize;
+
+                if !size.is_multiple_of(self.block_size()) || !(size / self.block_size()).is_power_of_two() {
+                    return Err("block size is not valid");
                 }
+
+                claim(node.first as usize, size)?;
             }
+        }
 
-            if self.nodes.is_none() {
-                return;
+        for (class, blocks) in self.free_blocks.iter().enumerate() {
+            let size = u32::try_from(class).ok().and_then(|class| 1usize.checked_shl(class)).and_then(|blocks| blocks.checked_mul(self.block_size())).ok_or("block size is not valid")?;
+
+            for first in blocks {
+                claim(*first as usize, size)?;
             }
+        }
 
-            let mut indexes = [false; 4];
+        let mut outside = 0;
 
-            indexes[self.get_index((position.0 - radius, position.1 - radius))] = true;
-            indexes[self.get_index((position.0 - radius, position.1 + radius))] = true;
-            indexes[self.get_index((position.0 + radius, position.1 - radius))] = true;
-            indexes[self.get_index((position.0 + radius, position.1 + radius))] = true;
+        for node in &self.nodes {
+            for slot in node.first as usize..(node.first + node.len) as usize {
+                if !self.reaches(slot) {
+                    return Err("element is not on the path to its position");
+                }
 
-            for (i, bool) in indexes.iter().enumerate() {
-                if *bool {
-                    self.nodes.as_ref().unwrap()[i].in_circle(position, radius, data, false);
+                if !self.is_inside(self.elements[slot].0) {
+                    outside += 1;
                 }
             }
         }
-    }
 
-    fn in_box(&self, location: (f64, f64), radius: f64) -> bool {
-        if in_range(location, (self.center.0 - self.size.0, self.center.1 + self.size.1), radius)
-            && in_range(location, (self.center.0 + self.size.0, self.center.1 + self.size.1), radius)
-            && in_range(location, (self.center.0 - self.size.0, self.center.1 - self.size.1), radius)
-            && in_range(location, (self.center.0 + self.size.0, self.center.1 - self.size.1), radius) {
-            return true;
+        if outside != self.outside {
+            return Err("count of elements outside of the bounds is wrong");
         }
 
-        false
+        self.slots.check(self.nodes.iter().flat_map(|node| node.first as usize..(node.first + node.len) as usize).map(|slot| (self.handles[slot], slot)))
     }
 
-    fn get_index(&self, location: (f64, f64)) -> usize {
-        if self.center.0 > location.0 {
-            if self.center.1 > location.1 {
-                0
-            } else {
-                1
+    /// whether `take` finds the node holding the slot on the path to its element, the element has
+    /// to lie in the box of every node on the path as long as it lies inside of the bounds
+    fn reaches(&self, slot: usize) -> bool {
+        let position = self.elements[slot].0;
+        let (mut index, mut sides) = (0, BORDER);
+
+        loop {
+            let node = &self.nodes[index];
+            let (min, max) = self.node_box(node, sides);
+
+            if self.is_inside(position) && !(min.0 <= position.0 && position.0 <= max.0 && min.1 <= position.1 && position.1 <= max.1) {
+                return false;
             }
-        } else if self.center.1 > location.1 {
-            2
-        } else {
-            3
+
+            if (node.first as usize..(node.first + node.len) as usize).contains(&slot) {
+                return true;
+            }
+
+            if node.children == 0 {
+                return false;
+            }
+
+            let child = node.get_index(position);
+
+            index = node.children as usize + child;
+            sides = child_sides(sides, child);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,645 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::ControlFlow;
+use crate::shape::Shape;
+use crate::util::nearest_in_box;
+
+/// most entries a node can hold before it gets split
+const MAX_ENTRIES: usize = 16;
+
+/// least entries a node has to hold after a split or a removal
+const MIN_ENTRIES: usize = 6;
+
+/// the (min_x, min_y) and (max_x, max_y) corner of a box
+type Bounds = ((f64, f64), (f64, f64));
+
+/// a box which contains nothing, every union with it is the other box
+const EMPTY: Bounds = ((f64::INFINITY, f64::INFINITY), (f64::NEG_INFINITY, f64::NEG_INFINITY));
+
+/// Stores boxes instead of positions e.g. building footprints or road segments
+///
+/// Nodes are split and chosen the R*-tree way: a split picks the axis with the smallest margin and
+/// then the distribution with the least overlap, and entries go into the child whose overlap grows
+/// the least. [`RTree::from_elements()`] packs the boxes with Sort-Tile-Recursive instead
+///
+/// Boxes are passed as their (min_x, min_y) and (max_x, max_y) corners and include their borders
+///
+/// An RTree is `Send` and `Sync` whenever `Data` is, so it can be queried from many threads at once
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "RTreeSnapshot<Data>"))]
+pub struct RTree<Data: Copy> {
+    root: Node<Data>,
+
+    count: usize,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Node<Data> {
+    bounds: Bounds,
+    children: Children<Data>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Children<Data> {
+    Leaf(Vec<(Bounds, Data)>),
+    Branch(Vec<Node<Data>>),
+}
+
+impl<Data: Copy> RTree<Data> {
+    /// creates an empty RTree, boxes can be anywhere
+    pub fn new() -> RTree<Data> {
+        RTree {
+            root: Node::leaf(Vec::new()),
+            count: 0,
+        }
+    }
+
+    /// builds a RTree from many boxes at once with Sort-Tile-Recursive packing, which leaves
+    /// almost every node full and the nodes hardly overlapping
+    ///
+    /// # Arguments
+    ///
+    /// * `elements`: ((min, max), data) of each box
+    ///
+    pub fn from_elements(elements: &[(Bounds, Data)]) -> RTree<Data> {
+        if elements.is_empty() {
+            return RTree::new();
+        }
+
+        let mut nodes: Vec<Node<Data>> = pack(elements.to_vec(), |entry| entry.0).into_iter().map(Node::leaf).collect();
+
+        while nodes.len() > 1 {
+            nodes = pack(nodes, |node| node.bounds).into_iter().map(Node::branch).collect();
+        }
+
+        RTree {
+            root: nodes.pop().unwrap(),
+            count: elements.len(),
+        }
+    }
+
+    /// inserts a box
+    ///
+    /// # Arguments
+    ///
+    /// * `min`: (min_x, min_y) corner of the box
+    /// * `max`: (max_x, max_y) corner of the box
+    /// * `data`: the DataValue which will be return when queued
+    ///
+    pub fn insert(&mut self, min: (f64, f64), max: (f64, f64), data: Data) {
+        self.insert_entry(((min, max), data));
+        self.count += 1;
+    }
+
+    /// returns the count of boxes which are currently stored
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn clear(&mut self) {
+        self.root = Node::leaf(Vec::new());
+        self.count = 0;
+    }
+
+    /// gets all DataValues whose box intersects the shape as far as [`Shape::intersects_box()`] can tell
+    ///
+    /// # Arguments
+    ///
+    /// * `shape`: the area which gets searched
+    ///
+    /// returns: Vec<Data>
+    ///
+    pub fn query_intersecting<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let _ = self.search(shape, false, &mut |found| {
+            data.push(found);
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    /// counts the DataValues whose box intersects the shape, more info here [`RTree::query_intersecting()`]
+    pub fn count_intersecting<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let _ = self.search(shape, false, &mut |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    /// checks if there is any box which intersects the shape, more info here [`RTree::query_intersecting()`]
+    pub fn any_intersecting<S: Shape>(&self, shape: &S) -> bool {
+        self.search(shape, false, &mut |_| ControlFlow::Break(())).is_break()
+    }
+
+    /// gets all DataValues whose box lies completely inside the shape
+    ///
+    /// # Arguments
+    ///
+    /// * `shape`: the area which gets searched
+    ///
+    /// returns: Vec<Data>
+    ///
+    pub fn query_contained<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let _ = self.search(shape, true, &mut |found| {
+            data.push(found);
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    /// gets the DataValue whose box is the closest to `position`, boxes containing it have a distance of 0
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: the position to search from
+    ///
+    /// returns: Option<Data> None if there are no boxes
+    ///
+    pub fn nearest(&self, position: (f64, f64)) -> Option<Data> {
+        self.k_nearest(position, 1).first().copied()
+    }
+
+    /// gets the `k` DataValues whose boxes are the closest to `position`, more info here [`RTree::nearest()`]
+    ///
+    /// returns: Vec<Data> sorted from the closest to the farthest
+    ///
+    pub fn k_nearest(&self, position: (f64, f64), k: usize) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        if self.count == 0 || k == 0 {
+            return data;
+        }
+
+        let mut candidates = BinaryHeap::new();
+        candidates.push(Candidate { distance: box_distance(position, self.root.bounds), item: Item::Node(&self.root) });
+
+        // the closest candidate is popped first, so every box comes out in order of its distance
+        while let Some(candidate) = candidates.pop() {
+            match candidate.item {
+                Item::Entry(found) => {
+                    data.push(found);
+
+                    if data.len() == k {
+                        break;
+                    }
+                }
+                Item::Node(node) => match &node.children {
+                    Children::Leaf(entries) => candidates.extend(entries.iter().map(|entry| {
+                        Candidate { distance: box_distance(position, entry.0), item: Item::Entry(entry.1) }
+                    })),
+                    Children::Branch(nodes) => candidates.extend(nodes.iter().map(|node| {
+                        Candidate { distance: box_distance(position, node.bounds), item: Item::Node(node) }
+                    })),
+                },
+            }
+        }
+
+        data
+    }
+
+    fn insert_entry(&mut self, entry: (Bounds, Data)) {
+        if let Some(sibling) = self.root.insert(entry) {
+            let root = std::mem::replace(&mut self.root, Node::leaf(Vec::new()));
+
+            self.root = Node::branch(vec![root, sibling]);
+        }
+    }
+
+    fn search<S: Shape>(&self, shape: &S, contained: bool, visit: &mut impl FnMut(Data) -> ControlFlow<()>) -> ControlFlow<()> {
+        if self.count == 0 {
+            return ControlFlow::Continue(());
+        }
+
+        self.root.search(shape, contained, visit)
+    }
+}
+
+impl<Data: Copy + PartialEq> RTree<Data> {
+    /// removes a box which was inserted with the same corners and data
+    ///
+    /// # Arguments
+    ///
+    /// * `min`: (min_x, min_y) corner of the box
+    /// * `max`: (max_x, max_y) corner of the box
+    /// * `data`: the DataValue of the box
+    ///
+    /// returns: bool false if there was no such box
+    ///
+    pub fn remove(&mut self, min: (f64, f64), max: (f64, f64), data: Data) -> bool {
+        let mut orphans = Vec::new();
+
+        if !self.root.remove((min, max), data, &mut orphans) {
+            return false;
+        }
+
+        self.count -= 1;
+
+        // a root with a single child is not needed anymore
+        while let Children::Branch(nodes) = &mut self.root.children {
+            match nodes.len() {
+                0 => self.root = Node::leaf(Vec::new()),
+                1 => self.root = nodes.pop().unwrap(),
+                _ => break,
+            }
+        }
+
+        // the boxes of nodes which got too small are inserted again
+        for orphan in orphans {
+            self.insert_entry(orphan);
+        }
+
+        true
+    }
+}
+
+impl<Data: Copy> Default for RTree<Data> {
+    fn default() -> Self {
+        RTree::new()
+    }
+}
+
+impl<Data: Copy> Node<Data> {
+    fn leaf(entries: Vec<(Bounds, Data)>) -> Node<Data> {
+        Node {
+            bounds: entries.iter().fold(EMPTY, |bounds, entry| union(bounds, entry.0)),
+            children: Children::Leaf(entries),
+        }
+    }
+
+    fn branch(nodes: Vec<Node<Data>>) -> Node<Data> {
+        Node {
+            bounds: nodes.iter().fold(EMPTY, |bounds, node| union(bounds, node.bounds)),
+            children: Children::Branch(nodes),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match &self.children {
+            Children::Leaf(entries) => entries.len(),
+            Children::Branch(nodes) => nodes.len(),
+        }
+    }
+
+    fn update_bounds(&mut self) {
+        self.bounds = match &self.children {
+            Children::Leaf(entries) => entries.iter().fold(EMPTY, |bounds, entry| union(bounds, entry.0)),
+            Children::Branch(nodes) => nodes.iter().fold(EMPTY, |bounds, node| union(bounds, node.bounds)),
+        };
+    }
+
+    /// inserts the entry into this subtree, returns the new sibling if this node had to be split
+    fn insert(&mut self, entry: (Bounds, Data)) -> Option<Node<Data>> {
+        self.bounds = union(self.bounds, entry.0);
+
+        let sibling = match &mut self.children {
+            Children::Leaf(entries) => {
+                entries.push(entry);
+
+                if entries.len() <= MAX_ENTRIES {
+                    return None;
+                }
+
+                Node::leaf(split(entries, |entry| entry.0))
+            }
+            Children::Branch(nodes) => {
+                let index = choose_subtree(nodes, entry.0);
+
+                let sibling = nodes[index].insert(entry)?;
+
+                nodes.push(sibling);
+
+                if nodes.len() <= MAX_ENTRIES {
+                    return None;
+                }
+
+                Node::branch(split(nodes, |node| node.bounds))
+            }
+        };
+
+        self.update_bounds();
+
+        Some(sibling)
+    }
+
+    fn search<S: Shape>(&self, shape: &S, contained: bool, visit: &mut impl FnMut(Data) -> ControlFlow<()>) -> ControlFlow<()> {
+        if !shape.intersects_box(self.bounds.0, self.bounds.1) {
+            return ControlFlow::Continue(());
+        }
+
+        // every box of the subtree is inside the shape
+        if shape.contains_box(self.bounds.0, self.bounds.1) {
+            return self.visit_all(visit);
+        }
+
+        match &self.children {
+            Children::Leaf(entries) => {
+                for entry in entries {
+                    let found = match contained {
+                        true => shape.contains_box(entry.0.0, entry.0.1),
+                        false => shape.intersects_box(entry.0.0, entry.0.1),
+                    };
+
+                    if found {
+                        visit(entry.1)?;
+                    }
+                }
+            }
+            Children::Branch(nodes) => {
+                for node in nodes {
+                    node.search(shape, contained, visit)?;
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn visit_all(&self, visit: &mut impl FnMut(Data) -> ControlFlow<()>) -> ControlFlow<()> {
+        match &self.children {
+            Children::Leaf(entries) => entries.iter().try_for_each(|entry| visit(entry.1)),
+            Children::Branch(nodes) => nodes.iter().try_for_each(|node| node.visit_all(visit)),
+        }
+    }
+
+    /// moves every entry of this subtree into `entries`
+    fn collect(self, entries: &mut Vec<(Bounds, Data)>) {
+        match self.children {
+            Children::Leaf(leaf) => entries.extend(leaf),
+            Children::Branch(nodes) => nodes.into_iter().for_each(|node| node.collect(entries)),
+        }
+    }
+}
+
+impl<Data: Copy + PartialEq> Node<Data> {
+    /// removes the entry from this subtree, the entries of nodes which become too small are moved to `orphans`
+    fn remove(&mut self, bounds: Bounds, data: Data, orphans: &mut Vec<(Bounds, Data)>) -> bool {
+        if !covers(self.bounds, bounds) {
+            return false;
+        }
+
+        let removed = match &mut self.children {
+            Children::Leaf(entries) => match entries.iter().position(|entry| entry.0 == bounds && entry.1 == data) {
+                Some(index) => {
+                    entries.remove(index);
+                    true
+                }
+                None => false,
+            },
+            Children::Branch(nodes) => match nodes.iter_mut().position(|node| node.remove(bounds, data, orphans)) {
+                Some(index) => {
+                    if nodes[index].len() < MIN_ENTRIES {
+                        nodes.remove(index).collect(orphans);
+                    }
+
+                    true
+                }
+                None => false,
+            },
+        };
+
+        if removed {
+            self.update_bounds();
+        }
+
+        removed
+    }
+}
+
+/// the fields of an [RTree] as they are deserialized, [`RTree::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RTreeSnapshot<Data> {
+    root: Node<Data>,
+    count: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> TryFrom<RTreeSnapshot<Data>> for RTree<Data> {
+    type Error = &'static str;
+
+    /// each node has to cover its children and only the root may be empty
+    fn try_from(snapshot: RTreeSnapshot<Data>) -> Result<Self, Self::Error> {
+        let count = match &snapshot.root.children {
+            Children::Leaf(entries) if entries.is_empty() => 0,
+            _ => snapshot.root.count()?,
+        };
+
+        if count != snapshot.count {
+            return Err("count does not match the boxes");
+        }
+
+        Ok(RTree {
+            root: snapshot.root,
+            count: snapshot.count,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> Node<Data> {
+    /// the count of boxes in this subtree, an error if a node is empty or does not cover its children
+    fn count(&self) -> Result<usize, &'static str> {
+        match &self.children {
+            Children::Leaf(entries) if !entries.is_empty() && entries.iter().all(|entry| covers(self.bounds, entry.0)) => Ok(entries.len()),
+            Children::Branch(nodes) if !nodes.is_empty() && nodes.iter().all(|node| covers(self.bounds, node.bounds)) => nodes.iter().map(Node::count).sum(),
+            _ => Err("node is empty or does not cover its children"),
+        }
+    }
+}
+
+/// picks the child which should take the box: near the leaves the one whose overlap with its
+/// siblings grows the least, above them the one whose area grows the least
+fn choose_subtree<Data>(nodes: &[Node<Data>], bounds: Bounds) -> usize {
+    let leaves = matches!(nodes[0].children, Children::Leaf(_));
+
+    let cost = |index: usize| {
+        let node = nodes[index].bounds;
+        let enlarged = union(node, bounds);
+
+        let overlap = match leaves {
+            true => nodes.iter().enumerate().filter(|(other, _)| *other != index)
+                .map(|(_, other)| overlap(enlarged, other.bounds) - overlap(node, other.bounds))
+                .sum(),
+            false => 0.0,
+        };
+
+        (overlap, area(enlarged) - area(node), area(node))
+    };
+
+    (0..nodes.len()).map(|index| (cost(index), index))
+        .min_by(|a, b| a.0.0.total_cmp(&b.0.0).then(a.0.1.total_cmp(&b.0.1)).then(a.0.2.total_cmp(&b.0.2)))
+        .unwrap().1
+}
+
+/// splits `items` the R*-tree way and returns the second group
+fn split<T>(items: &mut Vec<T>, bounds: impl Fn(&T) -> Bounds) -> Vec<T> {
+    let sort = |items: &mut Vec<T>, axis: usize, upper: bool| {
+        items.sort_by(|a, b| corner(bounds(a), axis, upper).total_cmp(&corner(bounds(b), axis, upper)))
+    };
+
+    // the axis where the groups have the smallest margin summed up over every distribution
+    let axis = [0, 1].into_iter().min_by(|a, b| {
+        let margins = |axis: usize, items: &mut Vec<T>| [false, true].into_iter().map(|upper| {
+            sort(items, axis, upper);
+
+            distributions(items, &bounds).map(|(first, second, _)| margin(first) + margin(second)).sum::<f64>()
+        }).sum::<f64>();
+
+        margins(*a, items).total_cmp(&margins(*b, items))
+    }).unwrap();
+
+    // the distribution with the least overlap and then the least area
+    let mut best = (f64::INFINITY, f64::INFINITY, false, 0);
+
+    for upper in [false, true] {
+        sort(items, axis, upper);
+
+        for (first, second, index) in distributions(items, &bounds) {
+            let cost = (overlap(first, second), area(first) + area(second));
+
+            if cost.0 < best.0 || (cost.0 == best.0 && cost.1 < best.1) {
+                best = (cost.0, cost.1, upper, index);
+            }
+        }
+    }
+
+    sort(items, axis, best.2);
+
+    items.split_off(best.3)
+}
+
+/// the bounds of both groups for every way to split the sorted `items` with at least `MIN_ENTRIES` in each group
+fn distributions<T>(items: &[T], bounds: impl Fn(&T) -> Bounds) -> impl Iterator<Item=(Bounds, Bounds, usize)> {
+    let mut prefix = Vec::with_capacity(items.len() + 1);
+    let mut suffix = vec![EMPTY; items.len() + 1];
+
+    prefix.push(EMPTY);
+
+    for item in items {
+        prefix.push(union(*prefix.last().unwrap(), bounds(item)));
+    }
+
+    for (index, item) in items.iter().enumerate().rev() {
+        suffix[index] = union(suffix[index + 1], bounds(item));
+    }
+
+    (MIN_ENTRIES..=items.len() - MIN_ENTRIES).map(move |index| (prefix[index], suffix[index], index))
+}
+
+/// groups the items into nodes with Sort-Tile-Recursive: vertical slices sorted by x which are then cut by y
+fn pack<T>(mut items: Vec<T>, bounds: impl Fn(&T) -> Bounds) -> Vec<Vec<T>> {
+    let center = |item: &T, axis: usize| {
+        let bounds = bounds(item);
+
+        corner(bounds, axis, false) + corner(bounds, axis, true)
+    };
+
+    let nodes = items.len().div_ceil(MAX_ENTRIES);
+    let slice_size = (nodes as f64).sqrt().ceil() as usize * MAX_ENTRIES;
+
+    items.sort_by(|a, b| center(a, 0).total_cmp(&center(b, 0)));
+
+    let mut groups = Vec::with_capacity(nodes);
+    let mut items = items.into_iter();
+
+    loop {
+        let mut slice: Vec<T> = items.by_ref().take(slice_size).collect();
+
+        if slice.is_empty() {
+            return groups;
+        }
+
+        slice.sort_by(|a, b| center(a, 1).total_cmp(&center(b, 1)));
+
+        let mut slice = slice.into_iter();
+
+        loop {
+            let group: Vec<T> = slice.by_ref().take(MAX_ENTRIES).collect();
+
+            if group.is_empty() {
+                break;
+            }
+
+            groups.push(group);
+        }
+    }
+}
+
+fn corner(bounds: Bounds, axis: usize, upper: bool) -> f64 {
+    match (axis, upper) {
+        (0, false) => bounds.0.0,
+        (0, true) => bounds.1.0,
+        (_, false) => bounds.0.1,
+        (_, true) => bounds.1.1,
+    }
+}
+
+fn union(a: Bounds, b: Bounds) -> Bounds {
+    ((a.0.0.min(b.0.0), a.0.1.min(b.0.1)), (a.1.0.max(b.1.0), a.1.1.max(b.1.1)))
+}
+
+/// true if `inner` lies completely inside `outer`
+fn covers(outer: Bounds, inner: Bounds) -> bool {
+    outer.0.0 <= inner.0.0 && outer.0.1 <= inner.0.1 && outer.1.0 >= inner.1.0 && outer.1.1 >= inner.1.1
+}
+
+fn area(bounds: Bounds) -> f64 {
+    (bounds.1.0 - bounds.0.0).max(0.0) * (bounds.1.1 - bounds.0.1).max(0.0)
+}
+
+fn margin(bounds: Bounds) -> f64 {
+    (bounds.1.0 - bounds.0.0) + (bounds.1.1 - bounds.0.1)
+}
+
+fn overlap(a: Bounds, b: Bounds) -> f64 {
+    area(((a.0.0.max(b.0.0), a.0.1.max(b.0.1)), (a.1.0.min(b.1.0), a.1.1.min(b.1.1))))
+}
+
+/// the squared distance from the position to the closest point of the box
+fn box_distance(position: (f64, f64), bounds: Bounds) -> f64 {
+    let nearest = nearest_in_box(position, bounds.0, bounds.1);
+
+    (nearest.0 - position.0).powi(2) + (nearest.1 - position.1).powi(2)
+}
+
+enum Item<'a, Data> {
+    Node(&'a Node<Data>),
+    Entry(Data),
+}
+
+/// a node or box waiting to be searched, the closest one gets popped first
+struct Candidate<'a, Data> {
+    distance: f64,
+    item: Item<'a, Data>,
+}
+
+impl<Data> PartialEq for Candidate<'_, Data> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Data> Eq for Candidate<'_, Data> {}
+
+impl<Data> PartialOrd for Candidate<'_, Data> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Data> Ord for Candidate<'_, Data> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
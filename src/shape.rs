@@ -0,0 +1,189 @@
+use std::ops::{ControlFlow, Range};
+use crate::metric::{Euclidean, Metric};
+use crate::util::{farthest_in_box, filter_lanes, nearest_in_box, offset};
+
+/// An area which can be searched for in a [SpatialIndex](crate::SpatialIndex)
+///
+/// Boxes are passed as their (min_x, min_y) and (max_x, max_y) corners and include their borders
+pub trait Shape {
+    /// returns: ((f64, f64), (f64, f64)) the min and max corner of a box which contains the whole shape
+    fn bounds(&self) -> ((f64, f64), (f64, f64));
+
+    /// returns: bool true if the position lies inside the shape
+    fn contains(&self, position: (f64, f64)) -> bool;
+
+    /// returns: bool true if every position of the box lies inside the shape
+    fn contains_box(&self, min: (f64, f64), max: (f64, f64)) -> bool;
+
+    /// returns: bool false if no position of the box can lie inside the shape
+    fn intersects_box(&self, min: (f64, f64), max: (f64, f64)) -> bool;
+
+    /// calls `visit` with the index of every position (`xs[i]`, `ys[i]`) inside the shape in ascending order
+    ///
+    /// the positions are tested in chunks the compiler can vectorise, the result is always the
+    /// same as calling [`Shape::contains()`] for each position
+    ///
+    /// # Arguments
+    ///
+    /// * `xs`: x of each position
+    /// * `ys`: y of each position, as long as `xs`
+    /// * `visit`: gets called for each position inside the shape and can stop the filter
+    ///
+    fn filter(&self, xs: &[f64], ys: &[f64], visit: &mut impl FnMut(usize) -> ControlFlow<()>) -> ControlFlow<()> {
+        filter_lanes(xs, ys, |position| self.contains(position), visit)
+    }
+}
+
+/// all positions which are at most `radius` away from `position`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circle<M: Metric = Euclidean> {
+    position: (f64, f64),
+    radius: f64,
+    metric: M,
+}
+
+impl Circle {
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the circle
+    /// * `radius`: radius of the circle
+    ///
+    pub fn new(position: (f64, f64), radius: f64) -> Circle {
+        Circle::with_metric(position, radius, Euclidean)
+    }
+}
+
+impl<M: Metric> Circle<M> {
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the circle
+    /// * `radius`: radius of the circle
+    /// * `metric`: how the distance to `position` is measured
+    ///
+    pub fn with_metric(position: (f64, f64), radius: f64, metric: M) -> Circle<M> {
+        Circle { position, radius, metric }
+    }
+}
+
+impl<M: Metric> Shape for Circle<M> {
+    fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        let extent = self.metric.extent(self.radius);
+
+        ((self.position.0 - extent.0, self.position.1 - extent.1), (self.position.0 + extent.0, self.position.1 + extent.1))
+    }
+
+    fn contains(&self, position: (f64, f64)) -> bool {
+        self.metric.in_range(offset(position, self.position), self.radius)
+    }
+
+    fn contains_box(&self, min: (f64, f64), max: (f64, f64)) -> bool {
+        self.contains(farthest_in_box(self.position, min, max))
+    }
+
+    fn intersects_box(&self, min: (f64, f64), max: (f64, f64)) -> bool {
+        self.contains(nearest_in_box(self.position, min, max))
+    }
+}
+
+/// all positions which are at least `inner` and at most `outer` away from `position`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ring<M: Metric = Euclidean> {
+    position: (f64, f64),
+    inner: f64,
+    outer: f64,
+    metric: M,
+}
+
+impl Ring {
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the ring
+    /// * `inner`: radius of the excluded inner circle
+    /// * `outer`: radius of the outer circle
+    ///
+    pub fn new(position: (f64, f64), inner: f64, outer: f64) -> Ring {
+        Ring::with_metric(position, inner, outer, Euclidean)
+    }
+}
+
+impl<M: Metric> Ring<M> {
+    ///
+    /// # Arguments
+    ///
+    /// * `position`: center of the ring
+    /// * `inner`: radius of the excluded inner circle
+    /// * `outer`: radius of the outer circle
+    /// * `metric`: how the distance to `position` is measured
+    ///
+    pub fn with_metric(position: (f64, f64), inner: f64, outer: f64, metric: M) -> Ring<M> {
+        Ring { position, inner, outer, metric }
+    }
+
+    fn in_outer(&self, position: (f64, f64)) -> bool {
+        self.metric.in_range(offset(position, self.position), self.outer)
+    }
+
+    fn in_inner(&self, position: (f64, f64)) -> bool {
+        self.metric.in_range_exclusive(offset(position, self.position), self.inner)
+    }
+}
+
+impl<M: Metric> Shape for Ring<M> {
+    fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        let extent = self.metric.extent(self.outer);
+
+        ((self.position.0 - extent.0, self.position.1 - extent.1), (self.position.0 + extent.0, self.position.1 + extent.1))
+    }
+
+    fn contains(&self, position: (f64, f64)) -> bool {
+        self.in_outer(position) && !self.in_inner(position)
+    }
+
+    fn contains_box(&self, min: (f64, f64), max: (f64, f64)) -> bool {
+        self.in_outer(farthest_in_box(self.position, min, max)) && !self.in_inner(nearest_in_box(self.position, min, max))
+    }
+
+    fn intersects_box(&self, min: (f64, f64), max: (f64, f64)) -> bool {
+        self.in_outer(nearest_in_box(self.position, min, max)) && !self.in_inner(farthest_in_box(self.position, min, max))
+    }
+}
+
+/// all positions in min_x..max_x and min_y..max_y
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rect {
+    x: Range<f64>,
+    y: Range<f64>,
+}
+
+impl Rect {
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x of the rectangle
+    /// * `y`: min_y..max_y of the rectangle
+    ///
+    pub fn new(x: Range<f64>, y: Range<f64>) -> Rect {
+        Rect { x, y }
+    }
+}
+
+impl Shape for Rect {
+    fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        ((self.x.start, self.y.start), (self.x.end, self.y.end))
+    }
+
+    fn contains(&self, position: (f64, f64)) -> bool {
+        self.x.contains(&position.0) && self.y.contains(&position.1)
+    }
+
+    fn contains_box(&self, min: (f64, f64), max: (f64, f64)) -> bool {
+        self.contains(min) && self.contains(max)
+    }
+
+    fn intersects_box(&self, min: (f64, f64), max: (f64, f64)) -> bool {
+        min.0 < self.x.end && max.0 >= self.x.start && min.1 < self.y.end && max.1 >= self.y.start
+    }
+}
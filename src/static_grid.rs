@@ -0,0 +1,391 @@
+use std::ops::{ControlFlow, Range};
+use crate::config::{self, check_cell_count, ConfigError};
+use crate::grid::Grid;
+use crate::SpatialIndex;
+use crate::shape::Shape;
+use crate::util::{is_edge_cell, CellAxis};
+
+/// A [Grid] which is built once from all of its elements and can not be changed afterwards
+///
+/// All elements are stored sorted by their cell, each cell is the range between two offsets. So
+/// there are only a few allocations no matter how many cells there are
+///
+/// x, y and data of the elements are kept in separate `Vec`s, which lets [`Shape::filter()`]
+/// test many positions of a cell at once
+///
+/// A StaticGrid is `Send` and `Sync` whenever `Data` is, so it can be queried from many threads at once
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "StaticGridSnapshot<Data>"))]
+pub struct StaticGrid<Data: Copy> {
+    /// the elements of cell `i` are at `offsets[i]..offsets[i + 1]`
+    offsets: Vec<usize>,
+
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+    data: Vec<Data>,
+
+    cell_count: (usize, usize),
+    cell_size: (f64, f64),
+
+    x: Range<f64>,
+    y: Range<f64>,
+
+    /// whether any element lies outside of the bounds, which only happens when it is copied from
+    /// a [Grid] which was filled with `insert_unchecked`
+    outside: bool,
+}
+
+impl<Data: Copy> StaticGrid<Data> {
+    /// builds a StaticGrid with a counting sort, each cell keeps the order of `elements`
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `cell_count`: (count_x, count_y) defines how many cell should be present
+    /// * `elements`: (position, data) of each element, all of them have to lie inside of the bounds
+    ///
+    /// panics if the settings are not valid, [`StaticGrid::try_from_elements()`] returns the reason instead
+    ///
+    pub fn from_elements(x: Range<f64>, y: Range<f64>, cell_count: (usize, usize), elements: &[((f64, f64), Data)]) -> StaticGrid<Data> {
+        StaticGrid::try_from_elements(x, y, cell_count, elements).unwrap_or_else(|error| panic!("tried to create a StaticGrid from invalid settings: {}", error))
+    }
+
+    /// like [`StaticGrid::from_elements()`] but returns a ConfigError for empty, reversed or infinite
+    /// ranges and for a cell_count without cells
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: min_x..max_x defines the area in wich data can be inserted
+    /// * `y`: min_y..max_y defines the area in wich data can be inserted
+    /// * `cell_count`: (count_x, count_y) defines how many cell should be present
+    /// * `elements`: (position, data) of each element, panics if one lies outside of the bounds
+    ///
+    /// returns: Result<StaticGrid<Data>, ConfigError>
+    ///
+    pub fn try_from_elements(x: Range<f64>, y: Range<f64>, cell_count: (usize, usize), elements: &[((f64, f64), Data)]) -> Result<StaticGrid<Data>, ConfigError> {
+        config::check_bounds(&x, &y)?;
+        check_cell_count(cell_count)?;
+
+        let mut grid = StaticGrid {
+            offsets: vec![0; cell_count.0 * cell_count.1 + 1],
+            xs: vec![0.0; elements.len()],
+            ys: vec![0.0; elements.len()],
+            data: elements.iter().map(|element| element.1).collect(),
+            cell_count,
+            cell_size: ((x.end - x.start) / cell_count.0 as f64, (y.end - y.start) / cell_count.1 as f64),
+            x,
+            y,
+            outside: false,
+        };
+
+        let indexes: Vec<usize> = elements.iter().map(|element| {
+            grid.check_bounds(element.0);
+
+            grid.cell_index(element.0)
+        }).collect();
+
+        for index in &indexes {
+            grid.offsets[index + 1] += 1;
+        }
+
+        for i in 1..grid.offsets.len() {
+            grid.offsets[i] += grid.offsets[i - 1];
+        }
+
+        let mut next = grid.offsets.clone();
+
+        for (element, index) in elements.iter().zip(indexes) {
+            grid.xs[next[index]] = element.0.0;
+            grid.ys[next[index]] = element.0.1;
+            grid.data[next[index]] = element.1;
+            next[index] += 1;
+        }
+
+        Ok(grid)
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// * `indexes`: (x,y) cord of the cell, panics if it lies outside of the cell_count
+    ///
+    /// returns: (&[f64], &[f64], &[Data]) x, y and data of the elements stored in the cell
+    ///
+    pub fn cell(&self, indexes: (usize, usize)) -> (&[f64], &[f64], &[Data]) {
+        if indexes.0 >= self.cell_count.0 || indexes.1 >= self.cell_count.1 {
+            panic!("tried to get cell {:?} of a StaticGrid with a cell_count of {:?}", indexes, self.cell_count)
+        }
+
+        let range = self.cell_range(indexes);
+
+        (&self.xs[range.clone()], &self.ys[range.clone()], &self.data[range])
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: (usize, usize) the defined cell_count
+    ///
+    pub fn cell_count(&self) -> (usize, usize) {
+        self.cell_count
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: (f64, f64) width and height of each cell
+    ///
+    pub fn cell_size(&self) -> (f64, f64) {
+        self.cell_size
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: (Range<f64>, Range<f64>) min_x..max_x and min_y..max_y the area in wich data can be inserted
+    ///
+    pub fn bounds(&self) -> (Range<f64>, Range<f64>) {
+        (self.x.clone(), self.y.clone())
+    }
+
+    fn check_bounds(&self, position: (f64, f64)) {
+        if position.0 < self.x.start || position.0 >= self.x.end || position.1 < self.y.start || position.1 >= self.y.end {
+            panic!("tried to insert position into StaticGrid which was out of bounce")
+        }
+    }
+
+    fn cell_index(&self, position: (f64, f64)) -> usize {
+        let index_position = self.clamped_index(position);
+
+        index_position.0 + (index_position.1 * self.cell_count.0)
+    }
+
+    fn cell_range(&self, indexes: (usize, usize)) -> Range<usize> {
+        let index = indexes.0 + (indexes.1 * self.cell_count.0);
+
+        self.offsets[index]..self.offsets[index + 1]
+    }
+
+    fn search<S: Shape>(&self, shape: &S, visit: &mut impl FnMut(Hit) -> ControlFlow<()>) -> ControlFlow<()> {
+        let (min, max) = shape.bounds();
+
+        let min = self.clamped_index(min);
+        let max = self.clamped_index(max);
+        let (axis_x, axis_y) = self.axes();
+
+        for y in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                let elements = self.cell_range((x, y));
+
+                if elements.is_empty() {
+                    continue;
+                }
+
+                // testing the cell only pays off if it saves enough distance checks, the box of an
+                // edge cell says nothing about the elements outside of the grid
+                if elements.len() > 4 && !(self.outside && is_edge_cell((x, y), self.cell_count)) {
+                    let pos = (axis_x.edge(x), axis_y.edge(y));
+                    let end = (axis_x.edge(x + 1), axis_y.edge(y + 1));
+
+                    if shape.contains_box(pos, end) {
+                        visit(Hit::All(elements))?;
+                        continue;
+                    }
+
+                    if !shape.intersects_box(pos, end) {
+                        continue;
+                    }
+                }
+
+                shape.filter(&self.xs[elements.clone()], &self.ys[elements.clone()], &mut |i| visit(Hit::One(elements.start + i)))?;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// the cell of the position, positions outside of the grid are moved to the closest cell
+    fn clamped_index(&self, position: (f64, f64)) -> (usize, usize) {
+        let (x, y) = self.axes();
+
+        (x.index(position.0), y.index(position.1))
+    }
+
+    fn axes(&self) -> (CellAxis, CellAxis) {
+        (
+            CellAxis { start: self.x.start, end: self.x.end, cell_size: self.cell_size.0, count: self.cell_count.0 },
+            CellAxis { start: self.y.start, end: self.y.end, cell_size: self.cell_size.1, count: self.cell_count.1 },
+        )
+    }
+}
+
+impl<Data: Copy> SpatialIndex<Data> for StaticGrid<Data> {
+    fn count(&self) -> usize {
+        self.data.len()
+    }
+
+    fn query<S: Shape>(&self, shape: &S) -> Vec<Data> {
+        let mut data = Vec::new();
+
+        let _ = self.search(shape, &mut |hit| {
+            match hit {
+                Hit::All(range) => data.extend_from_slice(&self.data[range]),
+                Hit::One(index) => data.push(self.data[index]),
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        data
+    }
+
+    fn query_count<S: Shape>(&self, shape: &S) -> usize {
+        let mut count = 0;
+
+        let _ = self.search(shape, &mut |hit| {
+            match hit {
+                Hit::All(range) => count += range.len(),
+                Hit::One(_) => count += 1,
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    fn query_any<S: Shape>(&self, shape: &S) -> bool {
+        self.search(shape, &mut |_| ControlFlow::Break(())).is_break()
+    }
+}
+
+impl<Data: Copy> From<&Grid<Data>> for StaticGrid<Data> {
+    /// copies the cells of the Grid one after another into a StaticGrid
+    fn from(grid: &Grid<Data>) -> Self {
+        let cell_count = grid.cell_count();
+        let (x, y) = grid.bounds();
+
+        let mut offsets = Vec::with_capacity(cell_count.0 * cell_count.1 + 1);
+        let mut xs = Vec::with_capacity(grid.count());
+        let mut ys = Vec::with_capacity(grid.count());
+        let mut data = Vec::with_capacity(grid.count());
+
+        offsets.push(0);
+
+        for cell_y in 0..cell_count.1 {
+            for cell_x in 0..cell_count.0 {
+                for element in grid.cell((cell_x, cell_y)) {
+                    xs.push(element.0.0);
+                    ys.push(element.0.1);
+                    data.push(element.1);
+                }
+
+                offsets.push(data.len());
+            }
+        }
+
+        let outside = xs.iter().zip(&ys).any(|(position_x, position_y)| !x.contains(position_x) || !y.contains(position_y));
+
+        StaticGrid {
+            offsets,
+            xs,
+            ys,
+            data,
+            cell_count,
+            cell_size: grid.cell_size(),
+            x,
+            y,
+            outside,
+        }
+    }
+}
+
+enum Hit {
+    /// every element of the cell lies in the shape
+    All(Range<usize>),
+    One(usize),
+}
+
+/// the fields of a [StaticGrid] as they are deserialized, [`StaticGrid::try_from()`] checks them before they are used
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct StaticGridSnapshot<Data> {
+    offsets: Vec<usize>,
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+    data: Vec<Data>,
+    cell_count: (usize, usize),
+    cell_size: (f64, f64),
+    x: Range<f64>,
+    y: Range<f64>,
+    outside: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> TryFrom<StaticGridSnapshot<Data>> for StaticGrid<Data> {
+    type Error = &'static str;
+
+    fn try_from(snapshot: StaticGridSnapshot<Data>) -> Result<Self, Self::Error> {
+        let grid = StaticGrid {
+            offsets: snapshot.offsets,
+            xs: snapshot.xs,
+            ys: snapshot.ys,
+            data: snapshot.data,
+            cell_count: snapshot.cell_count,
+            cell_size: snapshot.cell_size,
+            x: snapshot.x,
+            y: snapshot.y,
+            outside: snapshot.outside,
+        };
+
+        grid.validate()?;
+
+        Ok(grid)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Data: Copy> StaticGrid<Data> {
+    /// checks everything the other methods rely on, so a corrupted snapshot can not make them panic
+    /// or miss elements
+    fn validate(&self) -> Result<(), &'static str> {
+        if config::check_bounds(&self.x, &self.y).is_err() || check_cell_count(self.cell_count).is_err() {
+            return Err("bounds or cell_count are not valid");
+        }
+
+        if !(self.cell_size.0 > 0.0 && self.cell_size.0.is_finite() && self.cell_size.1 > 0.0 && self.cell_size.1.is_finite()) {
+            return Err("cell_size is not valid");
+        }
+
+        if self.xs.len() != self.data.len() || self.ys.len() != self.data.len() {
+            return Err("positions and data differ in length");
+        }
+
+        let cells = self.cell_count.0 * self.cell_count.1;
+
+        if self.offsets.len().checked_sub(1) != Some(cells) || self.offsets[0] != 0 || self.offsets[cells] != self.data.len() || self.offsets.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err("offsets do not split the elements into the cells");
+        }
+
+        let mut outside = false;
+
+        for (index, range) in self.offsets.windows(2).enumerate() {
+            for element in range[0]..range[1] {
+                let position = (self.xs[element], self.ys[element]);
+
+                if self.cell_index(position) != index {
+                    return Err("element lies in the wrong cell");
+                }
+
+                outside |= !self.x.contains(&position.0) || !self.y.contains(&position.1);
+            }
+        }
+
+        if outside != self.outside {
+            return Err("outside does not match the elements");
+        }
+
+        Ok(())
+    }
+}
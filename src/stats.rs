@@ -0,0 +1,219 @@
+//! Reports of how a structure divides its elements, to tune the cell_count of a [Grid](crate::grid::Grid)
+//! or the capacity of a [QuadTree](crate::quad_tree::QuadTree)
+//!
+//! [QueryStats] counts what single queries did e.g. how many positions they tested
+//!
+//! All reports implement `Display`, so they can be logged as they are
+//!
+//! ```
+//! use spatial_neighbors::SpatialIndexMut;
+//! use spatial_neighbors::grid::Grid;
+//!
+//! let mut grid = Grid::with_cell_count(0.0..10.0, 0.0..10.0, (10, 10));
+//!
+//! for i in 0..50 {
+//!     grid.insert((i as f64 / 5.0, 1.0), i);
+//! }
+//!
+//! let stats = grid.stats();
+//!
+//! assert_eq!(stats.max_load, 5);
+//! assert_eq!(stats.empty_ratio(), 0.9);
+//! println!("{}", stats);
+//! ```
+
+use std::fmt::{Display, Formatter};
+use crate::util::Visit;
+
+/// The load of the cells of a [Grid](crate::grid::Grid), created by [`Grid::stats()`](crate::grid::Grid::stats)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridStats {
+    pub cell_count: (usize, usize),
+    /// count of all elements
+    pub count: usize,
+    /// count of elements outside of the bounds, they are stored in the edge cells
+    pub outside: usize,
+    /// count of cells by their load, `occupancy[0]` counts the empty cells and `occupancy[i]` the
+    /// cells with `2^(i-1)..2^i` elements
+    pub occupancy: Vec<usize>,
+    pub empty_cells: usize,
+    /// the most elements stored in one cell
+    pub max_load: usize,
+}
+
+impl GridStats {
+    ///
+    /// # Arguments
+    ///
+    /// returns: f64 the share of cells without elements in 0.0..=1.0
+    ///
+    pub fn empty_ratio(&self) -> f64 {
+        self.empty_cells as f64 / (self.cell_count.0 * self.cell_count.1) as f64
+    }
+
+    ///
+    /// # Arguments
+    ///
+    /// returns: f64 the average count of elements in the cells which are not empty, 0 without elements
+    ///
+    pub fn mean_load(&self) -> f64 {
+        let filled = self.cell_count.0 * self.cell_count.1 - self.empty_cells;
+
+        if filled == 0 {
+            return 0.0;
+        }
+
+        self.count as f64 / filled as f64
+    }
+}
+
+impl Display for GridStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let cells = self.cell_count.0 * self.cell_count.1;
+
+        writeln!(f, "Grid with {} elements in {} x {} cells, {} of them outside of the bounds", self.count, self.cell_count.0, self.cell_count.1, self.outside)?;
+        writeln!(f, "empty cells: {} of {} ({:.1}%)", self.empty_cells, cells, self.empty_ratio() * 100.0)?;
+        writeln!(f, "load: at most {}, {:.1} on average in the cells which are not empty", self.max_load, self.mean_load())?;
+        writeln!(f, "{:<14}{:>10}", "elements", "cells")?;
+
+        for (bucket, cells) in self.occupancy.iter().enumerate() {
+            writeln!(f, "{:<14}{:>10}", loads(bucket), cells)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The shape of a [QuadTree](crate::quad_tree::QuadTree), created by [`QuadTree::stats()`](crate::quad_tree::QuadTree::stats)
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuadTreeStats {
+    /// count of all elements
+    pub count: usize,
+    pub capacity: u16,
+    pub max_depth: u32,
+    /// count of all nodes including the root
+    pub node_count: usize,
+    /// count of nodes without children
+    pub leaf_count: usize,
+    /// count of leaves without elements, nodes are not merged again after their elements were removed
+    pub empty_leaves: usize,
+    /// the nodes at each depth, `depths[0]` is the root
+    pub depths: Vec<DepthStats>,
+    /// count of nodes with children which still store elements themselves, these elements are
+    /// tested by every query which reaches the node
+    pub internal_with_elements: usize,
+    /// count of elements stored in nodes with children
+    pub internal_elements: usize,
+    /// the most elements stored in one node, more than the capacity only at the max_depth
+    pub max_len: usize,
+    /// the average count of elements of a leaf divided by the capacity
+    pub leaf_fill: f64,
+    /// count of element slots in the shared storage, the blocks of the nodes reserve slots for
+    /// later elements and free blocks wait to be reused
+    pub slots: usize,
+}
+
+/// The nodes at one depth of a [QuadTree](crate::quad_tree::QuadTree)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DepthStats {
+    pub nodes: usize,
+    pub leaves: usize,
+    /// count of elements stored in the nodes at this depth, not counting their children
+    pub elements: usize,
+}
+
+impl Display for QuadTreeStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "QuadTree with {} elements in {} nodes, {} of them leaves, capacity {}", self.count, self.node_count, self.leaf_count, self.capacity)?;
+
+        if self.max_depth != u32::MAX {
+            write!(f, ", max_depth {}", self.max_depth)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "leaves: {} empty, filled to {:.1}% of the capacity on average, at most {} elements in one node", self.empty_leaves, self.leaf_fill * 100.0, self.max_len)?;
+        writeln!(f, "internal nodes holding elements: {} with {} elements", self.internal_with_elements, self.internal_elements)?;
+        writeln!(f, "{:<8}{:>10}{:>10}{:>10}", "depth", "nodes", "leaves", "elements")?;
+
+        for (depth, stats) in self.depths.iter().enumerate() {
+            writeln!(f, "{:<8}{:>10}{:>10}{:>10}", depth, stats.nodes, stats.leaves, stats.elements)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What queries did, filled by [`Grid::query_with_stats()`](crate::grid::Grid::query_with_stats)
+/// and [`QuadTree::query_with_stats()`](crate::quad_tree::QuadTree::query_with_stats)
+///
+/// The counters are only ever added to, so one QueryStats can sum up e.g. all queries of a frame
+///
+/// ```
+/// use spatial_neighbors::SpatialIndexMut;
+/// use spatial_neighbors::quad_tree::QuadTree;
+/// use spatial_neighbors::stats::QueryStats;
+///
+/// let mut quad_tree = QuadTree::with_capacity(0.0..10.0, 0.0..10.0, 4);
+///
+/// for i in 0..100 {
+///     quad_tree.insert(((i % 10) as f64 + 0.5, (i / 10) as f64 + 0.5), i);
+/// }
+///
+/// let mut stats = QueryStats::default();
+/// let found = quad_tree.in_circle_with_stats((5.0, 5.0), 2.0, &mut stats);
+///
+/// assert_eq!(stats.returned, found.len());
+/// assert!(stats.tested < 100);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    /// count of cells or nodes with elements which were reached, including the accepted and rejected ones
+    pub visited: usize,
+    /// count of cells or nodes whose box lies inside the shape, their elements were taken without testing them
+    pub accepted: usize,
+    /// count of cells or nodes whose box lies outside the shape, their elements were skipped without testing them
+    pub rejected: usize,
+    /// count of positions which were tested against the shape
+    pub tested: usize,
+    /// count of DataValues which were returned
+    pub returned: usize,
+}
+
+impl QueryStats {
+    pub(crate) fn record(&mut self, visit: Visit) {
+        self.visited += 1;
+
+        match visit {
+            Visit::Tested(count) => self.tested += count,
+            Visit::Accepted => self.accepted += 1,
+            Visit::Rejected => self.rejected += 1,
+        }
+    }
+}
+
+impl Display for QueryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "visited {} cells or nodes, {} accepted and {} rejected as a whole, tested {} positions, returned {}", self.visited, self.accepted, self.rejected, self.tested, self.returned)
+    }
+}
+
+/// the index in [`GridStats::occupancy`] of a cell with this count of elements
+pub(crate) fn bucket(load: usize) -> usize {
+    (usize::BITS - load.leading_zeros()) as usize
+}
+
+/// the loads counted by the bucket, written like "4..7"
+fn loads(bucket: usize) -> String {
+    if bucket == 0 {
+        return "0".to_string();
+    }
+
+    let first = 1usize << (bucket - 1);
+    let last = first + (first - 1);
+
+    if first == last {
+        return first.to_string();
+    }
+
+    format!("{}..{}", first, last)
+}
@@ -0,0 +1,152 @@
+//! [proptest] strategies for bounds, positions, queries and whole workloads
+//!
+//! Every value shrinks towards a smaller and simpler one, e.g. positions towards the start of the
+//! bounds and workloads towards fewer operations. So a failing case ends up as a short list of
+//! operations, which [check()] prints next to the first answer which differed
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use spatial_neighbors::brute_force::BruteForce;
+//! use spatial_neighbors::grid::Grid;
+//! use spatial_neighbors::strategy::{check, workload};
+//!
+//! proptest! {
+//!     fn grid_matches_brute_force(workload in workload(0..100), cell_count in (1..20usize, 1..20usize)) {
+//!         let mut grid = Grid::with_cell_count(workload.x.clone(), workload.y.clone(), cell_count);
+//!
+//!         check(&mut grid, &mut BruteForce::new(), &workload.operations)?;
+//!     }
+//! }
+//! # grid_matches_brute_force();
+//! ```
+
+use std::ops::Range;
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use crate::SpatialIndexMut;
+use crate::testing::{compare, Operation, Query};
+
+/// Bounds and operations whose positions all lie inside of the bounds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workload {
+    pub x: Range<f64>,
+    pub y: Range<f64>,
+    pub operations: Vec<Operation>,
+}
+
+///
+/// # Arguments
+///
+/// returns: impl Strategy<Value=(Range<f64>, Range<f64>)> (x, y) with a start in -1000..1000 and an extent in 0.001..1000
+///
+pub fn bounds() -> impl Strategy<Value = (Range<f64>, Range<f64>)> {
+    (-1000.0..1000.0, 0.001..1000.0, -1000.0..1000.0, 0.001..1000.0)
+        .prop_map(|(x, width, y, height): (f64, f64, f64, f64)| (x..x + width, y..y + height))
+}
+
+/// a coordinate inside of the range, which is often the start or lies right below the end
+fn coordinate(range: Range<f64>) -> impl Strategy<Value = f64> {
+    let last = range.end.next_down();
+
+    prop_oneof![
+        3 => (0.0..1.0).prop_map(move |t: f64| (range.start + t * (range.end - range.start)).min(last)),
+        1 => Just(range.start),
+        1 => Just(last),
+    ]
+}
+
+///
+/// # Arguments
+///
+/// * `x`: min_x..max_x every position lies in
+/// * `y`: min_y..max_y every position lies in
+///
+/// returns: impl Strategy<Value=(f64, f64)> positions which can be inserted into a structure with these bounds
+///
+pub fn position(x: Range<f64>, y: Range<f64>) -> impl Strategy<Value = (f64, f64)> {
+    (coordinate(x), coordinate(y))
+}
+
+///
+/// # Arguments
+///
+/// * `x`: min_x..max_x the queries are centered in
+/// * `y`: min_y..max_y the queries are centered in
+///
+/// returns: impl Strategy<Value=Query> circles, rings and rects from empty ones up to ones which cover the whole bounds
+///
+pub fn query(x: Range<f64>, y: Range<f64>) -> impl Strategy<Value = Query> {
+    let extent = (x.end - x.start).max(y.end - y.start);
+
+    prop_oneof![
+        (position(x.clone(), y.clone()), 0.0..extent).prop_map(|(position, radius)| Query::Circle(position, radius)),
+        (position(x.clone(), y.clone()), 0.0..1.0, 0.0..extent).prop_map(|(position, inner, outer): (_, f64, f64)| {
+            Query::Ring(position, inner * outer, outer)
+        }),
+        (position(x.clone(), y.clone()), position(x, y)).prop_map(|(a, b)| {
+            Query::Rect(a.0.min(b.0)..a.0.max(b.0), a.1.min(b.1)..a.1.max(b.1))
+        }),
+    ]
+}
+
+///
+/// # Arguments
+///
+/// * `x`: min_x..max_x every position lies in
+/// * `y`: min_y..max_y every position lies in
+///
+/// returns: impl Strategy<Value=Operation> mostly inserts and queries, also removes, moves and rarely a clear
+///
+pub fn operation(x: Range<f64>, y: Range<f64>) -> impl Strategy<Value = Operation> {
+    prop_oneof![
+        8 => position(x.clone(), y.clone()).prop_map(Operation::Insert),
+        2 => any::<usize>().prop_map(Operation::Remove),
+        3 => (any::<usize>(), position(x.clone(), y.clone())).prop_map(|(index, position)| Operation::UpdatePosition(index, position)),
+        6 => query(x, y).prop_map(Operation::Query),
+        1 => Just(Operation::Clear),
+    ]
+}
+
+///
+/// # Arguments
+///
+/// * `x`: min_x..max_x every position lies in
+/// * `y`: min_y..max_y every position lies in
+/// * `len`: range of the count of operations
+///
+/// returns: impl Strategy<Value=Vec<Operation>>
+///
+pub fn operations(x: Range<f64>, y: Range<f64>, len: Range<usize>) -> impl Strategy<Value = Vec<Operation>> {
+    prop::collection::vec(operation(x, y), len)
+}
+
+/// random bounds with operations inside of them
+///
+/// # Arguments
+///
+/// * `len`: range of the count of operations
+///
+/// returns: impl Strategy<Value=Workload>
+///
+pub fn workload(len: Range<usize>) -> impl Strategy<Value = Workload> {
+    bounds().prop_flat_map(move |(x, y)| {
+        operations(x.clone(), y.clone(), len.clone()).prop_map(move |operations| Workload { x: x.clone(), y: y.clone(), operations })
+    })
+}
+
+/// runs [compare()] and turns a mismatch into a failed test case, which lists the operations up to the mismatch
+///
+/// # Arguments
+///
+/// * `left`: the structure under test
+/// * `right`: the structure it is compared against e.g. a [BruteForce](crate::brute_force::BruteForce)
+/// * `operations`: every position has to lie inside the bounds of both structures
+///
+/// returns: Result<(), TestCaseError>
+///
+pub fn check<Left, Right>(left: &mut Left, right: &mut Right, operations: &[Operation]) -> Result<(), TestCaseError>
+    where Left: SpatialIndexMut<usize>, Right: SpatialIndexMut<usize> {
+    compare(left, right, operations).map_err(|mismatch| {
+        TestCaseError::fail(format!("{}\noperations up to the mismatch: {:#?}", mismatch, &operations[..=mismatch.step]))
+    })
+}
@@ -0,0 +1,240 @@
+//! Draws a [Grid] or a [QuadTree] as an SVG image, to see how a configuration divides the elements
+//!
+//! The cells of a Grid are filled the darker the more elements they hold, the nodes of a QuadTree
+//! are drawn as boxes with their elements as points. The `_with_circle` variants also draw a query
+//! circle and fill every cell or node the query reaches by what it did with it
+//!
+//! - blue: its elements were tested one by one
+//! - green: accepted as a whole, all of its elements lie in the circle
+//! - grey: rejected as a whole, none of its elements can lie in the circle
+//!
+//! ```
+//! use spatial_neighbors::SpatialIndexMut;
+//! use spatial_neighbors::grid::Grid;
+//! use spatial_neighbors::svg;
+//!
+//! let mut grid = Grid::with_cell_count(0.0..10.0, 0.0..10.0, (5, 5));
+//!
+//! for i in 0..100 {
+//!     grid.insert(((i % 10) as f64 + 0.5, (i / 10) as f64 + 0.5), i);
+//! }
+//!
+//! let image = svg::grid_with_circle(&grid, (5.0, 5.0), 3.0);
+//!
+//! assert!(image.starts_with("<svg"));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Range;
+use crate::grid::Grid;
+use crate::quad_tree::{QuadTree, QuadTreeNode};
+use crate::shape::Circle;
+use crate::util::Visit;
+
+/// the length of the longer side of the image
+const SIZE: f64 = 800.0;
+
+const STYLE: &str = ".cell{fill:none;stroke:#9e9e9e;stroke-width:0.5}\
+    .load{fill:#e4572e}\
+    .tested{fill:#1e88e5;fill-opacity:0.35}\
+    .accepted{fill:#43a047;fill-opacity:0.45}\
+    .rejected{fill:#9e9e9e;fill-opacity:0.45}\
+    .point{fill:#212121}\
+    .query{fill:none;stroke:#000000;stroke-width:1.5;stroke-dasharray:6 3}";
+
+///
+/// # Arguments
+///
+/// * `grid`: the grid which gets drawn
+///
+/// returns: String an SVG image of the cells, the more elements a cell holds the darker it is filled
+///
+pub fn grid<Data: Copy>(grid: &Grid<Data>) -> String {
+    draw_grid(grid, None)
+}
+
+///
+/// # Arguments
+///
+/// * `grid`: the grid which gets drawn
+/// * `position`: center of the query circle
+/// * `radius`: radius of the query circle
+///
+/// returns: String an SVG image of the cells with the query circle and the cells it reached
+///
+pub fn grid_with_circle<Data: Copy>(grid: &Grid<Data>, position: (f64, f64), radius: f64) -> String {
+    draw_grid(grid, Some((position, radius)))
+}
+
+///
+/// # Arguments
+///
+/// * `quad_tree`: the QuadTree which gets drawn
+///
+/// returns: String an SVG image of the boxes of all nodes and the elements as points
+///
+pub fn quad_tree<Data: Copy>(quad_tree: &QuadTree<Data>) -> String {
+    draw_quad_tree(quad_tree, None)
+}
+
+///
+/// # Arguments
+///
+/// * `quad_tree`: the QuadTree which gets drawn
+/// * `position`: center of the query circle
+/// * `radius`: radius of the query circle
+///
+/// returns: String an SVG image of the boxes of all nodes and the elements with the query circle and the nodes it reached
+///
+pub fn quad_tree_with_circle<Data: Copy>(quad_tree: &QuadTree<Data>, position: (f64, f64), radius: f64) -> String {
+    draw_quad_tree(quad_tree, Some((position, radius)))
+}
+
+fn draw_grid<Data: Copy>(grid: &Grid<Data>, query: Option<((f64, f64), f64)>) -> String {
+    let (x, y) = grid.bounds();
+    let (axis_x, axis_y) = grid.axes();
+    let cell_count = grid.cell_count();
+
+    let mut canvas = Canvas::new(x.clone(), y.clone());
+
+    let cell_box = |index: usize| {
+        let (i, j) = (index % cell_count.0, index / cell_count.0);
+
+        ((axis_x.edge(i), axis_y.edge(j)), (axis_x.edge(i + 1), axis_y.edge(j + 1)))
+    };
+
+    let loads: Vec<usize> = (0..cell_count.0 * cell_count.1).map(|index| grid.cell((index % cell_count.0, index / cell_count.0)).len()).collect();
+    let max_load = loads.iter().copied().max().unwrap_or(0);
+
+    for (index, load) in loads.iter().enumerate().filter(|(_, load)| **load > 0) {
+        let (min, max) = cell_box(index);
+
+        canvas.rect(min, max, "load", &format!(" fill-opacity=\"{:.2}\"", *load as f64 / max_load as f64 * 0.8 + 0.1));
+    }
+
+    if let Some((position, radius)) = query {
+        grid.trace(&Circle::new(position, radius), |index, visit| {
+            let (min, max) = cell_box(index);
+
+            canvas.rect(min, max, class(visit), "");
+        });
+    }
+
+    for i in 0..=cell_count.0 {
+        canvas.line((axis_x.edge(i), y.start), (axis_x.edge(i), y.end));
+    }
+
+    for j in 0..=cell_count.1 {
+        canvas.line((x.start, axis_y.edge(j)), (x.end, axis_y.edge(j)));
+    }
+
+    if let Some((position, radius)) = query {
+        canvas.circle(position, radius);
+    }
+
+    canvas.finish()
+}
+
+fn draw_quad_tree<Data: Copy>(quad_tree: &QuadTree<Data>, query: Option<((f64, f64), f64)>) -> String {
+    let (x, y) = quad_tree.bounds();
+
+    let mut canvas = Canvas::new(x, y);
+    let mut visits = HashMap::new();
+
+    if let Some((position, radius)) = query {
+        quad_tree.trace(&Circle::new(position, radius), |index, visit| {
+            visits.insert(index, visit);
+        });
+    }
+
+    draw_node(&mut canvas, quad_tree.node(), &visits);
+
+    if let Some((position, radius)) = query {
+        canvas.circle(position, radius);
+    }
+
+    canvas.finish()
+}
+
+/// draws the node and all of its children, a filled box for a node the query reached
+fn draw_node<Data: Copy>(canvas: &mut Canvas, node: QuadTreeNode<Data>, visits: &HashMap<u32, Visit>) {
+    let (center, size) = (node.center(), node.size());
+    let (min, max) = ((center.0 - size.0, center.1 - size.1), (center.0 + size.0, center.1 + size.1));
+
+    if let Some(visit) = visits.get(&node.index()) {
+        canvas.rect(min, max, class(*visit), "");
+    }
+
+    canvas.rect(min, max, "cell", "");
+
+    for element in node.data() {
+        canvas.point(element.0);
+    }
+
+    for child in node.nodes().into_iter().flatten() {
+        draw_node(canvas, child, visits);
+    }
+}
+
+fn class(visit: Visit) -> &'static str {
+    match visit {
+        Visit::Tested(_) => "tested",
+        Visit::Accepted => "accepted",
+        Visit::Rejected => "rejected",
+    }
+}
+
+/// writes shapes given in the coordinates of the structure, y points up like in the structure
+struct Canvas {
+    svg: String,
+    x: Range<f64>,
+    y: Range<f64>,
+    scale: f64,
+}
+
+impl Canvas {
+    fn new(x: Range<f64>, y: Range<f64>) -> Canvas {
+        let scale = SIZE / (x.end - x.start).max(y.end - y.start);
+        let (width, height) = ((x.end - x.start) * scale, (y.end - y.start) * scale);
+
+        let mut svg = String::new();
+        let _ = write!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" viewBox=\"0 0 {width:.2} {height:.2}\">\n<style>{STYLE}</style>\n");
+
+        Canvas { svg, x, y, scale }
+    }
+
+    fn to_image(&self, position: (f64, f64)) -> (f64, f64) {
+        ((position.0 - self.x.start) * self.scale, (self.y.end - position.1) * self.scale)
+    }
+
+    fn rect(&mut self, min: (f64, f64), max: (f64, f64), class: &str, attributes: &str) {
+        let corner = self.to_image((min.0, max.1));
+        let size = ((max.0 - min.0) * self.scale, (max.1 - min.1) * self.scale);
+
+        let _ = writeln!(self.svg, "<rect class=\"{class}\" x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\"{attributes}/>", corner.0, corner.1, size.0, size.1);
+    }
+
+    fn line(&mut self, from: (f64, f64), to: (f64, f64)) {
+        let (from, to) = (self.to_image(from), self.to_image(to));
+
+        let _ = writeln!(self.svg, "<line class=\"cell\" x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>", from.0, from.1, to.0, to.1);
+    }
+
+    fn point(&mut self, position: (f64, f64)) {
+        let center = self.to_image(position);
+
+        let _ = writeln!(self.svg, "<circle class=\"point\" cx=\"{:.2}\" cy=\"{:.2}\" r=\"2\"/>", center.0, center.1);
+    }
+
+    fn circle(&mut self, position: (f64, f64), radius: f64) {
+        let center = self.to_image(position);
+
+        let _ = writeln!(self.svg, "<circle class=\"query\" cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\"/>", center.0, center.1, radius.max(0.0) * self.scale);
+    }
+
+    fn finish(mut self) -> String {
+        self.svg.push_str("</svg>\n");
+        self.svg
+    }
+}
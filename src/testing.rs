@@ -0,0 +1,249 @@
+//! Differential testing, runs the same operations against two structures and compares every answer
+//!
+//! Comparing a structure against [BruteForce](crate::brute_force::BruteForce) checks the ids of the
+//! results and not only their count, so it also catches two structures which are wrong the same way
+//!
+//! ```
+//! use spatial_neighbors::brute_force::BruteForce;
+//! use spatial_neighbors::quad_tree::QuadTree;
+//! use spatial_neighbors::testing::{compare, workload};
+//!
+//! for seed in 0..4 {
+//!     let operations = workload(seed, -10.0..10.0, -10.0..10.0, 500);
+//!
+//!     let mut quad_tree = QuadTree::with_capacity(-10.0..10.0, -10.0..10.0, 4);
+//!     let mut brute_force = BruteForce::new();
+//!
+//!     compare(&mut quad_tree, &mut brute_force, &operations).unwrap();
+//! }
+//! ```
+
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Range;
+use crate::{SpatialIndex, SpatialIndexMut};
+use crate::handle::Handle;
+use crate::shape::{Circle, Rect, Ring, Shape};
+
+/// One step of a workload, the elements get the index of their `Insert` in the workload as id
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Operation {
+    /// inserts the next element at the position
+    Insert((f64, f64)),
+    /// removes the element at `index % count` of the elements which are still stored
+    Remove(usize),
+    /// moves the element at `index % count` of the elements which are still stored
+    UpdatePosition(usize, (f64, f64)),
+    /// compares the results of [query](crate::SpatialIndex::query), [query_count](crate::SpatialIndex::query_count)
+    /// and [query_any](crate::SpatialIndex::query_any)
+    Query(Query),
+    /// removes all elements
+    Clear,
+}
+
+/// The shape of a [`Operation::Query`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Query {
+    /// (position, radius)
+    Circle((f64, f64), f64),
+    /// (position, inner radius, outer radius)
+    Ring((f64, f64), f64, f64),
+    /// (x, y)
+    Rect(Range<f64>, Range<f64>),
+}
+
+/// The first answer two structures disagreed on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// index of the operation in the workload
+    pub step: usize,
+    pub operation: Operation,
+    /// what was compared e.g. "query" or "count"
+    pub check: &'static str,
+    /// the answer of the first structure
+    pub left: String,
+    /// the answer of the second structure
+    pub right: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} differs after step {} {:?}: left {} right {}", self.check, self.step, self.operation, self.left, self.right)
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// runs the operations against both structures and compares every answer
+///
+/// after each step the counts get compared, after each insert or move the stored element
+///
+/// # Arguments
+///
+/// * `left`: the structure under test
+/// * `right`: the structure it is compared against e.g. a [BruteForce](crate::brute_force::BruteForce)
+/// * `operations`: e.g. created by [workload()], every position has to lie inside the bounds of both structures
+///
+/// returns: Result<(), Mismatch> the first answer which differs
+///
+pub fn compare<Left, Right>(left: &mut Left, right: &mut Right, operations: &[Operation]) -> Result<(), Mismatch>
+    where Left: SpatialIndexMut<usize>, Right: SpatialIndexMut<usize> {
+    // (handle in left, handle in right) of each element which is still stored
+    let mut stored: Vec<(Handle, Handle)> = Vec::new();
+
+    for (step, operation) in operations.iter().enumerate() {
+        let check = |check: &'static str, a: &dyn Debug, b: &dyn Debug| {
+            let (a, b) = (format!("{:?}", a), format!("{:?}", b));
+
+            if a == b {
+                return Ok(());
+            }
+
+            Err(Mismatch { step, operation: operation.clone(), check, left: a, right: b })
+        };
+
+        match operation {
+            Operation::Insert(position) => {
+                let handles = (left.insert(*position, step), right.insert(*position, step));
+                stored.push(handles);
+
+                check("get", &left.get(handles.0), &right.get(handles.1))?;
+            }
+            Operation::Remove(index) => {
+                if stored.is_empty() {
+                    continue;
+                }
+
+                let handles = stored.swap_remove(index % stored.len());
+
+                check("remove", &left.remove(handles.0), &right.remove(handles.1))?;
+            }
+            Operation::UpdatePosition(index, position) => {
+                if stored.is_empty() {
+                    continue;
+                }
+
+                let handles = stored[index % stored.len()];
+
+                check("update_position", &left.update_position(handles.0, *position), &right.update_position(handles.1, *position))?;
+                check("get", &left.get(handles.0), &right.get(handles.1))?;
+            }
+            Operation::Query(query) => {
+                let (a, b) = match query {
+                    Query::Circle(position, radius) => {
+                        let shape = Circle::new(*position, *radius);
+                        (answer(left, &shape), answer(right, &shape))
+                    }
+                    Query::Ring(position, inner, outer) => {
+                        let shape = Ring::new(*position, *inner, *outer);
+                        (answer(left, &shape), answer(right, &shape))
+                    }
+                    Query::Rect(x, y) => {
+                        let shape = Rect::new(x.clone(), y.clone());
+                        (answer(left, &shape), answer(right, &shape))
+                    }
+                };
+
+                check("query", &a.0, &b.0)?;
+                check("query_count", &a.1, &b.1)?;
+                check("query_any", &a.2, &b.2)?;
+            }
+            Operation::Clear => {
+                left.clear();
+                right.clear();
+                stored.clear();
+            }
+        }
+
+        check("count", &left.count(), &right.count())?;
+    }
+
+    Ok(())
+}
+
+/// (sorted ids, count, any) of the shape
+fn answer<S: Shape>(index: &impl SpatialIndex<usize>, shape: &S) -> (Vec<usize>, usize, bool) {
+    let mut ids = index.query(shape);
+    ids.sort();
+
+    (ids, index.query_count(shape), index.query_any(shape))
+}
+
+/// creates a random workload of inserts, removes, moves and queries, the same seed always creates
+/// the same workload
+///
+/// some positions are inserted many times and some lie right on the bounds, which is where
+/// structures tend to break
+///
+/// # Arguments
+///
+/// * `seed`: seed of the random numbers
+/// * `x`: min_x..max_x every position lies in
+/// * `y`: min_y..max_y every position lies in
+/// * `len`: count of operations
+///
+/// returns: Vec<Operation>
+///
+pub fn workload(seed: u64, x: Range<f64>, y: Range<f64>, len: usize) -> Vec<Operation> {
+    let mut random = Random(seed);
+    let mut positions: Vec<(f64, f64)> = Vec::new();
+
+    let extent = (x.end - x.start).max(y.end - y.start);
+
+    (0..len).map(|_| {
+        let position = match random.below(10) {
+            0 if !positions.is_empty() => positions[random.below(positions.len() as u64) as usize],
+            1 => (x.start, y.end.next_down()),
+            _ => (random.inside(&x), random.inside(&y)),
+        };
+
+        positions.push(position);
+
+        match random.below(200) {
+            0 => Operation::Clear,
+            1..=80 => Operation::Insert(position),
+            81..=100 => Operation::Remove(random.next() as usize),
+            101..=130 => Operation::UpdatePosition(random.next() as usize, position),
+            131..=160 => Operation::Query(Query::Circle(position, random.unit() * extent / 2.0)),
+            161..=175 => {
+                let outer = random.unit() * extent / 2.0;
+
+                Operation::Query(Query::Ring(position, random.unit() * outer, outer))
+            }
+            _ => {
+                let end = (random.inside(&x), random.inside(&y));
+
+                Operation::Query(Query::Rect(position.0.min(end.0)..position.0.max(end.0), position.1.min(end.1)..position.1.max(end.1)))
+            }
+        }
+    }).collect()
+}
+
+/// SplitMix64, good enough to spread positions and small enough to need no dependency
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, max: u64) -> u64 {
+        self.next() % max
+    }
+
+    /// in 0.0..1.0
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn inside(&mut self, range: &Range<f64>) -> f64 {
+        (range.start + self.unit() * (range.end - range.start)).min(range.end.next_down())
+    }
+}
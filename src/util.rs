@@ -1,3 +1,155 @@
-pub(crate) fn in_range(pos1: (f64, f64), pos2: (f64, f64), radius: f64) -> bool {
-    (pos1.0 - pos2.0).powi(2) + (pos1.1 - pos2.1).powi(2) <= radius.powi(2)
-}
\ No newline at end of file
+use std::ops::ControlFlow;
+
+/// the absolute offset (|dx|, |dy|) between the two positions
+pub(crate) fn offset(pos1: (f64, f64), pos2: (f64, f64)) -> (f64, f64) {
+    ((pos1.0 - pos2.0).abs(), (pos1.1 - pos2.1).abs())
+}
+
+/// the position inside the box which is the closest to `position`
+pub(crate) fn nearest_in_box(position: (f64, f64), min: (f64, f64), max: (f64, f64)) -> (f64, f64) {
+    (position.0.max(min.0).min(max.0), position.1.max(min.1).min(max.1))
+}
+
+/// the corner of the box which is the farthest away from `position`
+pub(crate) fn farthest_in_box(position: (f64, f64), min: (f64, f64), max: (f64, f64)) -> (f64, f64) {
+    let x = if position.0 - min.0 > max.0 - position.0 { min.0 } else { max.0 };
+    let y = if position.1 - min.1 > max.1 - position.1 { min.1 } else { max.1 };
+
+    (x, y)
+}
+
+/// The cells of a grid along one axis, cell `i` covers `edge(i)..edge(i + 1)`
+///
+/// positions are sorted into cells and cells are tested against shapes with the same edges, so
+/// rounding can never move a position outside of the box of its cell
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CellAxis {
+    pub(crate) start: f64,
+    pub(crate) end: f64,
+    pub(crate) cell_size: f64,
+    pub(crate) count: usize,
+}
+
+impl CellAxis {
+    /// the start of the cell, the end of the last cell is exactly the end of the axis
+    pub(crate) fn edge(self, index: usize) -> f64 {
+        if index >= self.count {
+            return self.end;
+        }
+
+        self.cell_size * index as f64 + self.start
+    }
+
+    /// the cell which contains the value, values outside of the axis are moved to the closest cell
+    /// and NaN ends up in the first cell
+    pub(crate) fn index(self, value: f64) -> usize {
+        let mut index = (((value - self.start) / self.cell_size).floor().max(0.0) as usize).min(self.count - 1);
+
+        // the division can round to a neighbour of the cell whose edges contain the value
+        while index > 0 && value < self.edge(index) {
+            index -= 1;
+        }
+
+        while index + 1 < self.count && value >= self.edge(index + 1) {
+            index += 1;
+        }
+
+        index
+    }
+}
+
+/// the sides of the bounds a quad tree node lies on as bits
+pub(crate) const LEFT: u8 = 0b0001;
+pub(crate) const RIGHT: u8 = 0b0010;
+pub(crate) const BOTTOM: u8 = 0b0100;
+pub(crate) const TOP: u8 = 0b1000;
+
+/// the root lies on all sides of the bounds
+pub(crate) const BORDER: u8 = LEFT | RIGHT | BOTTOM | TOP;
+
+/// the sides of the bounds the child at index `child` of a node on `sides` lies on, the children
+/// are ordered (-x, -y), (-x, +y), (+x, -y), (+x, +y)
+pub(crate) fn child_sides(sides: u8, child: usize) -> u8 {
+    let x = if child < 2 { LEFT } else { RIGHT };
+    let y = if child.is_multiple_of(2) { BOTTOM } else { TOP };
+
+    sides & (x | y)
+}
+
+/// the box with each of its `sides` moved to the same side of `bounds`
+pub(crate) fn border_box(node: ((f64, f64), (f64, f64)), sides: u8, bounds: ((f64, f64), (f64, f64))) -> ((f64, f64), (f64, f64)) {
+    let ((mut min, mut max), (bounds_min, bounds_max)) = (node, bounds);
+
+    if sides & LEFT != 0 {
+        min.0 = bounds_min.0;
+    }
+
+    if sides & RIGHT != 0 {
+        max.0 = bounds_max.0;
+    }
+
+    if sides & BOTTOM != 0 {
+        min.1 = bounds_min.1;
+    }
+
+    if sides & TOP != 0 {
+        max.1 = bounds_max.1;
+    }
+
+    (min, max)
+}
+
+/// what a search did with a cell or node it reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Visit {
+    /// this many elements were tested one by one
+    Tested(usize),
+    /// the box lies inside the shape, all elements were taken without testing them
+    Accepted,
+    /// the box lies outside the shape, no element was tested
+    Rejected,
+}
+
+/// whether the cell lies on the edge of a grid, which is where positions outside of the grid are stored
+pub(crate) fn is_edge_cell(cell: (usize, usize), cell_count: (usize, usize)) -> bool {
+    cell.0 == 0 || cell.1 == 0 || cell.0 + 1 == cell_count.0 || cell.1 + 1 == cell_count.1
+}
+
+/// how many positions get tested at once, enough for 4 or 8 f64 vector lanes
+pub(crate) const LANES: usize = 8;
+
+/// calls `visit` with the index of every position (`xs[i]`, `ys[i]`) for which `test` is true in ascending order
+///
+/// the positions are tested `LANES` at a time into a mask without branching so the compiler can
+/// vectorise the test, the remaining positions are tested one by one. Both call the same `test`
+/// so the result is exactly the same as testing every position on its own
+pub(crate) fn filter_lanes(xs: &[f64], ys: &[f64], test: impl Fn((f64, f64)) -> bool, visit: &mut impl FnMut(usize) -> ControlFlow<()>) -> ControlFlow<()> {
+    let x_chunks = xs.chunks_exact(LANES);
+    let y_chunks = ys.chunks_exact(LANES);
+
+    let tail = (x_chunks.remainder(), y_chunks.remainder());
+    let mut start = 0;
+
+    for (x, y) in x_chunks.zip(y_chunks) {
+        let mut mask = 0u32;
+
+        for (i, (x, y)) in x.iter().zip(y).enumerate() {
+            mask |= (test((*x, *y)) as u32) << i;
+        }
+
+        while mask != 0 {
+            visit(start + mask.trailing_zeros() as usize)?;
+            mask &= mask - 1;
+        }
+
+        start += LANES;
+    }
+
+    for (i, (x, y)) in tail.0.iter().zip(tail.1).enumerate() {
+        if test((*x, *y)) {
+            visit(start + i)?;
+        }
+    }
+
+    ControlFlow::Continue(())
+}
@@ -0,0 +1,107 @@
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::config::ConfigError;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::quad_tree::{QuadTree, QuadTreeNode};
+use spatial_neighbors::shape::Circle;
+
+fn depth(node: QuadTreeNode<u32>) -> u32 {
+    match node.nodes() {
+        Some(children) => 1 + children.into_iter().map(depth).max().unwrap(),
+        None => 0,
+    }
+}
+
+#[test]
+fn grid_builder() {
+    let grid: Grid<u32> = Grid::builder().bounds(-50.0..50.0, 0.0..30.0).build().unwrap();
+    assert_eq!(grid.cell_count(), (100, 100));
+    assert_eq!(grid.bounds(), (-50.0..50.0, 0.0..30.0));
+
+    let grid: Grid<u32> = Grid::builder().bounds(0.0..100.0, 0.0..25.0).cell_size((10.0, 10.0)).build().unwrap();
+    assert_eq!(grid.cell_count(), (10, 3));
+
+    // the later setting replaces the earlier one
+    let grid: Grid<u32> = Grid::builder().cell_size((10.0, 10.0)).cell_count((4, 2)).bounds(0.0..100.0, 0.0..25.0).build().unwrap();
+    assert_eq!(grid.cell_count(), (4, 2));
+
+    let grid: Grid<u32> = Grid::builder().bounds(0.0..1.0, 0.0..1.0).cell_size((5.0, 5.0)).build().unwrap();
+    assert_eq!(grid.cell_count(), (1, 1));
+}
+
+#[test]
+fn grid_builder_errors() {
+    assert_eq!(Grid::<u32>::builder().build().unwrap_err(), ConfigError::MissingBounds);
+
+    for (x, y) in [(0.0..0.0, 0.0..1.0), (1.0..0.0, 0.0..1.0), (0.0..1.0, f64::NAN..1.0), (0.0..f64::INFINITY, 0.0..1.0), (f64::MIN..f64::MAX, 0.0..1.0)] {
+        let error = Grid::<u32>::builder().bounds(x.clone(), y.clone()).build().unwrap_err();
+
+        assert!(matches!(error, ConfigError::InvalidRange { .. }), "{:?} {:?}", x, y);
+    }
+
+    assert_eq!(Grid::<u32>::builder().bounds(0.0..1.0, 0.0..1.0).cell_count((0, 4)).build().unwrap_err(), ConfigError::ZeroCellCount((0, 4)));
+
+    for cell_size in [(0.0, 1.0), (1.0, -1.0), (f64::NAN, 1.0), (1.0, f64::INFINITY), (1e-300, 1e-300)] {
+        let error = Grid::<u32>::builder().bounds(0.0..1.0, 0.0..1.0).cell_size(cell_size).build().unwrap_err();
+
+        assert!(matches!(error, ConfigError::InvalidCellSize(_)), "{:?}", cell_size);
+    }
+}
+
+#[test]
+fn quad_tree_builder() {
+    let quad_tree: QuadTree<u32> = QuadTree::builder().bounds(0.0..10.0, 0.0..10.0).build().unwrap();
+    assert_eq!(quad_tree.capacity(), 50);
+    assert_eq!(quad_tree.max_depth(), u32::MAX);
+
+    assert_eq!(QuadTree::<u32>::builder().build().unwrap_err(), ConfigError::MissingBounds);
+    assert_eq!(QuadTree::<u32>::builder().bounds(0.0..10.0, 0.0..10.0).capacity(0).build().unwrap_err(), ConfigError::ZeroCapacity);
+    assert!(matches!(QuadTree::<u32>::builder().bounds(0.0..10.0, 5.0..-5.0).build().unwrap_err(), ConfigError::InvalidRange { .. }));
+}
+
+#[test]
+fn quad_tree_max_depth() {
+    let mut quad_tree = QuadTree::builder().bounds(0.0..10.0, 0.0..10.0).capacity(2).max_depth(3).build().unwrap();
+
+    // elements at the same position would split the nodes forever without a max_depth
+    let mut handles: Vec<_> = (0..200).map(|i| quad_tree.insert((2.5, 2.5), i)).collect();
+    handles.extend((200..300).map(|i| quad_tree.insert((i as f64 / 40.0, 7.5), i)));
+
+    assert_eq!(depth(quad_tree.node()), 3);
+    assert_eq!(quad_tree.count(), 300);
+    assert_eq!(quad_tree.query_count(&Circle::new((2.5, 2.5), 0.1)), 200);
+
+    for (i, handle) in handles.iter().enumerate() {
+        assert_eq!(quad_tree.get(*handle).map(|element| element.1), Some(i as u32));
+    }
+
+    for handle in handles.iter().step_by(2) {
+        assert!(quad_tree.update_position(*handle, (9.0, 1.0)));
+    }
+
+    for handle in handles.iter().skip(1).step_by(4) {
+        assert!(quad_tree.remove(*handle).is_some());
+    }
+
+    assert_eq!(quad_tree.count(), 225);
+    assert_eq!(quad_tree.query_count(&Circle::new((9.0, 1.0), 0.1)), 150);
+    assert_eq!(quad_tree.query_count(&Circle::new((2.5, 2.5), 0.1)), 50);
+
+    for (i, handle) in handles.iter().enumerate().filter(|(i, _)| i % 4 != 1) {
+        assert_eq!(quad_tree.get(*handle).map(|element| element.1), Some(i as u32));
+    }
+}
+
+#[test]
+fn quad_tree_without_max_depth_stops_splitting_at_f64_precision() {
+    let mut quad_tree = QuadTree::with_capacity(0.0625..48.0, -0.0625..662.0625, 1);
+    let position = (16.0625, 16.0625);
+
+    for i in 0..200 {
+        quad_tree.insert(position, i);
+    }
+
+    // the children of deeper nodes would be no smaller than their parent with f64 precision
+    assert!(depth(quad_tree.node()) < 64);
+    assert_eq!(quad_tree.query_count(&Circle::new((0.0, 16.0625), 16.0625)), 200);
+    assert_eq!(quad_tree.query_count(&Circle::new(position, 0.0)), 200);
+}
@@ -0,0 +1,184 @@
+use spatial_neighbors::bvh::Bvh;
+use spatial_neighbors::handle::Handle;
+use spatial_neighbors::shape::{Circle, Rect, Shape};
+
+type Bounds = ((f64, f64), (f64, f64));
+type Element = (Bounds, u32);
+
+fn elements() -> Vec<Element> {
+    (0..1_000u32).map(|i| {
+        let min = ((i * 7919 % 1000) as f64 / 10.0 - 50.0, (i * 104_729 % 997) as f64 / 10.0 - 50.0);
+        let size = ((i % 13) as f64 / 4.0, (i % 7) as f64 / 3.0);
+
+        ((min, (min.0 + size.0, min.1 + size.1)), i)
+    }).collect()
+}
+
+fn sorted(mut data: Vec<u32>) -> Vec<u32> {
+    data.sort();
+    data
+}
+
+fn intersects(a: Bounds, b: Bounds) -> bool {
+    a.0.0 <= b.1.0 && b.0.0 <= a.1.0 && a.0.1 <= b.1.1 && b.0.1 <= a.1.1
+}
+
+/// the distance at which the ray enters the box, clipped with Liang-Barsky
+fn ray_hit(origin: (f64, f64), direction: (f64, f64), max_distance: f64, bounds: Bounds) -> Option<f64> {
+    let mut range = (0.0f64, max_distance);
+
+    for (p, q) in [(-direction.0, origin.0 - bounds.0.0), (direction.0, bounds.1.0 - origin.0), (-direction.1, origin.1 - bounds.0.1), (direction.1, bounds.1.1 - origin.1)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            range.0 = range.0.max(q / p);
+        } else {
+            range.1 = range.1.min(q / p);
+        }
+    }
+
+    (range.0 <= range.1).then_some(range.0)
+}
+
+fn assert_queries(bvh: &Bvh<u32>, elements: &[Element]) {
+    assert_eq!(bvh.count(), elements.len());
+
+    // a balanced tree with 1000 leaves is far less than 30 high
+    assert!(bvh.height() <= 30, "height {}", bvh.height());
+
+    for location in [(0.0, 0.0), (-49.0, -49.0), (12.3, 45.6), (300.0, 300.0)] {
+        for size in [0.5, 4.0, 23.7, 200.0] {
+            let circle = Circle::new(location, size);
+            let rect = Rect::new(location.0 - size..location.0 + size / 2.0, location.1 - size / 3.0..location.1 + size);
+
+            let expected = |shape: &dyn Fn(Bounds) -> bool| {
+                sorted(elements.iter().filter(|element| shape(element.0)).map(|element| element.1).collect())
+            };
+
+            assert_eq!(sorted(bvh.query_intersecting(&circle)), expected(&|bounds| circle.intersects_box(bounds.0, bounds.1)));
+            assert_eq!(sorted(bvh.query_intersecting(&rect)), expected(&|bounds| rect.intersects_box(bounds.0, bounds.1)));
+            assert_eq!(bvh.count_intersecting(&rect), expected(&|bounds| rect.intersects_box(bounds.0, bounds.1)).len());
+            assert_eq!(bvh.any_intersecting(&circle), !expected(&|bounds| circle.intersects_box(bounds.0, bounds.1)).is_empty());
+        }
+    }
+
+    let mut expected = Vec::new();
+
+    for (i, a) in elements.iter().enumerate() {
+        for b in &elements[i + 1..] {
+            if intersects(a.0, b.0) {
+                expected.push((a.1.min(b.1), a.1.max(b.1)));
+            }
+        }
+    }
+
+    let mut pairs: Vec<_> = bvh.overlap_pairs().into_iter().map(|(a, b)| (a.min(b), a.max(b))).collect();
+
+    pairs.sort();
+    expected.sort();
+
+    assert_eq!(pairs, expected);
+}
+
+#[test]
+fn bvh_matches_brute_force() {
+    let mut elements = elements();
+
+    let mut bvh = Bvh::new(0.5);
+    let mut handles: Vec<Handle> = elements.iter().map(|((min, max), data)| bvh.insert(*min, *max, *data)).collect();
+
+    assert_queries(&bvh, &elements);
+
+    // small moves stay inside of the enlarged boxes
+    for (handle, element) in handles.iter().zip(&mut elements) {
+        let ((min, max), _) = element;
+
+        *min = (min.0 + 0.25, min.1 - 0.25);
+        *max = (max.0 + 0.25, max.1 - 0.25);
+
+        assert_eq!(bvh.move_box(*handle, *min, *max), Some(false));
+    }
+
+    assert_queries(&bvh, &elements);
+
+    for (i, (handle, element)) in handles.iter().zip(&mut elements).enumerate() {
+        let ((min, max), _) = element;
+        let offset = ((i % 17) as f64 * 3.0 - 24.0, (i % 11) as f64 * 5.0 - 25.0);
+
+        *min = (min.0 + offset.0, min.1 + offset.1);
+        *max = (max.0 + offset.0, max.1 + offset.1);
+
+        assert!(bvh.move_box(*handle, *min, *max).is_some());
+        assert_eq!(bvh.get(*handle), Some(*element));
+    }
+
+    assert_queries(&bvh, &elements);
+
+    let removed: Vec<_> = handles.iter().zip(&elements).filter(|(_, element)| element.1 % 3 != 0).map(|(handle, element)| (*handle, element.1)).collect();
+
+    for (handle, data) in &removed {
+        assert_eq!(bvh.remove(*handle), Some(*data));
+        assert_eq!(bvh.remove(*handle), None);
+        assert_eq!(bvh.get(*handle), None);
+        assert_eq!(bvh.move_box(*handle, (0.0, 0.0), (1.0, 1.0)), None);
+    }
+
+    handles.retain(|handle| !removed.iter().any(|(other, _)| other == handle));
+    elements.retain(|element| element.1 % 3 == 0);
+
+    assert_queries(&bvh, &elements);
+
+    // the freed nodes get reused without making the old handles valid again
+    let handle = bvh.insert((0.0, 0.0), (1.0, 1.0), 5_000);
+
+    assert!(removed.iter().all(|(other, _)| bvh.get(*other).is_none() && bvh.move_box(*other, (0.0, 0.0), (1.0, 1.0)).is_none()));
+    assert_eq!(bvh.get(handle), Some((((0.0, 0.0), (1.0, 1.0)), 5_000)));
+
+    bvh.clear();
+
+    assert_eq!(bvh.count(), 0);
+    assert_eq!(bvh.get(handle), None);
+    assert_eq!(bvh.move_box(handle, (0.0, 0.0), (1.0, 1.0)), None);
+    assert!(bvh.query_intersecting(&Rect::new(-500.0..500.0, -500.0..500.0)).is_empty());
+}
+
+#[test]
+fn rays() {
+    let elements = elements();
+
+    let mut bvh = Bvh::new(0.5);
+    elements.iter().for_each(|((min, max), data)| { bvh.insert(*min, *max, *data); });
+
+    for (origin, direction, max_distance) in [((-60.0, -60.0), (1.0, 1.0), 200.0), ((0.0, 0.0), (1.0, 0.0), 30.0), ((12.3, -70.0), (0.0, 2.0), 40.0), ((0.1, 0.1), (-3.0, 1.0), 10.0)] {
+        let hits: Vec<_> = elements.iter().filter_map(|element| ray_hit(origin, direction, max_distance, element.0).map(|distance| (element.1, distance))).collect();
+
+        assert!(!hits.is_empty());
+        assert_eq!(sorted(bvh.query_ray(origin, direction, max_distance)), sorted(hits.iter().map(|hit| hit.0).collect()));
+
+        let (data, distance) = bvh.ray_cast(origin, direction, max_distance).unwrap();
+
+        assert_eq!(hits.iter().map(|hit| hit.1).fold(f64::INFINITY, f64::min), distance);
+        assert_eq!(ray_hit(origin, direction, max_distance, elements[data as usize].0), Some(distance));
+    }
+
+    assert_eq!(bvh.ray_cast((-60.0, -60.0), (-1.0, 0.0), 100.0), None);
+}
+
+#[test]
+fn stays_balanced() {
+    let mut bvh = Bvh::new(0.0);
+
+    // boxes along a line are the worst case for a tree without rotations
+    let handles: Vec<_> = (0..1_024).map(|i| bvh.insert((i as f64, 0.0), (i as f64 + 0.5, 0.5), i)).collect();
+
+    assert!(bvh.height() <= 20, "height {}", bvh.height());
+
+    for handle in &handles[..1_000] {
+        bvh.remove(*handle);
+    }
+
+    assert!(bvh.height() <= 10, "height {}", bvh.height());
+    assert_eq!(sorted(bvh.query_intersecting(&Rect::new(0.0..2_000.0, 0.0..1.0))), (1_000..1_024).collect::<Vec<_>>());
+}
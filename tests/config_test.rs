@@ -0,0 +1,195 @@
+use std::ops::Range;
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::bvh::Bvh;
+use spatial_neighbors::config::{Axis, ConfigError};
+use spatial_neighbors::geo::GeoIndex;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::kd_tree::KdTree;
+use spatial_neighbors::linear_quad_tree::LinearQuadTree;
+use spatial_neighbors::metric::Weighted;
+use spatial_neighbors::static_grid::StaticGrid;
+use spatial_neighbors::quad_tree::QuadTree;
+
+fn invalid_range(axis: Axis, range: Range<f64>) -> ConfigError {
+    ConfigError::InvalidRange { axis, range }
+}
+
+/// (x, y, the expected error) for each way the bounds can be broken
+fn invalid_bounds() -> Vec<(Range<f64>, Range<f64>, ConfigError)> {
+    vec![
+        (3.0..3.0, 0.0..1.0, invalid_range(Axis::X, 3.0..3.0)),
+        (0.0..1.0, 5.0..-5.0, invalid_range(Axis::Y, 5.0..-5.0)),
+        (f64::NEG_INFINITY..1.0, 0.0..1.0, invalid_range(Axis::X, f64::NEG_INFINITY..1.0)),
+        (0.0..1.0, 0.0..f64::INFINITY, invalid_range(Axis::Y, 0.0..f64::INFINITY)),
+        (f64::MIN..f64::MAX, 0.0..1.0, invalid_range(Axis::X, f64::MIN..f64::MAX)),
+    ]
+}
+
+#[test]
+fn grid_rejects_invalid_bounds() {
+    for (x, y, error) in invalid_bounds() {
+        assert_eq!(Grid::<u32>::try_new(x.clone(), y.clone()).unwrap_err(), error);
+        assert_eq!(Grid::<u32>::try_with_cell_count(x, y, (10, 10)).unwrap_err(), error);
+    }
+
+    // NaN is not equal to itself, so the error can only be matched
+    let error = Grid::<u32>::try_with_cell_count(f64::NAN..1.0, 0.0..1.0, (10, 10)).unwrap_err();
+    assert!(matches!(error, ConfigError::InvalidRange { axis: Axis::X, .. }));
+}
+
+#[test]
+fn grid_rejects_invalid_cell_count() {
+    for cell_count in [(0, 10), (10, 0), (0, 0)] {
+        assert_eq!(Grid::<u32>::try_with_cell_count(0.0..1.0, 0.0..1.0, cell_count).unwrap_err(), ConfigError::ZeroCellCount(cell_count));
+    }
+
+    assert_eq!(Grid::<u32>::try_with_cell_count(0.0..1.0, 0.0..1.0, (usize::MAX, 2)).unwrap_err(), ConfigError::TooManyCells((usize::MAX, 2)));
+}
+
+#[test]
+fn grid_accepts_valid_settings() {
+    let mut grid = Grid::try_with_cell_count(-1.0..1.0, 0.0..0.5, (1, 1)).unwrap();
+    grid.insert((0.0, 0.25), 1);
+
+    assert_eq!(grid.cell_size(), (2.0, 0.5));
+    assert_eq!(grid.cell((0, 0)), &vec![((0.0, 0.25), 1)]);
+}
+
+#[test]
+fn quad_tree_rejects_invalid_settings() {
+    for (x, y, error) in invalid_bounds() {
+        assert_eq!(QuadTree::<u32>::try_new(x.clone(), y.clone()).unwrap_err(), error);
+        assert_eq!(QuadTree::<u32>::try_with_capacity(x, y, 4).unwrap_err(), error);
+    }
+
+    assert_eq!(QuadTree::<u32>::try_with_capacity(0.0..1.0, 0.0..1.0, 0).unwrap_err(), ConfigError::ZeroCapacity);
+    assert_eq!(QuadTree::<u32>::try_with_capacity(0.0..1.0, 0.0..1.0, 1).unwrap().capacity(), 1);
+}
+
+#[test]
+fn static_grid_rejects_invalid_settings() {
+    let elements = [((0.5, 0.5), 1u32)];
+
+    for (x, y, error) in invalid_bounds() {
+        assert_eq!(StaticGrid::try_from_elements(x, y, (10, 10), &elements).unwrap_err(), error);
+    }
+
+    assert_eq!(StaticGrid::try_from_elements(0.0..1.0, 0.0..1.0, (0, 4), &elements).unwrap_err(), ConfigError::ZeroCellCount((0, 4)));
+    assert_eq!(StaticGrid::try_from_elements(0.0..1.0, 0.0..1.0, (usize::MAX, 2), &elements).unwrap_err(), ConfigError::TooManyCells((usize::MAX, 2)));
+    assert_eq!(StaticGrid::try_from_elements(0.0..1.0, 0.0..1.0, (2, 2), &elements).unwrap().cell((1, 1)).2, &[1]);
+}
+
+#[test]
+fn linear_quad_tree_rejects_invalid_settings() {
+    let elements = [((0.5, 0.5), 1u32)];
+
+    for (x, y, error) in invalid_bounds() {
+        assert_eq!(LinearQuadTree::<u32>::try_new(x.clone(), y.clone()).unwrap_err(), error);
+        assert_eq!(LinearQuadTree::try_from_elements(x, y, &elements).unwrap_err(), error);
+    }
+
+    assert_eq!(LinearQuadTree::try_from_elements(0.0..1.0, 0.0..1.0, &elements).unwrap().count(), 1);
+}
+
+#[test]
+fn kd_tree_rejects_invalid_settings() {
+    let elements = [((0.5, 0.5), 1u32)];
+
+    for (x, y, error) in invalid_bounds() {
+        assert_eq!(KdTree::<u32>::try_new(x.clone(), y.clone()).unwrap_err(), error);
+        assert_eq!(KdTree::try_from_elements(x, y, &elements).unwrap_err(), error);
+    }
+
+    assert_eq!(KdTree::try_from_elements(0.0..1.0, 0.0..1.0, &elements).unwrap().count(), 1);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_from_elements_rejects_invalid_settings() {
+    let elements = [((0.5, 0.5), 1u32)];
+
+    for (x, y, error) in invalid_bounds() {
+        assert_eq!(Grid::try_par_from_elements(x.clone(), y.clone(), (10, 10), &elements).unwrap_err(), error);
+        assert_eq!(QuadTree::try_par_from_elements(x, y, 4, &elements).unwrap_err(), error);
+    }
+
+    assert_eq!(Grid::try_par_from_elements(0.0..1.0, 0.0..1.0, (0, 4), &elements).unwrap_err(), ConfigError::ZeroCellCount((0, 4)));
+    assert_eq!(QuadTree::try_par_from_elements(0.0..1.0, 0.0..1.0, 0, &elements).unwrap_err(), ConfigError::ZeroCapacity);
+    assert_eq!(Grid::try_par_from_elements(0.0..1.0, 0.0..1.0, (2, 2), &elements).unwrap().count(), 1);
+    assert_eq!(QuadTree::try_par_from_elements(0.0..1.0, 0.0..1.0, 1, &elements).unwrap().count(), 1);
+}
+
+#[test]
+fn geo_index_rejects_invalid_settings() {
+    assert_eq!(GeoIndex::<u32, Grid<u32>>::try_with_cell_count((360, 0)).err(), Some(ConfigError::ZeroCellCount((360, 0))));
+    assert_eq!(GeoIndex::<u32, QuadTree<u32>>::try_with_capacity(0).err(), Some(ConfigError::ZeroCapacity));
+
+    assert!(GeoIndex::<u32, Grid<u32>>::try_with_cell_count((360, 180)).is_ok());
+    assert!(GeoIndex::<u32, QuadTree<u32>>::try_with_capacity(8).is_ok());
+}
+
+#[test]
+fn bvh_rejects_invalid_margin() {
+    for margin in [-0.5, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(Bvh::<u32>::try_new(margin).unwrap_err(), ConfigError::InvalidMargin(margin));
+    }
+
+    assert!(matches!(Bvh::<u32>::try_new(f64::NAN).unwrap_err(), ConfigError::InvalidMargin(_)));
+    assert_eq!(Bvh::<u32>::try_new(0.0).unwrap().count(), 0);
+}
+
+#[test]
+fn weighted_rejects_invalid_weights() {
+    for weight in [(0.0, 1.0), (1.0, -2.0), (f64::INFINITY, 1.0), (1.0, f64::MAX * 2.0)] {
+        assert_eq!(Weighted::try_new(weight.0, weight.1).unwrap_err(), ConfigError::InvalidWeight(weight));
+    }
+
+    assert!(matches!(Weighted::try_new(f64::NAN, 1.0).unwrap_err(), ConfigError::InvalidWeight(_)));
+    assert_eq!(Weighted::try_new(4.0, 0.25).unwrap(), Weighted::new(4.0, 0.25));
+}
+
+#[test]
+fn error_messages() {
+    assert_eq!(invalid_range(Axis::X, 3.0..3.0).to_string(), "the x range 3.0..3.0 is empty");
+    assert_eq!(invalid_range(Axis::Y, 5.0..-5.0).to_string(), "the y range 5.0..-5.0 is reversed, its start has to lie below its end");
+    assert_eq!(invalid_range(Axis::X, 0.0..f64::INFINITY).to_string(), "the x range 0.0..inf is not finite");
+    assert_eq!(ConfigError::ZeroCellCount((10, 0)).to_string(), "the cell_count (10, 0) has no cells along y");
+    assert_eq!(ConfigError::ZeroCapacity.to_string(), "the capacity has to be at least 1");
+    assert_eq!(ConfigError::InvalidMargin(-0.5).to_string(), "the margin -0.5 is not a finite size of at least 0");
+}
+
+#[test]
+#[should_panic(expected = "tried to create a Grid from invalid settings: the cell_count (0, 10) has no cells along x")]
+fn grid_with_zero_cells_panics() {
+    Grid::<u32>::with_cell_count(0.0..1.0, 0.0..1.0, (0, 10));
+}
+
+#[test]
+#[should_panic(expected = "tried to create a QuadTree from invalid settings: the y range 1.0..0.0 is reversed")]
+fn quad_tree_with_reversed_range_panics() {
+    QuadTree::<u32>::with_capacity(0.0..1.0, 1.0..0.0, 4);
+}
+
+#[test]
+#[should_panic(expected = "tried to create a Weighted metric from invalid settings: the weights (1.0, 0.0) are not positive and finite")]
+fn weighted_with_zero_weight_panics() {
+    Weighted::new(1.0, 0.0);
+}
+
+#[test]
+#[should_panic(expected = "tried to create a StaticGrid from invalid settings: the cell_count (0, 4) has no cells along x")]
+fn static_grid_with_zero_cells_panics() {
+    StaticGrid::<u32>::from_elements(0.0..1.0, 0.0..1.0, (0, 4), &[]);
+}
+
+#[test]
+#[should_panic(expected = "tried to create a LinearQuadTree from invalid settings: the x range 3.0..3.0 is empty")]
+fn linear_quad_tree_with_empty_range_panics() {
+    LinearQuadTree::<u32>::new(3.0..3.0, 0.0..1.0);
+}
+
+#[test]
+#[should_panic(expected = "tried to create a KdTree from invalid settings: the y range 0.0..inf is not finite")]
+fn kd_tree_with_infinite_range_panics() {
+    KdTree::<u32>::from_elements(0.0..1.0, 0.0..f64::INFINITY, &[]);
+}
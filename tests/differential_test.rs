@@ -0,0 +1,73 @@
+#![cfg(feature = "testing")]
+
+use spatial_neighbors::SpatialIndexMut;
+use spatial_neighbors::brute_force::BruteForce;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::kd_tree::KdTree;
+use spatial_neighbors::linear_quad_tree::LinearQuadTree;
+use spatial_neighbors::quad_tree::QuadTree;
+use spatial_neighbors::testing::{compare, workload, Operation, Query};
+
+fn assert_matches_brute_force<T: SpatialIndexMut<usize>>(create: impl Fn() -> T) {
+    for seed in 0..20 {
+        let operations = workload(seed, -50.0..50.0, -20.0..30.0, 2_000);
+
+        if let Err(mismatch) = compare(&mut create(), &mut BruteForce::new(), &operations) {
+            panic!("seed {}: {}", seed, mismatch);
+        }
+    }
+}
+
+#[test]
+fn grid_matches_brute_force() {
+    assert_matches_brute_force(|| Grid::with_cell_count(-50.0..50.0, -20.0..30.0, (17, 9)));
+    assert_matches_brute_force(|| Grid::with_cell_count(-50.0..50.0, -20.0..30.0, (1, 1)));
+}
+
+#[test]
+fn quad_tree_matches_brute_force() {
+    assert_matches_brute_force(|| QuadTree::with_capacity(-50.0..50.0, -20.0..30.0, 4));
+    assert_matches_brute_force(|| QuadTree::builder().bounds(-50.0..50.0, -20.0..30.0).capacity(2).max_depth(3).build().unwrap());
+}
+
+#[test]
+fn linear_quad_tree_matches_brute_force() {
+    assert_matches_brute_force(|| LinearQuadTree::new(-50.0..50.0, -20.0..30.0));
+}
+
+#[test]
+fn kd_tree_matches_brute_force() {
+    assert_matches_brute_force(|| KdTree::new(-50.0..50.0, -20.0..30.0));
+}
+
+#[test]
+fn workload_is_reproducible() {
+    assert_eq!(workload(7, 0.0..1.0, 0.0..1.0, 500), workload(7, 0.0..1.0, 0.0..1.0, 500));
+    assert_ne!(workload(7, 0.0..1.0, 0.0..1.0, 500), workload(8, 0.0..1.0, 0.0..1.0, 500));
+
+    for operation in workload(3, 2.0..3.0, -1.0..0.0, 2_000) {
+        if let Operation::Insert(position) | Operation::UpdatePosition(_, position) = operation {
+            assert!((2.0..3.0).contains(&position.0) && (-1.0..0.0).contains(&position.1));
+        }
+    }
+}
+
+#[test]
+fn reports_the_first_mismatch() {
+    let mut left = BruteForce::new();
+    let mut right = BruteForce::new();
+
+    // an element the workload does not know about
+    left.insert((0.5, 0.5), 100);
+
+    let operations = [
+        Operation::Insert((0.25, 0.25)),
+        Operation::Query(Query::Circle((0.5, 0.5), 0.1)),
+    ];
+
+    let mismatch = compare(&mut left, &mut right, &operations).unwrap_err();
+
+    assert_eq!(mismatch.step, 0);
+    assert_eq!(mismatch.check, "count");
+    assert_eq!((mismatch.left.as_str(), mismatch.right.as_str()), ("2", "1"));
+}
@@ -0,0 +1,179 @@
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::frozen::{FrozenError, FrozenGrid, FrozenQuadTree, MAGIC};
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::quad_tree::QuadTree;
+use spatial_neighbors::shape::Circle;
+
+fn elements() -> Vec<((f64, f64), u32)> {
+    (0..3_000u32).map(|i| (((i * 7919 % 1000) as f64 / 10.0 - 50.0, (i * 104_729 % 997) as f64 / 10.0 - 50.0), i)).collect()
+}
+
+fn fill<P: SpatialIndexMut<u32>>(partitioner: &mut P) -> Vec<((f64, f64), u32)> {
+    let elements = elements();
+
+    for (position, data) in &elements {
+        partitioner.insert(*position, *data);
+    }
+
+    elements
+}
+
+fn brute_force_nearest(elements: &[((f64, f64), u32)], position: (f64, f64)) -> f64 {
+    elements.iter().map(|(element, _)| (element.0 - position.0).powi(2) + (element.1 - position.1).powi(2)).fold(f64::INFINITY, f64::min)
+}
+
+fn distance(elements: &[((f64, f64), u32)], data: u32, position: (f64, f64)) -> f64 {
+    let element = elements.iter().find(|element| element.1 == data).unwrap().0;
+
+    (element.0 - position.0).powi(2) + (element.1 - position.1).powi(2)
+}
+
+const LOCATIONS: [(f64, f64); 6] = [(0.0, 0.0), (-49.0, -49.0), (12.3, 45.6), (49.9, -20.0), (-80.0, 10.0), (300.0, 300.0)];
+
+#[test]
+fn frozen_quad_tree_matches_quad_tree() {
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 6);
+    let elements = fill(&mut quad_tree);
+
+    let mut bytes = Vec::new();
+    quad_tree.write_frozen(&mut bytes).unwrap();
+
+    let frozen = FrozenQuadTree::<u32>::from_bytes(&bytes).unwrap();
+
+    assert_eq!(frozen.count(), quad_tree.count());
+    assert_eq!(frozen.capacity(), quad_tree.capacity());
+    assert_eq!(frozen.bounds(), quad_tree.bounds());
+
+    for location in LOCATIONS {
+        for radius in [0.5, 3.0, 20.0, 200.0] {
+            assert_eq!(frozen.in_circle(location, radius), quad_tree.in_circle(location, radius));
+            assert_eq!(frozen.query_count(&Circle::new(location, radius)), quad_tree.count_in_circle(location, radius));
+            assert_eq!(frozen.query_any(&Circle::new(location, radius)), quad_tree.any_in_circle(location, radius));
+        }
+
+        let rect = (location.0 - 5.0)..(location.0 + 15.0);
+        assert_eq!(frozen.in_rect(rect.clone(), -50.0..0.0), quad_tree.in_rect(rect, -50.0..0.0));
+
+        let nearest = frozen.nearest(location).unwrap();
+        assert_eq!(distance(&elements, nearest, location), brute_force_nearest(&elements, location));
+    }
+}
+
+#[test]
+fn frozen_grid_matches_grid() {
+    let mut grid = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (13, 7));
+    let elements = fill(&mut grid);
+
+    let bytes = grid.to_frozen_bytes();
+    let frozen = FrozenGrid::<u32>::from_bytes(&bytes).unwrap();
+
+    assert_eq!(frozen.count(), grid.count());
+    assert_eq!(frozen.cell_count(), grid.cell_count());
+    assert_eq!(frozen.cell_size(), grid.cell_size());
+    assert_eq!(frozen.bounds(), grid.bounds());
+
+    for location in LOCATIONS {
+        for radius in [0.5, 3.0, 20.0, 200.0] {
+            assert_eq!(frozen.in_circle(location, radius), grid.in_circle(location, radius));
+            assert_eq!(frozen.query_count(&Circle::new(location, radius)), grid.count_in_circle(location, radius));
+        }
+
+        let nearest = frozen.nearest(location).unwrap();
+        assert_eq!(distance(&elements, nearest, location), brute_force_nearest(&elements, location));
+    }
+}
+
+#[test]
+fn frozen_grid_nearest_in_sparse_grid() {
+    let mut grid = Grid::with_cell_count(0.0..100.0, 0.0..40.0, (50, 20));
+    let elements = vec![((1.0, 1.0), 0), ((99.0, 39.0), 1), ((60.0, 2.0), 2), ((3.0, 38.0), 3), ((150.0, -20.0), 4)];
+
+    for (position, data) in &elements {
+        grid.insert_unchecked(*position, *data);
+    }
+
+    let bytes = grid.to_frozen_bytes();
+    let frozen = FrozenGrid::<u32>::from_bytes(&bytes).unwrap();
+
+    for x in (-20..=120).step_by(7) {
+        for y in (-10..=50).step_by(3) {
+            let location = (x as f64, y as f64);
+            let nearest = frozen.nearest(location).unwrap();
+
+            assert_eq!(distance(&elements, nearest, location), brute_force_nearest(&elements, location), "{:?}", location);
+        }
+    }
+}
+
+#[test]
+fn frozen_empty() {
+    let quad_tree: QuadTree<u32> = QuadTree::with_capacity(0.0..1.0, 0.0..1.0, 4);
+    let grid: Grid<u32> = Grid::with_cell_count(0.0..1.0, 0.0..1.0, (3, 3));
+
+    let quad_tree_bytes = quad_tree.to_frozen_bytes();
+    let grid_bytes = grid.to_frozen_bytes();
+
+    assert_eq!(FrozenQuadTree::<u32>::from_bytes(&quad_tree_bytes).unwrap().nearest((0.5, 0.5)), None);
+    assert_eq!(FrozenGrid::<u32>::from_bytes(&grid_bytes).unwrap().nearest((0.5, 0.5)), None);
+}
+
+#[test]
+fn frozen_invalid_bytes() {
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 6);
+    fill(&mut quad_tree);
+
+    let bytes = quad_tree.to_frozen_bytes();
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    assert_eq!(FrozenQuadTree::<u32>::from_bytes(&bad_magic).unwrap_err(), FrozenError::BadMagic);
+
+    let mut bad_version = bytes.clone();
+    bad_version[4] = 99;
+    assert_eq!(FrozenQuadTree::<u32>::from_bytes(&bad_version).unwrap_err(), FrozenError::UnsupportedVersion(99));
+
+    assert_eq!(FrozenGrid::<u32>::from_bytes(&bytes).unwrap_err(), FrozenError::WrongKind(1));
+    assert_eq!(FrozenQuadTree::<u64>::from_bytes(&bytes).unwrap_err(), FrozenError::DataSize { expected: 8, found: 4 });
+
+    assert_eq!(FrozenQuadTree::<u32>::from_bytes(&MAGIC).unwrap_err(), FrozenError::UnexpectedEnd);
+    assert_eq!(FrozenQuadTree::<u32>::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(), FrozenError::UnexpectedEnd);
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(matches!(FrozenQuadTree::<u32>::from_bytes(&trailing), Err(FrozenError::Corrupt(_))));
+
+    // the first child index of the root node
+    let mut bad_child = bytes.clone();
+    bad_child[68 + 56] = 0;
+    bad_child[68 + 57] = 1;
+    assert!(matches!(FrozenQuadTree::<u32>::from_bytes(&bad_child), Err(FrozenError::Corrupt(_))));
+
+    // the element count of the root node
+    let mut bad_count = bytes.clone();
+    bad_count[68 + 48] ^= 1;
+    assert!(matches!(FrozenQuadTree::<u32>::from_bytes(&bad_count), Err(FrozenError::Corrupt(_))));
+
+    // the capacity is written as u64 but a QuadTree only holds a u16
+    let mut big_capacity = bytes.clone();
+    big_capacity[44..52].copy_from_slice(&70_000u64.to_le_bytes());
+    assert_eq!(FrozenQuadTree::<u32>::from_bytes(&big_capacity).unwrap_err(), FrozenError::Corrupt("capacity does not fit into a u16"));
+
+    let mut zero_capacity = bytes.clone();
+    zero_capacity[44..52].copy_from_slice(&0u64.to_le_bytes());
+    assert!(matches!(FrozenQuadTree::<u32>::from_bytes(&zero_capacity), Err(FrozenError::Corrupt(_))));
+
+    // the first element of the last node
+    let mut bad_first = bytes.clone();
+    let node_count = u64::from_le_bytes(bytes[52..60].try_into().unwrap()) as usize;
+    let last = 68 + (node_count - 1) * 64 + 32;
+    bad_first[last..last + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_eq!(FrozenQuadTree::<u32>::from_bytes(&bad_first).unwrap_err(), FrozenError::Corrupt("node exceeds the element or node count"));
+
+    let mut grid = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (13, 7));
+    fill(&mut grid);
+
+    let mut bad_offset = grid.to_frozen_bytes();
+    // the offset of the second cell
+    bad_offset[84 + 8..84 + 16].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(matches!(FrozenGrid::<u32>::from_bytes(&bad_offset), Err(FrozenError::Corrupt(_))));
+}
@@ -0,0 +1,79 @@
+use spatial_neighbors::geo::{distance, GeoGrid, GeoQuadTree};
+
+fn positions() -> Vec<(f64, f64)> {
+    let mut positions = Vec::new();
+
+    for latitude in (-90..90).step_by(3) {
+        for longitude in (-180..180).step_by(4) {
+            positions.push((latitude as f64 + 0.25, longitude as f64 + 0.5));
+        }
+    }
+
+    positions.push((90.0, 0.0));
+    positions.push((-90.0, 123.0));
+    positions.push((10.0, 179.9));
+    positions.push((10.0, -179.9));
+
+    positions
+}
+
+fn fill(grid: &mut GeoGrid<usize>, quad_tree: &mut GeoQuadTree<usize>) -> Vec<(f64, f64)> {
+    let positions = positions();
+
+    for (index, position) in positions.iter().enumerate() {
+        grid.insert(*position, index);
+        quad_tree.insert(*position, index);
+    }
+
+    positions
+}
+
+#[test]
+fn in_radius_matches_great_circle_distance() {
+    let location = [(0.0, 0.0), (10.0, 180.0), (10.0, -179.5), (60.0, 170.0), (-75.0, 20.0), (88.0, -40.0), (-89.5, 100.0)];
+    let sizes = [10_000.0, 250_000.0, 1_000_000.0, 3_000_000.0, 25_000_000.0];
+
+    let mut grid = GeoGrid::with_cell_count((72, 36));
+    let mut quad_tree = GeoQuadTree::with_capacity(8);
+
+    let positions = fill(&mut grid, &mut quad_tree);
+
+    for location in location {
+        for size in sizes {
+            let mut expected: Vec<usize> = (0..positions.len()).filter(|index| distance(location, positions[*index]) <= size).collect();
+            let mut in_grid = grid.in_radius(location, size);
+            let mut in_tree = quad_tree.in_radius(location, size);
+
+            expected.sort();
+            in_grid.sort();
+            in_tree.sort();
+
+            assert_eq!(in_grid, expected, "{:?} {}", location, size);
+            assert_eq!(in_tree, expected, "{:?} {}", location, size);
+            assert_eq!(grid.count_in_radius(location, size), expected.len());
+            assert_eq!(quad_tree.any_in_radius(location, size), !expected.is_empty());
+        }
+    }
+}
+
+#[test]
+fn in_radius_across_antimeridian() {
+    let mut grid = GeoGrid::new();
+
+    grid.insert((10.0, 179.9), 0);
+    grid.insert((10.0, -179.9), 1);
+    grid.insert((10.0, 540.0), 2);
+
+    let mut in_radius = grid.in_radius((10.0, 180.0), 50_000.0);
+    in_radius.sort();
+
+    assert_eq!(in_radius, vec![0, 1, 2]);
+}
+
+#[test]
+#[should_panic]
+fn insert_invalid_latitude() {
+    let mut grid = GeoGrid::new();
+
+    grid.insert((90.5, 0.0), 0);
+}
@@ -0,0 +1,205 @@
+use std::ops::Range;
+use proptest::prelude::*;
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::frozen::FrozenGrid;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::shape::{Circle, Shape};
+use spatial_neighbors::static_grid::StaticGrid;
+
+/// (x, y) ranges with a random start and extent
+fn bounds() -> impl Strategy<Value = (Range<f64>, Range<f64>)> {
+    (-1000.0..1000.0, 0.001..1000.0, -1000.0..1000.0, 0.001..1000.0)
+        .prop_map(|(x, width, y, height): (f64, f64, f64, f64)| (x..x + width, y..y + height))
+}
+
+/// a coordinate inside of the range, often one which lies exactly on a cell border or right below the end
+fn coordinate(range: Range<f64>, cell_count: usize) -> impl Strategy<Value = f64> {
+    let extent = range.end - range.start;
+    let cell_size = extent / cell_count as f64;
+    let inside = range.clone();
+
+    prop_oneof![
+        (0.0..1.0).prop_map(move |t: f64| (range.start + t * extent).min(range.end.next_down())),
+        (0..=cell_count).prop_map(move |i| (inside.start + i as f64 * cell_size).min(inside.end.next_down())),
+        Just(inside.start),
+        Just(inside.end.next_down()),
+    ]
+}
+
+/// a coordinate anywhere, including far outside of the range and NaN
+fn any_coordinate(range: Range<f64>, cell_count: usize) -> impl Strategy<Value = f64> {
+    let extent = range.end - range.start;
+
+    prop_oneof![
+        4 => coordinate(range.clone(), cell_count),
+        2 => (-2.0..3.0).prop_map(move |t: f64| range.start + t * extent),
+        1 => Just(range.end),
+        1 => prop::sample::select(vec![f64::NAN, -1e300, 1e300]),
+    ]
+}
+
+fn sorted(mut data: Vec<usize>) -> Vec<usize> {
+    data.sort();
+    data
+}
+
+fn brute_force(elements: &[((f64, f64), usize)], shape: &impl Shape) -> Vec<usize> {
+    sorted(elements.iter().filter(|element| shape.contains(element.0)).map(|element| element.1).collect())
+}
+
+/// a grid with its elements and circles around some of the elements and around random positions
+#[derive(Debug)]
+struct Case {
+    x: Range<f64>,
+    y: Range<f64>,
+    cell_count: (usize, usize),
+    positions: Vec<(f64, f64)>,
+    circles: Vec<((f64, f64), f64)>,
+}
+
+fn case(inside_only: bool) -> impl Strategy<Value = Case> {
+    (bounds(), 1..40usize, 1..40usize).prop_flat_map(move |((x, y), count_x, count_y)| {
+        let extent = (x.end - x.start).max(y.end - y.start);
+
+        let position = if inside_only {
+            (coordinate(x.clone(), count_x), coordinate(y.clone(), count_y)).boxed()
+        } else {
+            (any_coordinate(x.clone(), count_x), any_coordinate(y.clone(), count_y)).boxed()
+        };
+
+        let center = (any_coordinate(x.clone(), count_x), any_coordinate(y.clone(), count_y));
+        let radius = prop_oneof![Just(0.0), 0.0..extent * 1.5, Just(extent * 4.0)];
+
+        (
+            Just((x, y, (count_x, count_y))),
+            prop::collection::vec(position, 0..200),
+            prop::collection::vec((center, radius), 1..12),
+            prop::collection::vec((any::<prop::sample::Index>(), 0.0..extent / 4.0), 0..6),
+        ).prop_map(|((x, y, cell_count), positions, mut circles, around)| {
+            // circles centered on an element find it at least with a radius of 0
+            if !positions.is_empty() {
+                circles.extend(around.into_iter().map(|(index, radius)| (*index.get(&positions), radius)));
+            }
+
+            Case { x, y, cell_count, positions, circles }
+        })
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn in_circle_matches_brute_force(case in case(true)) {
+        let mut grid = Grid::with_cell_count(case.x.clone(), case.y.clone(), case.cell_count);
+        let elements: Vec<_> = case.positions.iter().enumerate().map(|(i, position)| (*position, i)).collect();
+
+        for element in &elements {
+            grid.insert(element.0, element.1);
+        }
+
+        let static_grid = StaticGrid::from_elements(case.x.clone(), case.y.clone(), case.cell_count, &elements);
+
+        for (position, radius) in &case.circles {
+            let expected = brute_force(&elements, &Circle::new(*position, *radius));
+
+            prop_assert_eq!(&sorted(grid.in_circle(*position, *radius)), &expected);
+            prop_assert_eq!(grid.count_in_circle(*position, *radius), expected.len());
+            prop_assert_eq!(&sorted(static_grid.in_circle(*position, *radius)), &expected);
+        }
+    }
+
+    #[test]
+    fn insert_unchecked_matches_brute_force(case in case(false)) {
+        let mut grid = Grid::with_cell_count(case.x.clone(), case.y.clone(), case.cell_count);
+        let mut elements: Vec<_> = case.positions.iter().enumerate().map(|(i, position)| (*position, i)).collect();
+
+        let handles: Vec<_> = elements.iter().map(|element| grid.insert_unchecked(element.0, element.1)).collect();
+
+        let static_grid = StaticGrid::from(&grid);
+        let bytes = grid.to_frozen_bytes();
+        let frozen_grid = FrozenGrid::<usize>::from_bytes(&bytes).unwrap();
+
+        for (position, radius) in &case.circles {
+            let expected = brute_force(&elements, &Circle::new(*position, *radius));
+
+            prop_assert_eq!(&sorted(grid.in_circle(*position, *radius)), &expected);
+            prop_assert_eq!(&sorted(static_grid.in_circle(*position, *radius)), &expected);
+            prop_assert_eq!(&sorted(frozen_grid.in_circle(*position, *radius)), &expected);
+        }
+
+        // removing the elements outside of the bounds brings back the shortcuts of the edge cells
+        for (handle, element) in handles.iter().zip(&elements) {
+            let inside = case.x.contains(&element.0.0) && case.y.contains(&element.0.1);
+
+            if !inside {
+                prop_assert_eq!(grid.remove(*handle), Some(element.1));
+            }
+        }
+
+        elements.retain(|element| case.x.contains(&element.0.0) && case.y.contains(&element.0.1));
+
+        for (position, radius) in &case.circles {
+            prop_assert_eq!(sorted(grid.in_circle(*position, *radius)), brute_force(&elements, &Circle::new(*position, *radius)));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "out of bounce")]
+fn insert_on_the_end_panics() {
+    let mut grid = Grid::with_cell_count(0.0..10.0, 0.0..10.0, (10, 10));
+
+    grid.insert((10.0, 5.0), 0);
+}
+
+#[test]
+fn positions_right_below_the_end_stay_in_the_last_cell() {
+    let mut grid = Grid::with_cell_count(0.0..0.3, 0.0..0.3, (3, 3));
+    let position = (0.3f64.next_down(), 0.3f64.next_down());
+
+    grid.insert(position, 0);
+
+    assert_eq!(grid.cell((2, 2)), &vec![(position, 0)]);
+    assert_eq!(grid.in_circle(position, 0.0), vec![0]);
+}
+
+#[test]
+#[should_panic(expected = "tried to get cell (3, 0) of a Grid with a cell_count of (3, 2)")]
+fn cell_outside_of_the_grid_panics() {
+    let grid: Grid<u32> = Grid::with_cell_count(0.0..3.0, 0.0..2.0, (3, 2));
+
+    // would be the first cell of the second row if the index was not checked
+    grid.cell((3, 0));
+}
+
+#[test]
+fn dense_cells_stay_consistent_after_updates_and_removes() {
+    let mut grid = Grid::with_cell_count(0.0..100.0, 0.0..100.0, (2, 2));
+    let mut elements: Vec<_> = (0..2000).map(|i| (((i * 37 % 100) as f64 + 0.5, (i * 61 % 100) as f64 + 0.25), i)).collect();
+
+    let handles: Vec<_> = elements.iter().map(|element| grid.insert(element.0, element.1)).collect();
+
+    // moves some elements within their cell and some to another cell, then removes every seventh
+    for (i, handle) in handles.iter().enumerate().step_by(3) {
+        let position = ((elements[i].0.0 + (i % 2) as f64 * 50.0) % 100.0, elements[i].0.1);
+
+        assert!(grid.update_position(*handle, position));
+        elements[i].0 = position;
+    }
+
+    for (i, handle) in handles.iter().enumerate().step_by(7) {
+        assert_eq!(grid.remove(*handle), Some(elements[i].1));
+    }
+
+    elements.retain(|element| element.1 % 7 != 0);
+
+    for center in [(10.0, 10.0), (50.0, 50.0), (75.0, 20.0), (99.0, 99.0)] {
+        for radius in [0.0, 3.0, 12.5, 40.0, 200.0] {
+            let expected = brute_force(&elements, &Circle::new(center, radius));
+
+            assert_eq!(sorted(grid.in_circle(center, radius)), expected);
+            assert_eq!(grid.count_in_circle(center, radius), expected.len());
+        }
+    }
+}
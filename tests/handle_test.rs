@@ -0,0 +1,142 @@
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::brute_force::BruteForce;
+use spatial_neighbors::geo::GeoGrid;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::handle::Handle;
+use spatial_neighbors::kd_tree::KdTree;
+use spatial_neighbors::linear_quad_tree::LinearQuadTree;
+use spatial_neighbors::quad_tree::QuadTree;
+use spatial_neighbors::shape::{Circle, Rect};
+
+fn position(i: u32) -> (f64, f64) {
+    ((i as u64 * 7919 % 1000) as f64 / 10.0 - 50.0, (i as u64 * 104_729 % 997) as f64 / 10.0 - 50.0)
+}
+
+fn sorted(mut data: Vec<u32>) -> Vec<u32> {
+    data.sort();
+    data
+}
+
+/// compares the partitioner against a plain list of the elements which are still stored
+fn assert_same(partitioner: &impl SpatialIndexMut<u32>, elements: &[(Handle, (f64, f64), u32)]) {
+    assert_eq!(partitioner.count(), elements.len());
+
+    for (handle, position, data) in elements {
+        assert_eq!(partitioner.get(*handle), Some((*position, *data)));
+    }
+
+    for location in [(0.0, 0.0), (-45.0, 30.0), (20.0, -20.0)] {
+        for radius in [1.0, 7.5, 30.0, 100.0] {
+            let expected = sorted(elements.iter().filter(|element| {
+                (element.1.0 - location.0).powi(2) + (element.1.1 - location.1).powi(2) <= radius * radius
+            }).map(|element| element.2).collect());
+
+            assert_eq!(sorted(partitioner.query(&Circle::new(location, radius))), expected);
+            assert_eq!(partitioner.query_count(&Circle::new(location, radius)), expected.len());
+        }
+    }
+
+    assert_eq!(sorted(partitioner.query(&Rect::new(-50.0..50.0, -50.0..50.0))), sorted(elements.iter().map(|element| element.2).collect()));
+}
+
+fn assert_handles(mut partitioner: impl SpatialIndexMut<u32>) {
+    let mut elements: Vec<_> = (0..2_000).map(|i| (partitioner.insert(position(i), i), position(i), i)).collect();
+
+    assert_same(&partitioner, &elements);
+
+    // moves the elements far enough to end up in other cells and nodes
+    for (i, element) in elements.iter_mut().enumerate().filter(|(i, _)| i % 2 == 0) {
+        element.1 = position(i as u32 * 31 + 7);
+
+        assert!(partitioner.update_position(element.0, element.1));
+    }
+
+    assert_same(&partitioner, &elements);
+
+    let removed: Vec<_> = elements.iter().copied().filter(|element| element.2 % 3 == 0).collect();
+    elements.retain(|element| element.2 % 3 != 0);
+
+    for (handle, _, data) in &removed {
+        assert_eq!(partitioner.remove(*handle), Some(*data));
+        assert_eq!(partitioner.remove(*handle), None);
+        assert!(!partitioner.update_position(*handle, (0.0, 0.0)));
+    }
+
+    assert_same(&partitioner, &elements);
+
+    // the freed slots get reused without making the removed handles valid again
+    for i in 0..100 {
+        elements.push((partitioner.insert(position(i * 3), 10_000 + i), position(i * 3), 10_000 + i));
+    }
+
+    assert!(removed.iter().all(|(handle, _, _)| partitioner.get(*handle).is_none()));
+    assert_same(&partitioner, &elements);
+
+    partitioner.clear();
+
+    assert!(elements.iter().all(|(handle, _, _)| partitioner.get(*handle).is_none()));
+    assert_same(&partitioner, &[]);
+}
+
+#[test]
+fn grid_handles() {
+    assert_handles(Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (13, 7)));
+}
+
+#[test]
+fn quad_tree_handles() {
+    assert_handles(QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4));
+    assert_handles(QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 0));
+}
+
+#[test]
+fn linear_quad_tree_handles() {
+    assert_handles(LinearQuadTree::new(-50.0..50.0, -50.0..50.0));
+}
+
+#[test]
+fn kd_tree_handles() {
+    assert_handles(KdTree::new(-50.0..50.0, -50.0..50.0));
+}
+
+#[test]
+fn brute_force_handles() {
+    assert_handles(BruteForce::new());
+}
+
+#[test]
+fn from_elements_handles() {
+    let elements: Vec<_> = (0..500).map(|i| (position(i), i)).collect();
+
+    let mut kd_tree = KdTree::from_elements(-50.0..50.0, -50.0..50.0, &elements);
+    let mut linear_quad_tree = LinearQuadTree::from_elements(-50.0..50.0, -50.0..50.0, &elements);
+
+    for (i, element) in elements.iter().enumerate() {
+        assert_eq!(kd_tree.remove(Handle::nth(i)), Some(element.1));
+        assert_eq!(linear_quad_tree.remove(Handle::nth(i)), Some(element.1));
+        assert_eq!(kd_tree.count(), elements.len() - i - 1);
+        assert_eq!(linear_quad_tree.count(), elements.len() - i - 1);
+    }
+}
+
+#[test]
+fn geo_handles() {
+    let mut grid: GeoGrid<u32> = GeoGrid::new();
+
+    let handle = grid.insert((52.5, 13.5), 0);
+
+    assert_eq!(grid.get(handle), Some(((52.5, 13.5), 0)));
+    assert!(grid.update_position(handle, (48.1, 371.5)));
+    assert_eq!(grid.in_radius((48.1, 11.5), 1_000.0), vec![0]);
+    assert_eq!(grid.remove(handle), Some(0));
+    assert_eq!(grid.count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn update_position_out_of_bounds() {
+    let mut grid = Grid::new(0.0..10.0, 0.0..10.0);
+
+    let handle = grid.insert((5.0, 5.0), 0);
+    grid.update_position(handle, (10.0, 5.0));
+}
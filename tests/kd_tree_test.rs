@@ -0,0 +1,64 @@
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::kd_tree::KdTree;
+
+/// clustered around a few centers
+fn elements() -> Vec<((f64, f64), u32)> {
+    let centers = [(-40.0, -40.0), (10.0, 35.0), (30.0, -5.0)];
+
+    (0..3_000u32).map(|i| {
+        let center = centers[i as usize % 3];
+        let angle = i as f64 * 2.399;
+        let radius = (i % 97) as f64 / 20.0;
+
+        ((center.0 + angle.cos() * radius, center.1 + angle.sin() * radius), i)
+    }).collect()
+}
+
+fn brute_force_k_nearest(elements: &[((f64, f64), u32)], position: (f64, f64), k: usize) -> Vec<f64> {
+    let mut distances: Vec<f64> = elements.iter().map(|(element, _)| (element.0 - position.0).powi(2) + (element.1 - position.1).powi(2)).collect();
+
+    distances.sort_by(f64::total_cmp);
+    distances.truncate(k);
+
+    distances
+}
+
+fn distances(elements: &[((f64, f64), u32)], found: &[u32], position: (f64, f64)) -> Vec<f64> {
+    found.iter().map(|data| {
+        let element = elements[*data as usize].0;
+
+        (element.0 - position.0).powi(2) + (element.1 - position.1).powi(2)
+    }).collect()
+}
+
+#[test]
+fn k_nearest_matches_brute_force() {
+    let elements = elements();
+
+    let built = KdTree::from_elements(-50.0..50.0, -50.0..50.0, &elements);
+
+    let mut inserted = KdTree::new(-50.0..50.0, -50.0..50.0);
+    elements.iter().for_each(|(position, data)| { inserted.insert(*position, *data); });
+
+    for position in [(0.0, 0.0), (-40.0, -40.0), (10.1, 35.2), (49.0, -49.0), (300.0, -300.0)] {
+        for k in [1, 2, 10, 100] {
+            let expected = brute_force_k_nearest(&elements, position, k);
+
+            assert_eq!(distances(&elements, &built.k_nearest(position, k), position), expected);
+            assert_eq!(distances(&elements, &inserted.k_nearest(position, k), position), expected);
+        }
+
+        assert_eq!(distances(&elements, &[built.nearest(position).unwrap()], position), brute_force_k_nearest(&elements, position, 1));
+    }
+
+    assert_eq!(built.k_nearest((0.0, 0.0), 5_000).len(), elements.len());
+    assert!(built.k_nearest((0.0, 0.0), 0).is_empty());
+}
+
+#[test]
+fn empty_kd_tree() {
+    let kd_tree: KdTree<u32> = KdTree::new(-50.0..50.0, -50.0..50.0);
+
+    assert_eq!(kd_tree.nearest((0.0, 0.0)), None);
+    assert!(kd_tree.in_circle((0.0, 0.0), 100.0).is_empty());
+}
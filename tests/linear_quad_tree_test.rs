@@ -0,0 +1,101 @@
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::brute_force::BruteForce;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::linear_quad_tree::LinearQuadTree;
+use spatial_neighbors::metric::Manhattan;
+use spatial_neighbors::shape::{Rect, Ring};
+
+fn elements() -> Vec<((f64, f64), u32)> {
+    (0..3_000u32).map(|i| (((i * 7919 % 1000) as f64 / 10.0 - 50.0, (i * 104_729 % 997) as f64 / 10.0 - 50.0), i)).collect()
+}
+
+const LOCATIONS: [(f64, f64); 6] = [(0.0, 0.0), (-49.0, -49.0), (12.3, 45.6), (49.9, -20.0), (-80.0, 10.0), (300.0, 300.0)];
+
+fn sorted(mut data: Vec<u32>) -> Vec<u32> {
+    data.sort();
+    data
+}
+
+#[test]
+fn linear_quad_tree_matches_grid() {
+    let elements = elements();
+
+    let mut grid = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (20, 20));
+    let mut linear_quad_tree = LinearQuadTree::new(-50.0..50.0, -50.0..50.0);
+
+    for (position, data) in &elements {
+        grid.insert(*position, *data);
+        linear_quad_tree.insert(*position, *data);
+    }
+
+    assert_eq!(linear_quad_tree.count(), grid.count());
+
+    for location in LOCATIONS {
+        for radius in [0.05, 0.5, 5.0, 23.7, 200.0] {
+            assert_eq!(sorted(linear_quad_tree.in_circle(location, radius)), sorted(grid.in_circle(location, radius)));
+            assert_eq!(sorted(linear_quad_tree.in_circle_with(location, radius, Manhattan)), sorted(grid.in_circle_with(location, radius, Manhattan)));
+            assert_eq!(linear_quad_tree.query_count(&Ring::new(location, radius / 2.0, radius)), grid.query_count(&Ring::new(location, radius / 2.0, radius)));
+            assert_eq!(linear_quad_tree.any_in_circle(location, radius), grid.any_in_circle(location, radius));
+        }
+
+        let rect = Rect::new(location.0 - 10.0..location.0 + 3.0, location.1 - 2.0..location.1 + 20.0);
+        assert_eq!(sorted(linear_quad_tree.query(&rect)), sorted(grid.query(&rect)));
+    }
+}
+
+#[test]
+fn from_elements_matches_insert() {
+    let elements = elements();
+
+    let mut linear_quad_tree = LinearQuadTree::new(-50.0..50.0, -50.0..50.0);
+    elements.iter().for_each(|(position, data)| { linear_quad_tree.insert(*position, *data); });
+
+    let built = LinearQuadTree::from_elements(-50.0..50.0, -50.0..50.0, &elements);
+
+    assert_eq!(built.codes(), linear_quad_tree.codes());
+    assert_eq!(built.elements(), linear_quad_tree.elements());
+    assert!(built.codes().windows(2).all(|codes| codes[0] <= codes[1]));
+}
+
+#[test]
+fn morton_code_interleaves_bits() {
+    let linear_quad_tree: LinearQuadTree<u32> = LinearQuadTree::new(0.0..4_294_967_296.0, 0.0..4_294_967_296.0);
+
+    assert_eq!(linear_quad_tree.code((0.0, 0.0)), 0);
+    assert_eq!(linear_quad_tree.code((1.0, 0.0)), 0b01);
+    assert_eq!(linear_quad_tree.code((0.0, 1.0)), 0b10);
+    assert_eq!(linear_quad_tree.code((3.0, 5.0)), 0b100111);
+    assert_eq!(linear_quad_tree.code((4_294_967_295.0, 4_294_967_295.0)), u64::MAX);
+}
+
+#[test]
+fn elements_on_node_edges_lie_in_their_node() {
+    let bounds = -132.239..258.009;
+    let cell_size = (bounds.end - bounds.start) / 4_294_967_296.0;
+
+    // positions on and right next to the edges of the smallest nodes, which a division can round into the neighbouring node
+    let mut positions = vec![44.59193935760731];
+
+    for cell in [1u64, 1_000_003, 1_234_567_891, 2_147_483_648, 3_000_000_019, 4_294_967_295] {
+        let edge = bounds.start + cell as f64 * cell_size;
+
+        positions.extend([edge.next_down(), edge, edge.next_up()]);
+    }
+
+    // more than fit into a leaf, so the elements get split down to the smallest node
+    let elements: Vec<_> = positions.iter().enumerate()
+        .flat_map(|(i, x)| std::iter::repeat_n(((*x, *x), i as u32), 64))
+        .collect();
+
+    let linear_quad_tree = LinearQuadTree::from_elements(bounds.clone(), bounds.clone(), &elements);
+
+    let mut brute_force = BruteForce::new();
+    elements.iter().for_each(|(position, data)| { brute_force.insert(*position, *data); });
+
+    for x in &positions {
+        for rect in [Rect::new(*x..bounds.end, bounds.clone()), Rect::new(bounds.start..x.next_up(), bounds.clone()), Rect::new(*x..x.next_up(), *x..x.next_up())] {
+            assert_eq!(linear_quad_tree.query_count(&rect), brute_force.query_count(&rect));
+            assert_eq!(sorted(linear_quad_tree.query(&rect)), sorted(brute_force.query(&rect)));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::quad_tree::QuadTree;
+use spatial_neighbors::shape::Rect;
+use spatial_neighbors::stats::QueryStats;
+
+fn sorted(mut data: Vec<usize>) -> Vec<usize> {
+    data.sort();
+    data
+}
+
+/// 6 x 6 cells of size 1 with 5 elements each, enough for the shortcuts of the cells
+fn filled_grid() -> Grid<usize> {
+    let mut grid = Grid::with_cell_count(0.0..6.0, 0.0..6.0, (6, 6));
+
+    for i in 0..36 * 5 {
+        let cell = i / 5;
+        grid.insert(((cell % 6) as f64 + 0.1 * (i % 5) as f64 + 0.3, (cell / 6) as f64 + 0.5), i);
+    }
+
+    grid
+}
+
+#[test]
+fn grid_in_circle_with_stats() {
+    let grid = filled_grid();
+    let mut stats = QueryStats::default();
+
+    let found = grid.in_circle_with_stats((3.0, 3.0), 2.1, &mut stats);
+
+    assert_eq!(sorted(found.clone()), sorted(grid.in_circle((3.0, 3.0), 2.1)));
+
+    // the four cells around the center lie completely inside, the corners of the searched area outside
+    assert_eq!(stats, QueryStats { visited: 36, accepted: 4, rejected: 12, tested: 100, returned: found.len() });
+    assert_eq!(stats.to_string(), format!("visited 36 cells or nodes, 4 accepted and 12 rejected as a whole, tested 100 positions, returned {}", found.len()));
+}
+
+#[test]
+fn stats_sum_up_many_queries() {
+    let grid = filled_grid();
+    let mut stats = QueryStats::default();
+
+    let first = grid.query_with_stats(&Rect::new(-1.0..7.0, -1.0..7.0), &mut stats);
+    assert_eq!(stats, QueryStats { visited: 36, accepted: 36, rejected: 0, tested: 0, returned: 180 });
+
+    let second = grid.in_circle_with_stats((0.5, 0.5), 0.0, &mut stats);
+    assert_eq!(first.len() + second.len(), stats.returned);
+    assert_eq!(stats.visited, 37);
+    assert_eq!(stats.tested, 5);
+}
+
+#[test]
+fn quad_tree_in_circle_with_stats() {
+    let mut quad_tree = QuadTree::with_capacity(0.0..8.0, 0.0..8.0, 4);
+
+    for i in 0..32 * 32 {
+        quad_tree.insert(((i % 32) as f64 * 0.25 + 0.125, (i / 32) as f64 * 0.25 + 0.125), i);
+    }
+
+    let mut stats = QueryStats::default();
+    let found = quad_tree.in_circle_with_stats((2.0, 2.0), 1.5, &mut stats);
+
+    assert_eq!(sorted(found.clone()), sorted(quad_tree.in_circle((2.0, 2.0), 1.5)));
+    assert_eq!(stats.returned, found.len());
+    assert!(stats.accepted > 0 && stats.rejected > 0);
+    assert!(stats.visited > stats.accepted + stats.rejected);
+
+    // every position is either tested or taken with an accepted node
+    assert!(stats.tested < 1024 && stats.tested + stats.accepted * 4 >= found.len());
+
+    // the root lies inside, so it is taken without looking at any other node
+    let mut stats = QueryStats::default();
+    quad_tree.in_circle_with_stats((4.0, 4.0), 6.0, &mut stats);
+
+    assert_eq!(stats, QueryStats { visited: 1, accepted: 1, rejected: 0, tested: 0, returned: 1024 });
+}
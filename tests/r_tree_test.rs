@@ -0,0 +1,101 @@
+use spatial_neighbors::r_tree::RTree;
+use spatial_neighbors::shape::{Circle, Rect, Shape};
+
+type Element = (((f64, f64), (f64, f64)), u32);
+
+fn elements() -> Vec<Element> {
+    (0..2_000u32).map(|i| {
+        let min = ((i * 7919 % 1000) as f64 / 10.0 - 50.0, (i * 104_729 % 997) as f64 / 10.0 - 50.0);
+        let size = ((i % 13) as f64 / 4.0, (i % 7) as f64 / 3.0);
+
+        ((min, (min.0 + size.0, min.1 + size.1)), i)
+    }).collect()
+}
+
+fn brute_force<S: Shape>(elements: &[Element], shape: &S, contained: bool) -> Vec<u32> {
+    elements.iter()
+        .filter(|((min, max), _)| if contained { shape.contains_box(*min, *max) } else { shape.intersects_box(*min, *max) })
+        .map(|(_, data)| *data)
+        .collect()
+}
+
+fn sorted(mut data: Vec<u32>) -> Vec<u32> {
+    data.sort();
+    data
+}
+
+fn assert_queries(r_tree: &RTree<u32>, elements: &[Element]) {
+    assert_eq!(r_tree.count(), elements.len());
+
+    for location in [(0.0, 0.0), (-49.0, -49.0), (12.3, 45.6), (49.9, -20.0), (300.0, 300.0)] {
+        for size in [0.5, 4.0, 23.7, 200.0] {
+            let circle = Circle::new(location, size);
+            let rect = Rect::new(location.0 - size..location.0 + size / 2.0, location.1 - size / 3.0..location.1 + size);
+
+            assert_eq!(sorted(r_tree.query_intersecting(&circle)), brute_force(elements, &circle, false));
+            assert_eq!(sorted(r_tree.query_contained(&circle)), brute_force(elements, &circle, true));
+            assert_eq!(sorted(r_tree.query_intersecting(&rect)), brute_force(elements, &rect, false));
+            assert_eq!(sorted(r_tree.query_contained(&rect)), brute_force(elements, &rect, true));
+
+            assert_eq!(r_tree.count_intersecting(&circle), brute_force(elements, &circle, false).len());
+            assert_eq!(r_tree.any_intersecting(&rect), !brute_force(elements, &rect, false).is_empty());
+        }
+
+        let distance = |data: &u32| {
+            let ((min, max), _) = elements.iter().find(|element| element.1 == *data).unwrap();
+            let nearest = (location.0.max(min.0).min(max.0), location.1.max(min.1).min(max.1));
+
+            (nearest.0 - location.0).powi(2) + (nearest.1 - location.1).powi(2)
+        };
+
+        let mut expected: Vec<f64> = elements.iter().map(|element| distance(&element.1)).collect();
+        expected.sort_by(f64::total_cmp);
+        expected.truncate(25);
+
+        assert_eq!(r_tree.k_nearest(location, 25).iter().map(distance).collect::<Vec<_>>(), expected);
+        assert_eq!(r_tree.nearest(location).map(|data| distance(&data)), expected.first().copied());
+    }
+}
+
+#[test]
+fn r_tree_matches_brute_force() {
+    let elements = elements();
+
+    let mut inserted = RTree::new();
+    elements.iter().for_each(|((min, max), data)| inserted.insert(*min, *max, *data));
+
+    assert_queries(&inserted, &elements);
+    assert_queries(&RTree::from_elements(&elements), &elements);
+}
+
+#[test]
+fn remove_boxes() {
+    let mut elements = elements();
+
+    let mut inserted = RTree::new();
+    elements.iter().for_each(|((min, max), data)| inserted.insert(*min, *max, *data));
+
+    let mut bulk_loaded = RTree::from_elements(&elements);
+
+    let removed: Vec<Element> = elements.iter().copied().filter(|element| element.1 % 3 != 0).collect();
+    elements.retain(|element| element.1 % 3 == 0);
+
+    for ((min, max), data) in &removed {
+        assert!(inserted.remove(*min, *max, *data));
+        assert!(bulk_loaded.remove(*min, *max, *data));
+    }
+
+    assert!(!inserted.remove((0.0, 0.0), (1.0, 1.0), 1));
+    assert!(!bulk_loaded.remove(removed[0].0.0, removed[0].0.1, removed[0].1));
+
+    assert_queries(&inserted, &elements);
+    assert_queries(&bulk_loaded, &elements);
+
+    for ((min, max), data) in &elements {
+        assert!(inserted.remove(*min, *max, *data));
+    }
+
+    assert_eq!(inserted.count(), 0);
+    assert!(inserted.query_intersecting(&Rect::new(-100.0..100.0, -100.0..100.0)).is_empty());
+    assert_eq!(inserted.nearest((0.0, 0.0)), None);
+}
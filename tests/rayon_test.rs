@@ -0,0 +1,101 @@
+#![cfg(feature = "rayon")]
+
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::handle::Handle;
+use spatial_neighbors::quad_tree::{QuadTree, QuadTreeNode};
+
+fn elements() -> Vec<((f64, f64), i32)> {
+    let mut elements = Vec::new();
+
+    for x in -100..100 {
+        for y in -100..100 {
+            elements.push((((x * 7 % 200) as f64 * 0.5, (y * 13 % 200) as f64 * 0.5), x * 1000 + y));
+        }
+    }
+
+    elements
+}
+
+fn assert_same_node(a: QuadTreeNode<i32>, b: QuadTreeNode<i32>) {
+    assert_eq!(a.data(), b.data());
+    assert_eq!(a.count(), b.count());
+
+    match (a.nodes(), b.nodes()) {
+        (Some(a), Some(b)) => a.into_iter().zip(b).for_each(|(a, b)| assert_same_node(a, b)),
+        (None, None) => {}
+        _ => panic!("nodes differ"),
+    }
+}
+
+#[test]
+fn par_from_elements_matches_insert() {
+    let elements = elements();
+
+    for capacity in [0, 1, 4, 50] {
+        let mut quad_tree = QuadTree::with_capacity(-100.0..100.0, -100.0..100.0, capacity);
+        elements.iter().for_each(|(position, data)| { quad_tree.insert(*position, *data); });
+
+        assert_same_node(quad_tree.node(), QuadTree::par_from_elements(-100.0..100.0, -100.0..100.0, capacity, &elements).node());
+    }
+
+    let mut grid = Grid::with_cell_count(-100.0..100.0, -100.0..100.0, (30, 20));
+    elements.iter().for_each(|(position, data)| { grid.insert(*position, *data); });
+
+    let par_grid = Grid::par_from_elements(-100.0..100.0, -100.0..100.0, (30, 20), &elements);
+
+    assert_eq!(grid.count(), par_grid.count());
+
+    for x in 0..30 {
+        for y in 0..20 {
+            assert_eq!(grid.cell((x, y)), par_grid.cell((x, y)));
+        }
+    }
+}
+
+#[test]
+fn par_from_elements_handles() {
+    let elements = elements();
+
+    let mut grid = Grid::par_from_elements(-100.0..100.0, -100.0..100.0, (30, 20), &elements);
+    let mut quad_tree = QuadTree::par_from_elements(-100.0..100.0, -100.0..100.0, 8, &elements);
+
+    for (i, element) in elements.iter().enumerate() {
+        assert_eq!(grid.get(Handle::nth(i)), Some(*element));
+        assert_eq!(quad_tree.get(Handle::nth(i)), Some(*element));
+    }
+
+    for i in (0..elements.len()).step_by(3) {
+        assert_eq!(grid.remove(Handle::nth(i)), Some(elements[i].1));
+        assert_eq!(quad_tree.remove(Handle::nth(i)), Some(elements[i].1));
+    }
+
+    for (i, element) in elements.iter().enumerate().filter(|(i, _)| i % 3 != 0) {
+        assert_eq!(grid.get(Handle::nth(i)), Some(*element));
+        assert_eq!(quad_tree.get(Handle::nth(i)), Some(*element));
+    }
+}
+
+#[test]
+fn par_in_circle_batch_matches_in_circle() {
+    let elements = elements();
+
+    let grid = Grid::par_from_elements(-100.0..100.0, -100.0..100.0, (30, 20), &elements);
+    let quad_tree = QuadTree::par_from_elements(-100.0..100.0, -100.0..100.0, 8, &elements);
+
+    let queries: Vec<((f64, f64), f64)> = (0..200).map(|i| (((i % 20) as f64 * 10.0 - 100.0, (i / 10) as f64 * 10.0 - 100.0), (i % 7) as f64 * 3.0)).collect();
+
+    let grid_results = grid.par_in_circle_batch(&queries);
+    let quad_tree_results = quad_tree.par_in_circle_batch(&queries);
+
+    for (i, (position, radius)) in queries.iter().enumerate() {
+        assert_eq!(grid_results[i], grid.in_circle(*position, *radius));
+        assert_eq!(quad_tree_results[i], quad_tree.in_circle(*position, *radius));
+    }
+}
+
+#[test]
+#[should_panic]
+fn par_from_elements_out_of_bounds() {
+    Grid::par_from_elements(0.0..10.0, 0.0..10.0, (10, 10), &[((10.0, 5.0), 0)]);
+}
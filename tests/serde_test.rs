@@ -0,0 +1,260 @@
+#![cfg(feature = "serde")]
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::brute_force::BruteForce;
+use spatial_neighbors::bvh::Bvh;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::handle::Handle;
+use spatial_neighbors::kd_tree::KdTree;
+use spatial_neighbors::linear_quad_tree::LinearQuadTree;
+use spatial_neighbors::quad_tree::{QuadTree, QuadTreeNode};
+use spatial_neighbors::r_tree::RTree;
+use spatial_neighbors::shape::{Circle, Rect};
+use spatial_neighbors::static_grid::StaticGrid;
+
+fn fill<P: SpatialIndexMut<u32>>(partitioner: &mut P) {
+    for i in 0..2_000u32 {
+        let x = (i * 7919 % 1000) as f64 / 10.0 - 33.3;
+        let y = (i * 104_729 % 1000) as f64 / 7.0 - 12.1;
+
+        partitioner.insert((x, y), i);
+    }
+}
+
+/// fills the partitioner like `fill`, removes every third element again and adds two elements
+/// outside of the bounds, returns the handles of the elements which are left
+fn churn<P: SpatialIndexMut<u32>>(partitioner: &mut P) -> Vec<Handle> {
+    let mut handles = Vec::new();
+
+    for i in 0..2_000u32 {
+        let x = (i * 7919 % 1000) as f64 / 10.0 - 33.3;
+        let y = (i * 104_729 % 1000) as f64 / 7.0 - 12.1;
+
+        let handle = partitioner.insert((x, y), i);
+
+        match i % 3 {
+            0 => assert!(partitioner.remove(handle).is_some()),
+            _ => handles.push(handle),
+        }
+    }
+
+    handles.push(partitioner.insert_unchecked((-50.0, 20.0), 5000));
+    handles.push(partitioner.insert_unchecked((200.0, 200.0), 5001));
+
+    handles
+}
+
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+/// the message of the error the corrupted snapshot of `value` is rejected with
+fn rejected<T: Serialize + DeserializeOwned>(value: &T, corrupt: impl FnOnce(&mut Value)) -> String {
+    let mut snapshot = serde_json::to_value(value).unwrap();
+    corrupt(&mut snapshot);
+
+    match serde_json::from_value::<T>(snapshot) {
+        Ok(_) => panic!("the corrupted snapshot was accepted"),
+        Err(error) => error.to_string(),
+    }
+}
+
+fn sorted(mut data: Vec<u32>) -> Vec<u32> {
+    data.sort();
+    data
+}
+
+fn assert_same_node(a: QuadTreeNode<u32>, b: QuadTreeNode<u32>) {
+    assert_eq!(a.data(), b.data());
+    assert_eq!(a.count(), b.count());
+    assert_eq!(a.center(), b.center());
+    assert_eq!(a.size(), b.size());
+
+    match (a.nodes(), b.nodes()) {
+        (Some(a), Some(b)) => a.into_iter().zip(b).for_each(|(a, b)| assert_same_node(a, b)),
+        (None, None) => {}
+        _ => panic!("nodes differ"),
+    }
+}
+
+#[test]
+fn grid_round_trip() {
+    let mut grid = Grid::with_cell_count(-33.3..66.7, -12.1..130.8, (17, 23));
+    fill(&mut grid);
+
+    let loaded: Grid<u32> = serde_json::from_str(&serde_json::to_string(&grid).unwrap()).unwrap();
+
+    assert_eq!(loaded.cell_count(), grid.cell_count());
+    assert_eq!(loaded.cell_size(), grid.cell_size());
+    assert_eq!(loaded.bounds(), grid.bounds());
+    assert_eq!(loaded.count(), grid.count());
+
+    for x in 0..17 {
+        for y in 0..23 {
+            assert_eq!(loaded.cell((x, y)), grid.cell((x, y)));
+        }
+    }
+
+    assert_eq!(loaded.in_circle((10.0, 10.0), 20.0), grid.in_circle((10.0, 10.0), 20.0));
+}
+
+#[test]
+fn quad_tree_round_trip() {
+    let mut quad_tree = QuadTree::with_capacity(-33.3..66.7, -12.1..130.8, 7);
+    fill(&mut quad_tree);
+
+    let loaded: QuadTree<u32> = serde_json::from_str(&serde_json::to_string(&quad_tree).unwrap()).unwrap();
+
+    assert_eq!(loaded.capacity(), quad_tree.capacity());
+    assert_eq!(loaded.bounds(), quad_tree.bounds());
+    assert_eq!(loaded.count(), quad_tree.count());
+
+    assert_same_node(loaded.node(), quad_tree.node());
+
+    assert_eq!(loaded.in_circle((10.0, 10.0), 20.0), quad_tree.in_circle((10.0, 10.0), 20.0));
+}
+
+fn assert_round_trip_after_churn<P: SpatialIndexMut<u32> + Serialize + DeserializeOwned>(mut partitioner: P) {
+    let handles = churn(&mut partitioner);
+    let mut loaded = round_trip(&partitioner);
+
+    assert_eq!(loaded.count(), partitioner.count());
+
+    for shape in [Circle::new((10.0, 10.0), 20.0), Circle::new((-50.0, 20.0), 1.0), Circle::new((0.0, 0.0), 1000.0)] {
+        assert_eq!(sorted(loaded.query(&shape)), sorted(partitioner.query(&shape)));
+    }
+
+    // the handles stay valid and the loaded structure keeps working
+    for handle in &handles {
+        assert_eq!(loaded.get(*handle), partitioner.get(*handle));
+    }
+
+    loaded.insert((1.0, 1.0), 6000);
+
+    for handle in handles {
+        assert!(loaded.remove(handle).is_some());
+    }
+
+    assert_eq!(loaded.in_circle((1.0, 1.0), 0.5), vec![6000]);
+    assert_eq!(loaded.count(), 1);
+}
+
+#[test]
+fn round_trip_after_removals_and_elements_outside_of_the_bounds() {
+    assert_round_trip_after_churn(Grid::with_cell_count(-33.3..66.7, -12.1..130.8, (17, 23)));
+    assert_round_trip_after_churn(QuadTree::with_capacity(-64.0..192.0, -64.0..192.0, 7));
+    assert_round_trip_after_churn(QuadTree::builder().bounds(-64.0..192.0, -64.0..192.0).capacity(4).max_depth(2).build().unwrap());
+    assert_round_trip_after_churn(LinearQuadTree::new(-33.3..66.7, -12.1..130.8));
+    assert_round_trip_after_churn(KdTree::new(-33.3..66.7, -12.1..130.8));
+    assert_round_trip_after_churn(BruteForce::new());
+}
+
+#[test]
+fn static_grid_round_trip() {
+    let mut grid = Grid::with_cell_count(-33.3..66.7, -12.1..130.8, (17, 23));
+    churn(&mut grid);
+
+    let static_grid = StaticGrid::from(&grid);
+    let loaded = round_trip(&static_grid);
+
+    assert_eq!(loaded.count(), static_grid.count());
+    assert_eq!(loaded.cell((3, 4)), static_grid.cell((3, 4)));
+    assert_eq!(sorted(loaded.in_circle((-50.0, 20.0), 1.0)), vec![5000]);
+    assert_eq!(sorted(loaded.in_circle((10.0, 10.0), 20.0)), sorted(static_grid.in_circle((10.0, 10.0), 20.0)));
+}
+
+#[test]
+fn box_structures_round_trip() {
+    let boxes: Vec<_> = (0..1_000u32).map(|i| {
+        let min = ((i * 7919 % 1000) as f64 / 10.0 - 50.0, (i * 104_729 % 997) as f64 / 10.0 - 50.0);
+
+        ((min, (min.0 + (i % 13) as f64 / 4.0, min.1 + (i % 7) as f64 / 3.0)), i)
+    }).collect();
+
+    let mut bvh = Bvh::new(0.5);
+    let mut r_tree = RTree::new();
+    let mut handles = Vec::new();
+
+    for (bounds, data) in &boxes {
+        handles.push(bvh.insert(bounds.0, bounds.1, *data));
+        r_tree.insert(bounds.0, bounds.1, *data);
+    }
+
+    for (i, ((bounds, data), handle)) in boxes.iter().zip(&handles).enumerate() {
+        match i % 3 {
+            0 => {
+                bvh.remove(*handle);
+                r_tree.remove(bounds.0, bounds.1, *data);
+            }
+            1 => {
+                bvh.move_box(*handle, (bounds.0.0 + 3.0, bounds.0.1), (bounds.1.0 + 3.0, bounds.1.1));
+            }
+            _ => {}
+        }
+    }
+
+    let mut loaded_bvh = round_trip(&bvh);
+    let loaded_r_tree = round_trip(&r_tree);
+    let shape = Rect::new(-20.0..10.0, 0.0..30.0);
+
+    assert_eq!(sorted(loaded_bvh.query_intersecting(&shape)), sorted(bvh.query_intersecting(&shape)));
+    assert_eq!(sorted(loaded_r_tree.query_intersecting(&shape)), sorted(r_tree.query_intersecting(&shape)));
+    assert_eq!(loaded_r_tree.count(), r_tree.count());
+
+    for handle in handles.iter().skip(1).step_by(3) {
+        assert!(loaded_bvh.remove(*handle).is_some());
+    }
+
+    assert_eq!(loaded_bvh.count(), bvh.count() - handles.iter().skip(1).step_by(3).count());
+}
+
+#[test]
+fn corrupted_snapshots_are_rejected() {
+    let mut grid = Grid::with_cell_count(-33.3..66.7, -12.1..130.8, (17, 23));
+    churn(&mut grid);
+
+    assert!(rejected(&grid, |snapshot| snapshot["count"] = 7.into()).contains("counts do not match the elements"));
+    assert!(rejected(&grid, |snapshot| snapshot["cell_count"] = serde_json::json!([17, 22])).contains("count of cells does not match"));
+    assert!(rejected(&grid, |snapshot| {
+        snapshot["cells"].as_array_mut().unwrap().swap(0, 200);
+        snapshot["handles"].as_array_mut().unwrap().swap(0, 200);
+    }).contains("element lies in the wrong cell"));
+
+    let mut quad_tree = QuadTree::with_capacity(-64.0..192.0, -64.0..192.0, 7);
+    churn(&mut quad_tree);
+
+    assert!(rejected(&quad_tree, |snapshot| snapshot["nodes"][0]["children"] = 100_000.into()).contains("node points to invalid children"));
+    assert!(rejected(&quad_tree, |snapshot| snapshot["nodes"][5]["first"] = snapshot["nodes"][6]["first"].clone()).contains("blocks overlap"));
+    assert!(rejected(&quad_tree, |snapshot| snapshot["slots"]["free"].as_array_mut().unwrap().push(0.into())).contains("free slot list is not valid"));
+    assert!(rejected(&quad_tree, |snapshot| snapshot["handles"].as_array_mut().unwrap().swap(0, 1)).contains("slot does not point to its element"));
+
+    let mut linear_quad_tree = LinearQuadTree::new(-33.3..66.7, -12.1..130.8);
+    churn(&mut linear_quad_tree);
+
+    assert!(rejected(&linear_quad_tree, |snapshot| snapshot["codes"].as_array_mut().unwrap().swap(0, 100)).contains("codes are not the sorted codes"));
+
+    let mut kd_tree = KdTree::new(-33.3..66.7, -12.1..130.8);
+    churn(&mut kd_tree);
+    kd_tree.rebuild();
+
+    assert!(rejected(&kd_tree, |snapshot| snapshot["removed_count"] = 3.into()).contains("removed_count does not match"));
+    assert!(rejected(&kd_tree, |snapshot| snapshot["elements"].as_array_mut().unwrap().swap(0, 1000)).contains("element lies outside of its subtree"));
+
+    let static_grid = StaticGrid::from(&grid);
+
+    assert!(rejected(&static_grid, |snapshot| snapshot["offsets"][1] = 100_000.into()).contains("offsets do not split the elements"));
+
+    let mut bvh = Bvh::new(0.5);
+    (0..20).for_each(|i| { bvh.insert((i as f64, 0.0), (i as f64 + 1.0, 1.0), i); });
+
+    assert!(rejected(&bvh, |snapshot| snapshot["root"] = 1000.into()).contains("node points to invalid children"));
+    assert!(rejected(&bvh, |snapshot| snapshot["count"] = 19.into()).contains("count does not match the leaves"));
+
+    let mut r_tree = RTree::new();
+    (0..20).for_each(|i| r_tree.insert((i as f64, 0.0), (i as f64 + 1.0, 1.0), i));
+
+    assert!(rejected(&r_tree, |snapshot| snapshot["count"] = 21.into()).contains("count does not match the boxes"));
+}
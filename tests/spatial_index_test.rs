@@ -0,0 +1,433 @@
+use std::ops::Range;
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::quad_tree::QuadTree;
+use spatial_neighbors::frozen::{FrozenGrid, FrozenQuadTree};
+use spatial_neighbors::handle::Handle;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::kd_tree::KdTree;
+use spatial_neighbors::linear_quad_tree::LinearQuadTree;
+use spatial_neighbors::metric::{Chebyshev, Euclidean, Manhattan, Metric, Weighted};
+use spatial_neighbors::shape::Ring;
+use spatial_neighbors::static_grid::StaticGrid;
+
+fn elements(x: (i32, i32), y: (i32, i32)) -> Vec<((f64, f64), i32)> {
+    let mut elements = Vec::new();
+
+    for x in x.0..x.1 {
+        for y in y.0..y.1 {
+            if ((x + 50) * 100 + y) % 3 == 0 || ((x + 50) * 100 + y) % 4 == 0 {
+                continue;
+            }
+
+            elements.push(((x as f64, y as f64), (x + 50) * 100 + y));
+        }
+    }
+
+    elements
+}
+
+fn fill_spatial_partitioner(spatial_hash: &mut Grid<i32>, quad_tree: &mut QuadTree<i32>, x: (i32, i32), y: (i32, i32)) {
+    for (position, data) in elements(x, y) {
+        spatial_hash.insert(position, data);
+        quad_tree.insert(position, data);
+    }
+}
+
+#[test]
+fn in_circle_different_cell_counts() {
+    let cell_count = [4, 7, 100, 1000];
+
+    for cell_count in cell_count {
+        let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (cell_count, cell_count / 2));
+        let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, cell_count as u16);
+
+        fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+        assert_eq!(spatial_hash.in_circle((0.0, 0.0), 10.0).len(), quad_tree.in_circle((0.0, 0.0), 10.0).len());
+    }
+}
+
+#[test]
+fn in_circle_different_location() {
+    let location = [(0.0, 0.0), (-10.0, 0.0), (-20.0, -20.0), (15.0, 10.0), (-20.0, 10.0), (15.0, 10.0)];
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (500, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    for location in location {
+        assert_eq!(spatial_hash.in_circle(location, 2.0).len(), quad_tree.in_circle(location, 2.0).len());
+    }
+}
+
+#[test]
+fn in_circle_different_radius() {
+    let sizes = [1, 7, 100, 200];
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    for size in sizes {
+        assert_eq!(spatial_hash.in_circle((0.0, 0.0), size as f64).len(), quad_tree.in_circle((0.0, 0.0), size as f64).len());
+    }
+}
+
+#[test]
+fn in_circle_different_size() {
+    let sizes = [((-50, 50), (10, 100)), ((50, 100), (-10, 10)), ((0, 50), (-100, -10))];
+
+    for size in sizes {
+        let mut spatial_hash = Grid::with_cell_count(size.0.0 as f64..size.0.1 as f64, size.1.0 as f64..size.1.1 as f64, (50, 50));
+        let mut quad_tree = QuadTree::with_capacity(size.0.0 as f64..size.0.1 as f64, size.1.0 as f64..size.1.1 as f64, 4);
+
+        fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, size.0, size.1);
+
+        assert_eq!(spatial_hash.in_circle((0.0, 0.0), 10.0).len(), quad_tree.in_circle((0.0, 0.0), 10.0).len());
+    }
+}
+
+#[test]
+fn in_circle_on_borders() {
+    let location = [(-49.5, 0.0), (48.3, -49.9), (12.0, 49.7), (49.0, 49.0), (-49.0, -49.0)];
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    for location in location {
+        assert_eq!(spatial_hash.in_circle(location, 2.0).len(), quad_tree.in_circle(location, 2.0).len());
+    }
+}
+
+#[test]
+fn in_ring_different_radius() {
+    let sizes = [(0.5, 5.0), (2.5, 7.0), (5.0, 13.0), (10.5, 25.0), (10.0, 25.0), (30.5, 200.0)];
+    let location = [(0.0, 0.0), (-20.0, 10.0), (49.0, 49.0)];
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    let distance_squared = |position: (f64, f64), location: (f64, f64)| (position.0 - location.0).powi(2) + (position.1 - location.1).powi(2);
+
+    for location in location {
+        for (inner, outer) in sizes {
+            // elements closer than inner are excluded, the ones exactly on inner or outer are part of the ring
+            let mut expected: Vec<i32> = elements((-50, 50), (-50, 50)).into_iter()
+                .filter(|(position, _)| (inner * inner..=outer * outer).contains(&distance_squared(*position, location)))
+                .map(|(_, data)| data)
+                .collect();
+
+            let mut in_ring = spatial_hash.in_ring(location, inner, outer);
+
+            expected.sort();
+            in_ring.sort();
+
+            assert_eq!(in_ring, expected);
+            assert_eq!(in_ring.len(), quad_tree.in_ring(location, inner, outer).len());
+        }
+    }
+}
+
+#[test]
+fn in_ring_contains_both_radii() {
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    // e.g. (0, 5) and (-4, 3) lie exactly on the inner radius, (-12, 5) and (0, -13) on the outer one
+    let on_radius = |radius: f64| -> Vec<i32> {
+        elements((-50, 50), (-50, 50)).into_iter()
+            .filter(|(position, _)| position.0 * position.0 + position.1 * position.1 == radius * radius)
+            .map(|(_, data)| data)
+            .collect()
+    };
+
+    let (on_inner, on_outer) = (on_radius(5.0), on_radius(13.0));
+
+    assert!(!on_inner.is_empty() && !on_outer.is_empty());
+
+    for in_ring in [spatial_hash.in_ring((0.0, 0.0), 5.0, 13.0), quad_tree.in_ring((0.0, 0.0), 5.0, 13.0)] {
+        for data in on_inner.iter().chain(&on_outer) {
+            assert!(in_ring.contains(data));
+        }
+    }
+
+    // with the same inner and outer radius only the elements on the circle line are left
+    for mut in_ring in [spatial_hash.in_ring((0.0, 0.0), 5.0, 5.0), quad_tree.in_ring((0.0, 0.0), 5.0, 5.0)] {
+        in_ring.sort();
+
+        assert_eq!(in_ring, on_inner);
+    }
+}
+
+#[test]
+fn in_rect_different_location() {
+    let rects: [(Range<f64>, Range<f64>); 5] = [(-10.0..10.0, -10.0..10.0), (-50.0..-40.0, 30.0..50.0), (-0.5..0.5, -100.0..100.0), (20.0..20.0, 0.0..10.0), (-200.0..200.0, -200.0..200.0)];
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    for (x, y) in rects {
+        let expected = (x.start.ceil() as i32..x.end.ceil() as i32).flat_map(|x| (y.start.ceil() as i32..y.end.ceil() as i32).map(move |y| (x, y)))
+            .filter(|(x, y)| (-50..50).contains(x) && (-50..50).contains(y))
+            .filter(|(x, y)| ((x + 50) * 100 + y) % 3 != 0 && ((x + 50) * 100 + y) % 4 != 0)
+            .count();
+
+        assert_eq!(spatial_hash.in_rect(x.clone(), y.clone()).len(), expected);
+        assert_eq!(quad_tree.in_rect(x.clone(), y.clone()).len(), expected);
+        assert_eq!(spatial_hash.count_in_rect(x.clone(), y.clone()), expected);
+        assert_eq!(quad_tree.count_in_rect(x, y), expected);
+    }
+}
+
+#[test]
+fn count_and_any_in_circle() {
+    let location = [(0.0, 0.0), (-20.0, -20.0), (49.0, 49.0), (-49.5, 0.0), (300.0, 300.0)];
+    let sizes = [0.5, 1.0, 7.0, 100.0];
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    for location in location {
+        for size in sizes {
+            let expected = spatial_hash.in_circle(location, size).len();
+
+            assert_eq!(spatial_hash.count_in_circle(location, size), expected);
+            assert_eq!(quad_tree.count_in_circle(location, size), expected);
+            assert_eq!(spatial_hash.any_in_circle(location, size), expected > 0);
+            assert_eq!(quad_tree.any_in_circle(location, size), expected > 0);
+        }
+    }
+}
+
+#[test]
+fn in_circle_different_metrics() {
+    let location = [(0.0, 0.0), (-20.5, 10.0), (49.0, -49.0)];
+    let sizes = [1.0, 7.5, 30.0];
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+    let kd_tree = KdTree::from_elements(-50.0..50.0, -50.0..50.0, &elements((-50, 50), (-50, 50)));
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    for location in location {
+        for size in sizes {
+            assert_metric(&spatial_hash, &quad_tree, &kd_tree, location, size, Euclidean);
+            assert_metric(&spatial_hash, &quad_tree, &kd_tree, location, size, Manhattan);
+            assert_metric(&spatial_hash, &quad_tree, &kd_tree, location, size, Chebyshev);
+            assert_metric(&spatial_hash, &quad_tree, &kd_tree, location, size, Weighted::new(4.0, 0.25));
+        }
+    }
+}
+
+#[test]
+fn negative_radius_contains_nothing() {
+    fn assert_empty<M: Metric + Copy>(metric: M) {
+        for radius in [-0.5, -30.0, f64::NEG_INFINITY, f64::NAN] {
+            assert!(!metric.in_range((0.0, 0.0), radius));
+            assert!(!metric.in_range((0.25, -0.25), radius));
+            assert!(!metric.in_range_exclusive((0.0, 0.0), radius));
+        }
+
+        assert!(metric.in_range((0.0, 0.0), 0.0));
+    }
+
+    assert_empty(Euclidean);
+    assert_empty(Manhattan);
+    assert_empty(Chebyshev);
+    assert_empty(Weighted::new(4.0, 0.25));
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+    let kd_tree = KdTree::from_elements(-50.0..50.0, -50.0..50.0, &elements((-50, 50), (-50, 50)));
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    assert_metric(&spatial_hash, &quad_tree, &kd_tree, (0.0, 0.0), -30.0, Euclidean);
+    assert_metric(&spatial_hash, &quad_tree, &kd_tree, (0.0, 0.0), -30.0, Weighted::new(4.0, 0.25));
+}
+
+fn assert_metric<M: Metric + Copy>(spatial_hash: &Grid<i32>, quad_tree: &QuadTree<i32>, kd_tree: &KdTree<i32>, location: (f64, f64), radius: f64, metric: M) {
+    let mut expected: Vec<i32> = elements((-50, 50), (-50, 50)).into_iter()
+        .filter(|(position, _)| metric.distance(((position.0 - location.0).abs(), (position.1 - location.1).abs())) <= radius)
+        .map(|(_, data)| data)
+        .collect();
+
+    let mut in_grid = spatial_hash.in_circle_with(location, radius, metric);
+    let mut in_tree = quad_tree.in_circle_with(location, radius, metric);
+    let mut in_kd_tree = kd_tree.in_circle_with(location, radius, metric);
+
+    expected.sort();
+    in_grid.sort();
+    in_tree.sort();
+    in_kd_tree.sort();
+
+    assert_eq!(in_grid, expected);
+    assert_eq!(in_tree, expected);
+    assert_eq!(in_kd_tree, expected);
+}
+
+#[test]
+fn count_and_clear() {
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+    
+    assert_eq!(spatial_hash.count(), quad_tree.count());
+
+    spatial_hash.clear();
+    quad_tree.clear();
+
+    assert_eq!(spatial_hash.count(), quad_tree.count());
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    assert_eq!(spatial_hash.count(), quad_tree.count());
+}
+#[test]
+fn kd_tree_matches_grid_and_quad_tree() {
+    let location = [(0.0, 0.0), (-20.0, -20.0), (49.0, 49.0), (-49.5, 0.0), (12.3, -7.7), (300.0, 300.0)];
+    let sizes = [0.5, 1.0, 7.0, 23.5, 100.0];
+
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (50, 50));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 4);
+    let mut kd_tree = KdTree::new(-50.0..50.0, -50.0..50.0);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    // inserted one by one so the tree gets rebuilt a few times and still has pending elements
+    for (position, data) in elements((-50, 50), (-50, 50)) {
+        kd_tree.insert(position, data);
+    }
+
+    assert_eq!(kd_tree.count(), spatial_hash.count());
+
+    for location in location {
+        for size in sizes {
+            let mut expected = spatial_hash.in_circle(location, size);
+            let mut in_tree = quad_tree.in_circle(location, size);
+            let mut in_kd_tree = kd_tree.in_circle(location, size);
+
+            expected.sort();
+            in_tree.sort();
+            in_kd_tree.sort();
+
+            assert_eq!(in_tree, expected);
+            assert_eq!(in_kd_tree, expected);
+
+            assert_eq!(kd_tree.count_in_circle(location, size), expected.len());
+            assert_eq!(kd_tree.any_in_circle(location, size), !expected.is_empty());
+            assert_eq!(kd_tree.query_count(&Ring::new(location, size / 2.0, size)), quad_tree.query_count(&Ring::new(location, size / 2.0, size)));
+            assert_eq!(kd_tree.count_in_rect(location.0 - size..location.0, location.1..location.1 + size), spatial_hash.count_in_rect(location.0 - size..location.0, location.1..location.1 + size));
+        }
+    }
+}
+
+/// only needs the query side, so read-only structures can be passed as well
+fn sorted_in_circle(index: &impl SpatialIndex<i32>, location: (f64, f64), size: f64) -> Vec<i32> {
+    let mut data = index.in_circle(location, size);
+    data.sort();
+
+    data
+}
+
+#[test]
+fn read_only_structures_implement_spatial_index() {
+    let mut spatial_hash = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (20, 20));
+    let mut quad_tree = QuadTree::with_capacity(-50.0..50.0, -50.0..50.0, 8);
+
+    fill_spatial_partitioner(&mut spatial_hash, &mut quad_tree, (-50, 50), (-50, 50));
+
+    let static_grid = StaticGrid::from(&spatial_hash);
+    let bytes = spatial_hash.to_frozen_bytes();
+    let frozen_grid = FrozenGrid::<i32>::from_bytes(&bytes).unwrap();
+
+    assert_eq!(static_grid.count(), spatial_hash.count());
+    assert_eq!(frozen_grid.count(), spatial_hash.count());
+
+    for location in [(0.0, 0.0), (-33.3, 12.5), (49.0, 49.0)] {
+        for size in [0.5, 4.0, 17.0] {
+            let expected = sorted_in_circle(&spatial_hash, location, size);
+
+            assert_eq!(sorted_in_circle(&quad_tree, location, size), expected);
+            assert_eq!(sorted_in_circle(&static_grid, location, size), expected);
+            assert_eq!(sorted_in_circle(&frozen_grid, location, size), expected);
+        }
+    }
+}
+
+/// fills the bounds 0..10 and puts two elements outside of them, returns the handle of the one at (50, 50)
+fn fill_outside_of_the_bounds(index: &mut impl SpatialIndexMut<i32>) -> Handle {
+    for x in 0..10 {
+        for y in 0..10 {
+            index.insert((x as f64 + 0.5, y as f64 + 0.5), x * 10 + y);
+        }
+    }
+
+    index.insert_unchecked((-3.0, 5.5), 1001);
+    index.insert_unchecked((50.0, 50.0), 1000)
+}
+
+fn assert_found_outside_of_the_bounds(index: &impl SpatialIndex<i32>) {
+    assert!(!index.in_circle((5.0, 5.0), 2.0).contains(&1000));
+    assert_eq!(index.count_in_circle((5.0, 5.0), 2.0), 12);
+    assert_eq!(index.in_circle((50.0, 50.0), 1.0), vec![1000]);
+    assert_eq!(index.count_in_circle((50.0, 50.0), 1.0), 1);
+    assert!(index.any_in_circle((50.0, 50.0), 1.0));
+    assert_eq!(index.in_circle((-3.0, 5.0), 1.0), vec![1001]);
+    assert_eq!(index.count_in_circle((5.0, 5.0), 100.0), 102);
+
+    // the box of the whole bounds lies inside of the circle but the element outside does not
+    assert_eq!(index.count_in_circle((5.0, 5.0), 10.0), 101);
+}
+
+fn assert_outside_of_the_bounds(index: &mut impl SpatialIndexMut<i32>) {
+    let handle = fill_outside_of_the_bounds(index);
+
+    assert_found_outside_of_the_bounds(index);
+
+    assert_eq!(index.remove(handle), Some(1000));
+
+    assert!(!index.any_in_circle((50.0, 50.0), 1.0));
+    assert_eq!(index.count_in_circle((5.0, 5.0), 100.0), 101);
+    assert_eq!(index.in_circle((-3.0, 5.0), 1.0), vec![1001]);
+}
+
+#[test]
+fn elements_outside_of_the_bounds_are_found() {
+    assert_outside_of_the_bounds(&mut Grid::with_cell_count(0.0..10.0, 0.0..10.0, (2, 2)));
+    assert_outside_of_the_bounds(&mut QuadTree::with_capacity(0.0..10.0, 0.0..10.0, 4));
+    assert_outside_of_the_bounds(&mut LinearQuadTree::new(0.0..10.0, 0.0..10.0));
+    assert_outside_of_the_bounds(&mut KdTree::new(0.0..10.0, 0.0..10.0));
+
+    // the elements outside of the bounds have to be moved from the pending elements into the tree
+    let mut kd_tree = KdTree::new(0.0..10.0, 0.0..10.0);
+    fill_outside_of_the_bounds(&mut kd_tree);
+    kd_tree.rebuild();
+
+    assert_found_outside_of_the_bounds(&kd_tree);
+    assert_eq!(kd_tree.nearest((49.0, 49.0)), Some(1000));
+
+    let mut quad_tree = QuadTree::with_capacity(0.0..10.0, 0.0..10.0, 4);
+    fill_outside_of_the_bounds(&mut quad_tree);
+
+    let bytes = quad_tree.to_frozen_bytes();
+    let frozen_quad_tree = FrozenQuadTree::<i32>::from_bytes(&bytes).unwrap();
+
+    assert_found_outside_of_the_bounds(&frozen_quad_tree);
+    assert_eq!(frozen_quad_tree.nearest((49.0, 49.0)), Some(1000));
+    assert_eq!(frozen_quad_tree.nearest((-2.0, 5.5)), Some(1001));
+}
@@ -0,0 +1,107 @@
+use std::ops::ControlFlow;
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::metric::{Chebyshev, Manhattan, Weighted};
+use spatial_neighbors::shape::{Circle, Rect, Ring, Shape};
+use spatial_neighbors::static_grid::StaticGrid;
+
+fn elements() -> Vec<((f64, f64), u32)> {
+    (0..3_000u32).map(|i| (((i * 7919 % 1000) as f64 / 10.0 - 50.0, (i * 104_729 % 997) as f64 / 10.0 - 50.0), i)).collect()
+}
+
+const LOCATIONS: [(f64, f64); 5] = [(0.0, 0.0), (-49.0, -49.0), (12.3, 45.6), (49.9, -20.0), (-80.0, 10.0)];
+
+#[test]
+fn static_grid_matches_grid() {
+    let elements = elements();
+
+    for cell_count in [(1, 1), (7, 3), (40, 40)] {
+        let mut grid = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, cell_count);
+        elements.iter().for_each(|(position, data)| { grid.insert(*position, *data); });
+
+        let static_grid = StaticGrid::from_elements(-50.0..50.0, -50.0..50.0, cell_count, &elements);
+
+        assert_eq!(static_grid.count(), grid.count());
+
+        for y in 0..cell_count.1 {
+            for x in 0..cell_count.0 {
+                let (xs, ys, data) = static_grid.cell((x, y));
+                let expected = grid.cell((x, y));
+
+                assert_eq!(xs.iter().zip(ys).map(|(x, y)| (*x, *y)).zip(data.iter().copied()).collect::<Vec<_>>(), *expected);
+            }
+        }
+
+        for location in LOCATIONS {
+            for radius in [0.5, 5.0, 23.7, 200.0] {
+                assert_eq!(static_grid.in_circle(location, radius), grid.in_circle(location, radius));
+                assert_eq!(static_grid.query_count(&Ring::new(location, radius / 2.0, radius)), grid.query_count(&Ring::new(location, radius / 2.0, radius)));
+                assert_eq!(static_grid.query_any(&Circle::new(location, radius)), grid.any_in_circle(location, radius));
+            }
+
+            let rect = Rect::new(location.0 - 10.0..location.0 + 3.0, location.1 - 2.0..location.1 + 20.0);
+            assert_eq!(static_grid.query(&rect), grid.query(&rect));
+        }
+    }
+}
+
+#[test]
+fn static_grid_from_grid() {
+    let mut grid = Grid::with_cell_count(-50.0..50.0, -50.0..50.0, (13, 9));
+    elements().iter().for_each(|(position, data)| { grid.insert(*position, *data); });
+
+    let static_grid = StaticGrid::from(&grid);
+
+    assert_eq!(static_grid.count(), grid.count());
+    assert_eq!(static_grid.cell_size(), grid.cell_size());
+
+    for location in LOCATIONS {
+        assert_eq!(static_grid.in_circle(location, 17.0), grid.in_circle(location, 17.0));
+    }
+}
+
+#[test]
+#[should_panic]
+fn static_grid_out_of_bounds() {
+    StaticGrid::from_elements(0.0..10.0, 0.0..10.0, (2, 2), &[((10.0, 5.0), 1)]);
+}
+
+fn assert_filter_matches_contains<S: Shape>(shape: &S, xs: &[f64], ys: &[f64]) {
+    for len in 0..xs.len() {
+        let mut found = Vec::new();
+
+        let _ = shape.filter(&xs[..len], &ys[..len], &mut |i| {
+            found.push(i);
+            ControlFlow::Continue(())
+        });
+
+        let expected: Vec<usize> = (0..len).filter(|i| shape.contains((xs[*i], ys[*i]))).collect();
+
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn filter_matches_contains() {
+    // positions right on and next to the border of the shapes
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+
+    for i in 0..37 {
+        let angle = i as f64 * 0.37;
+        let radius = [3.0, f64::next_up(3.0), f64::next_down(3.0), 0.1, 1.5][i % 5];
+
+        xs.push(1.0 + angle.cos() * radius);
+        ys.push(-2.0 + angle.sin() * radius);
+    }
+
+    xs.extend([4.0, 1.0, -2.0, 1.0]);
+    ys.extend([-2.0, 1.0, -2.0, -5.0]);
+
+    assert_filter_matches_contains(&Circle::new((1.0, -2.0), 3.0), &xs, &ys);
+    assert_filter_matches_contains(&Circle::with_metric((1.0, -2.0), 3.0, Manhattan), &xs, &ys);
+    assert_filter_matches_contains(&Circle::with_metric((1.0, -2.0), 3.0, Chebyshev), &xs, &ys);
+    assert_filter_matches_contains(&Circle::with_metric((1.0, -2.0), 3.0, Weighted::new(0.3, 2.0)), &xs, &ys);
+    assert_filter_matches_contains(&Ring::new((1.0, -2.0), 1.5, 3.0), &xs, &ys);
+    assert_filter_matches_contains(&Rect::new(-2.0..1.0, -5.0..-2.0), &xs, &ys);
+}
@@ -0,0 +1,143 @@
+use spatial_neighbors::{SpatialIndex, SpatialIndexMut};
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::quad_tree::QuadTree;
+use spatial_neighbors::stats::DepthStats;
+
+#[test]
+fn grid_stats() {
+    let mut grid = Grid::with_cell_count(0.0..4.0, 0.0..2.0, (4, 2));
+
+    // loads of 1, 2, 3 and 6 in four cells
+    for (i, load) in [1, 2, 3, 6].into_iter().enumerate() {
+        for _ in 0..load {
+            grid.insert((i as f64 + 0.5, 0.5), i);
+        }
+    }
+
+    let stats = grid.stats();
+
+    assert_eq!(stats.cell_count, (4, 2));
+    assert_eq!(stats.count, 12);
+    assert_eq!(stats.outside, 0);
+    assert_eq!(stats.occupancy, vec![4, 1, 2, 1]);
+    assert_eq!(stats.empty_cells, 4);
+    assert_eq!(stats.max_load, 6);
+    assert_eq!(stats.empty_ratio(), 0.5);
+    assert_eq!(stats.mean_load(), 3.0);
+
+    grid.insert_unchecked((-5.0, 0.5), 10);
+
+    assert_eq!(grid.stats().outside, 1);
+    assert_eq!(grid.stats().to_string(), "\
+Grid with 13 elements in 4 x 2 cells, 1 of them outside of the bounds
+empty cells: 4 of 8 (50.0%)
+load: at most 6, 3.2 on average in the cells which are not empty
+elements           cells
+0                      4
+1                      0
+2..3                   3
+4..7                   1
+");
+}
+
+#[test]
+fn empty_grid_stats() {
+    let stats = Grid::<u32>::with_cell_count(0.0..1.0, 0.0..1.0, (3, 3)).stats();
+
+    assert_eq!(stats.occupancy, vec![9]);
+    assert_eq!(stats.max_load, 0);
+    assert_eq!(stats.empty_ratio(), 1.0);
+    assert_eq!(stats.mean_load(), 0.0);
+}
+
+#[test]
+fn quad_tree_stats() {
+    let mut quad_tree = QuadTree::with_capacity(0.0..4.0, 0.0..4.0, 2);
+
+    // the first two elements fill the root, which splits and keeps them
+    quad_tree.insert((1.0, 1.0), 0);
+    quad_tree.insert((3.0, 3.0), 1);
+    quad_tree.insert((1.0, 3.0), 2);
+
+    let stats = quad_tree.stats();
+
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.node_count, 5);
+    assert_eq!(stats.leaf_count, 4);
+    assert_eq!(stats.empty_leaves, 3);
+    assert_eq!(stats.depths, vec![
+        DepthStats { nodes: 1, leaves: 0, elements: 2 },
+        DepthStats { nodes: 4, leaves: 4, elements: 1 },
+    ]);
+    assert_eq!(stats.internal_with_elements, 1);
+    assert_eq!(stats.internal_elements, 2);
+    assert_eq!(stats.max_len, 2);
+    assert_eq!(stats.leaf_fill, 0.125);
+
+    assert_eq!(stats.to_string(), "\
+QuadTree with 3 elements in 5 nodes, 4 of them leaves, capacity 2
+leaves: 3 empty, filled to 12.5% of the capacity on average, at most 2 elements in one node
+internal nodes holding elements: 1 with 2 elements
+depth        nodes    leaves  elements
+0                1         0         2
+1                4         4         1
+");
+}
+
+#[test]
+fn quad_tree_stats_with_max_depth() {
+    let mut quad_tree = QuadTree::builder().bounds(0.0..4.0, 0.0..4.0).capacity(2).max_depth(2).build().unwrap();
+
+    for i in 0..20 {
+        quad_tree.insert((0.5, 0.5), i);
+    }
+
+    let stats = quad_tree.stats();
+
+    assert_eq!(stats.depths.len(), 3);
+    assert_eq!(stats.max_len, 16);
+    assert_eq!(stats.internal_elements, 4);
+    assert!(stats.to_string().starts_with("QuadTree with 20 elements in 9 nodes, 7 of them leaves, capacity 2, max_depth 2\n"));
+}
+
+#[test]
+fn quad_tree_slots_stay_bounded_under_churn() {
+    let mut quad_tree = QuadTree::builder().bounds(0.0..16.0, 0.0..16.0).capacity(4).max_depth(2).build().unwrap();
+    let mut slots = Vec::new();
+
+    // a cluster of elements moves through all leaves, each leaf grows its block and gets empty again
+    for round in 0..160 {
+        let spot = ((round % 4) as f64 * 4.0 + 2.0, ((round / 4) % 4) as f64 * 4.0 + 2.0);
+        let handles: Vec<_> = (0..100).map(|i| quad_tree.insert((spot.0 + (i % 10) as f64 * 0.1, spot.1 + (i / 10) as f64 * 0.1), i)).collect();
+
+        assert_eq!(quad_tree.count_in_circle(spot, 2.0), 100);
+
+        for handle in handles {
+            assert!(quad_tree.remove(handle).is_some());
+        }
+
+        slots.push(quad_tree.stats().slots);
+    }
+
+    assert_eq!(quad_tree.count(), 0);
+    assert!(slots[159] <= slots[0], "{} slots after the first round, {} after the last", slots[0], slots[159]);
+}
+
+#[test]
+fn quad_tree_splits_bounds_which_are_not_powers_of_two() {
+    let mut quad_tree = QuadTree::with_capacity(-33.3..66.7, -12.1..130.8, 4);
+    let positions: Vec<_> = (0..1000u32).map(|i| ((i * 7919 % 1000) as f64 / 10.0 - 33.3, (i * 104_729 % 1000) as f64 / 7.0 - 12.1)).collect();
+
+    for (i, position) in positions.iter().enumerate() {
+        quad_tree.insert(*position, i);
+    }
+
+    let stats = quad_tree.stats();
+
+    // the centers of the nodes are rounded, the nodes split all the same
+    assert!(stats.depths.len() > 4);
+    assert_eq!(stats.max_len, 4);
+
+    let inside = |position: &&(f64, f64)| (position.0 - 10.0).powi(2) + (position.1 - 50.0).powi(2) <= 30.0 * 30.0;
+    assert_eq!(quad_tree.count_in_circle((10.0, 50.0), 30.0), positions.iter().filter(inside).count());
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "proptest")]
+
+use proptest::prelude::*;
+use spatial_neighbors::SpatialIndexMut;
+use spatial_neighbors::brute_force::BruteForce;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::kd_tree::KdTree;
+use spatial_neighbors::linear_quad_tree::LinearQuadTree;
+use spatial_neighbors::quad_tree::QuadTree;
+use spatial_neighbors::strategy::{check, position, query, workload};
+use spatial_neighbors::testing::{Operation, Query};
+
+proptest! {
+    #[test]
+    fn positions_lie_inside_of_the_bounds((x, y, position) in spatial_neighbors::strategy::bounds().prop_flat_map(|(x, y)| {
+        (Just(x.clone()), Just(y.clone()), position(x, y))
+    })) {
+        prop_assert!(x.contains(&position.0) && y.contains(&position.1));
+    }
+
+    #[test]
+    fn queries_are_not_reversed(query in query(-5.0..5.0, 0.0..1.0)) {
+        match query {
+            Query::Circle(_, radius) => prop_assert!(radius >= 0.0),
+            Query::Ring(_, inner, outer) => prop_assert!(0.0 <= inner && inner <= outer),
+            Query::Rect(x, y) => prop_assert!(x.start <= x.end && y.start <= y.end),
+        }
+    }
+
+    #[test]
+    fn grid_matches_brute_force(workload in workload(0..200), cell_count in (1..30usize, 1..30usize)) {
+        let mut grid = Grid::with_cell_count(workload.x.clone(), workload.y.clone(), cell_count);
+
+        check(&mut grid, &mut BruteForce::new(), &workload.operations)?;
+    }
+
+    #[test]
+    fn quad_tree_matches_brute_force(workload in workload(0..200), capacity in 1..16u16, max_depth in 0..12u32) {
+        let mut quad_tree = QuadTree::builder().bounds(workload.x.clone(), workload.y.clone()).capacity(capacity).max_depth(max_depth).build().unwrap();
+
+        check(&mut quad_tree, &mut BruteForce::new(), &workload.operations)?;
+    }
+
+    #[test]
+    fn linear_quad_tree_matches_brute_force(workload in workload(0..200)) {
+        check(&mut LinearQuadTree::new(workload.x.clone(), workload.y.clone()), &mut BruteForce::new(), &workload.operations)?;
+    }
+
+    #[test]
+    fn kd_tree_matches_brute_force(workload in workload(0..200)) {
+        check(&mut KdTree::new(workload.x.clone(), workload.y.clone()), &mut BruteForce::new(), &workload.operations)?;
+    }
+}
+
+#[test]
+fn failure_lists_the_operations_up_to_the_mismatch() {
+    let mut left = BruteForce::new();
+    left.insert_unchecked((0.0, 0.0), 100);
+
+    let operations = [Operation::Insert((0.5, 0.5)), Operation::Query(Query::Circle((0.5, 0.5), 1.0))];
+
+    let message = check(&mut left, &mut BruteForce::new(), &operations).unwrap_err().to_string();
+
+    assert!(message.contains("count differs after step 0"), "{}", message);
+    assert!(message.contains("Insert") && !message.contains("Query"), "{}", message);
+}
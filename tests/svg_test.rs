@@ -0,0 +1,85 @@
+#![cfg(feature = "svg")]
+
+use spatial_neighbors::SpatialIndexMut;
+use spatial_neighbors::grid::Grid;
+use spatial_neighbors::quad_tree::{QuadTree, QuadTreeNode};
+use spatial_neighbors::svg;
+
+fn occurrences(image: &str, class: &str) -> usize {
+    image.matches(&format!("class=\"{}\"", class)).count()
+}
+
+fn node_count(node: QuadTreeNode<usize>) -> usize {
+    1 + node.nodes().into_iter().flatten().map(node_count).sum::<usize>()
+}
+
+/// 6 x 6 cells of size 1 with 5 elements each, enough for the shortcuts of the cells
+fn filled_grid() -> Grid<usize> {
+    let mut grid = Grid::with_cell_count(0.0..6.0, 0.0..6.0, (6, 6));
+
+    for i in 0..36 * 5 {
+        let cell = i / 5;
+        grid.insert(((cell % 6) as f64 + 0.1 * (i % 5) as f64 + 0.3, (cell / 6) as f64 + 0.5), i);
+    }
+
+    grid
+}
+
+#[test]
+fn grid_cells_and_heat_map() {
+    let mut grid = filled_grid();
+    grid.insert((0.5, 0.5), 1000);
+
+    let image = svg::grid(&grid);
+
+    assert!(image.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"800\""));
+    assert!(image.ends_with("</svg>\n"));
+
+    // a line on every border between the cells and around the grid
+    assert_eq!(occurrences(&image, "cell"), 14);
+    assert_eq!(occurrences(&image, "load"), 36);
+    assert_eq!(image.matches("fill-opacity=\"0.90\"").count(), 1);
+    assert_eq!(occurrences(&image, "query"), 0);
+}
+
+#[test]
+fn grid_with_circle_shows_each_cell_the_query_reached() {
+    let image = svg::grid_with_circle(&filled_grid(), (3.0, 3.0), 2.1);
+
+    // the four cells around the center lie completely inside, the corners of the searched area outside
+    assert_eq!(occurrences(&image, "accepted"), 4);
+    assert_eq!(occurrences(&image, "tested"), 20);
+    assert_eq!(occurrences(&image, "rejected"), 12);
+    assert!(image.contains("<circle class=\"query\" cx=\"400.00\" cy=\"400.00\" r=\"280.00\"/>"));
+}
+
+#[test]
+fn grid_image_keeps_the_aspect_ratio() {
+    let grid: Grid<usize> = Grid::with_cell_count(-2.0..2.0, 10.0..11.0, (4, 1));
+
+    assert!(svg::grid(&grid).starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"200\""));
+}
+
+#[test]
+fn quad_tree_nodes_and_points() {
+    let mut quad_tree = QuadTree::with_capacity(0.0..8.0, 0.0..8.0, 4);
+
+    for i in 0..32 * 32 {
+        quad_tree.insert(((i % 32) as f64 * 0.25 + 0.125, (i / 32) as f64 * 0.25 + 0.125), i);
+    }
+
+    let image = svg::quad_tree(&quad_tree);
+
+    assert_eq!(occurrences(&image, "point"), 1024);
+    assert_eq!(occurrences(&image, "cell"), node_count(quad_tree.node()));
+
+    let image = svg::quad_tree_with_circle(&quad_tree, (2.0, 2.0), 1.5);
+
+    assert_eq!(occurrences(&image, "point"), 1024);
+    assert!(occurrences(&image, "tested") > 0);
+    assert!(occurrences(&image, "accepted") > 0);
+    assert!(occurrences(&image, "rejected") > 0);
+
+    // the first filled box is the root, which the query always reaches
+    assert!(image.contains("<rect class=\"tested\" x=\"0.00\" y=\"0.00\" width=\"800.00\" height=\"800.00\"/>"));
+}